@@ -0,0 +1,31 @@
+// Built-in `about:` pages rendered by the bridge instead of fetched through Zig.
+
+use crate::{download, history, palette};
+
+const BLANK_PAGE: &str = "<!DOCTYPE html><html><head><title>New Tab</title></head><body></body></html>";
+
+/// Render the built-in page for `url`, or `None` if it isn't an `about:` page we own.
+pub fn render(url: &str) -> Option<String> {
+    match url.trim_end_matches('/') {
+        palette::NEW_TAB_URL => Some(BLANK_PAGE.to_string()),
+        download::PAGE_URL => Some(download::render_downloads_page()),
+        history::PAGE_URL => Some(history::render_page(url)),
+        page if page.starts_with("about:history?") => Some(history::render_page(url)),
+        _ => None,
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
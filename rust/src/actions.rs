@@ -0,0 +1,217 @@
+// Declarative event handlers: elements marked `data-frontier-action="name"`
+// report clicks, input, changes and submits to the host instead of taking
+// their default action (following a link, submitting a form).
+//
+// Clicks and submits are decided before Blitz sees the event so the default
+// can be suppressed. Input and toggles are reported after Blitz has applied
+// them, since only then does the element hold its new value.
+
+use std::collections::BTreeMap;
+
+use blitz_dom::BaseDocument;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::forms::{checked, input_type, is_text_field, value};
+use crate::{dom, forms, host, overlay};
+
+pub const ACTION_ATTR: &str = "data-frontier-action";
+
+const EVENT_NAME: &str = "frontier-action";
+
+#[derive(Serialize)]
+struct ActionEvent {
+    window_id: u64,
+    action: String,
+    event: &'static str,
+    id: Option<String>,
+    tag: Option<String>,
+    dataset: BTreeMap<String, String>,
+    value: Option<String>,
+    checked: Option<bool>,
+    form: Option<BTreeMap<String, Value>>,
+}
+
+/// Work left for after Blitz has handled the event.
+pub enum Pending {
+    /// A checkbox, radio button or select was clicked.
+    Toggle(usize),
+    /// A key was typed into a text field.
+    Typing {
+        node_id: usize,
+        before: Option<String>,
+        enter: bool,
+    },
+}
+
+pub enum Outcome {
+    /// Not ours; let Blitz handle the event as usual.
+    Unhandled,
+    /// Reported to the host; don't pass the event on.
+    Handled,
+    /// Pass the event to Blitz, then call `after`.
+    Pending(Pending),
+}
+
+/// A left click released over `node_id`.
+pub fn before_click(doc: &BaseDocument, node_id: Option<usize>, window_id: u64) -> Outcome {
+    let Some(node_id) = node_id.filter(|node_id| !overlay::contains(doc, *node_id)) else {
+        return Outcome::Unhandled;
+    };
+
+    if is_submit_button(doc, node_id) {
+        if let Some(form) = action_form(doc, node_id) {
+            emit(doc, form, "submit", window_id);
+            return Outcome::Handled;
+        }
+    }
+
+    let Some(element) = action_element(doc, node_id) else {
+        return Outcome::Unhandled;
+    };
+    if is_toggle(doc, element) {
+        Outcome::Pending(Pending::Toggle(element))
+    } else if is_text_field(doc, element) || dom::tag_name(doc, element) == Some("form") {
+        // Clicking just focuses fields; forms only report submits
+        Outcome::Unhandled
+    } else {
+        emit(doc, element, "click", window_id);
+        Outcome::Handled
+    }
+}
+
+/// A key press about to be delivered to the focused element.
+pub fn before_key(doc: &BaseDocument, enter: bool, window_id: u64) -> Outcome {
+    let Some(focused) = doc.get_focussed_node_id() else {
+        return Outcome::Unhandled;
+    };
+    if !is_text_field(doc, focused) {
+        return Outcome::Unhandled;
+    }
+
+    // Enter in a single-line field submits its form
+    if enter && dom::tag_name(doc, focused) == Some("input") {
+        if let Some(form) = action_form(doc, focused) {
+            emit(doc, form, "submit", window_id);
+            return Outcome::Handled;
+        }
+    }
+
+    if dom::attr(doc, focused, ACTION_ATTR).is_none() {
+        return Outcome::Unhandled;
+    }
+    Outcome::Pending(Pending::Typing {
+        node_id: focused,
+        before: value(doc, focused),
+        enter,
+    })
+}
+
+pub fn after(doc: &BaseDocument, pending: Pending, window_id: u64) {
+    match pending {
+        Pending::Toggle(element) => emit(doc, element, "change", window_id),
+        Pending::Typing { node_id, before, enter } => {
+            if value(doc, node_id) != before {
+                emit(doc, node_id, "input", window_id);
+            }
+            if enter {
+                emit(doc, node_id, "change", window_id);
+            }
+        }
+    }
+}
+
+/// `node_id` or its closest ancestor with an action, stopping at overlays.
+fn action_element(doc: &BaseDocument, node_id: usize) -> Option<usize> {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        if dom::attr(doc, id, ACTION_ATTR).is_some() {
+            return Some(id);
+        }
+        if dom::attr(doc, id, overlay::OVERLAY_ATTR).is_some() {
+            return None;
+        }
+        current = doc.get_node(id)?.parent;
+    }
+    None
+}
+
+/// The enclosing form, if it has an action.
+fn action_form(doc: &BaseDocument, node_id: usize) -> Option<usize> {
+    dom::closest(doc, node_id, "form").filter(|form| dom::attr(doc, *form, ACTION_ATTR).is_some())
+}
+
+fn is_submit_button(doc: &BaseDocument, node_id: usize) -> bool {
+    let Some(button) = dom::closest(doc, node_id, "button") else {
+        return input_type(doc, node_id).as_deref() == Some("submit");
+    };
+    dom::attr(doc, button, "type").is_none_or(|kind| kind.eq_ignore_ascii_case("submit"))
+}
+
+fn is_toggle(doc: &BaseDocument, node_id: usize) -> bool {
+    dom::tag_name(doc, node_id) == Some("select")
+        || matches!(input_type(doc, node_id).as_deref(), Some("checkbox" | "radio"))
+}
+
+/// `data-*` attributes (other than the action itself), keyed like the DOM's
+/// `dataset`: `data-user-id` becomes `userId`.
+fn dataset(doc: &BaseDocument, node_id: usize) -> BTreeMap<String, String> {
+    let Some(element) = doc.get_node(node_id).and_then(|node| node.element_data()) else {
+        return BTreeMap::new();
+    };
+    element
+        .attrs()
+        .iter()
+        .filter_map(|attr| {
+            let name = attr.name.local.as_ref();
+            if name == ACTION_ATTR {
+                return None;
+            }
+            let key = name.strip_prefix("data-")?;
+            Some((camel_case(key), attr.value.clone()))
+        })
+        .collect()
+}
+
+fn camel_case(key: &str) -> String {
+    let mut out = String::with_capacity(key.len());
+    let mut upper = false;
+    for ch in key.chars() {
+        if ch == '-' {
+            upper = true;
+        } else if upper {
+            out.push(ch.to_ascii_uppercase());
+            upper = false;
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+fn emit(doc: &BaseDocument, element: usize, event: &'static str, window_id: u64) {
+    let Some(action) = dom::attr(doc, element, ACTION_ATTR) else {
+        return;
+    };
+    let form = if dom::tag_name(doc, element) == Some("form") {
+        Some(element)
+    } else {
+        dom::closest(doc, element, "form")
+    };
+
+    tracing::info!("Action {:?} ({})", action, event);
+    host::emit(
+        EVENT_NAME,
+        &ActionEvent {
+            window_id,
+            action: action.to_string(),
+            event,
+            id: dom::attr(doc, element, "id").map(str::to_owned),
+            tag: dom::tag_name(doc, element).map(str::to_owned),
+            dataset: dataset(doc, element),
+            value: value(doc, element),
+            checked: checked(doc, element),
+            form: form.map(|form| forms::submission(doc, form)),
+        },
+    );
+}
@@ -0,0 +1,39 @@
+//! Run browsing scenarios headlessly against the test host.
+//!
+//!     cargo run --features test-host --bin frontier-scenario -- scenarios/*.toml
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use frontier_blitz_bridge::scenario;
+
+fn main() -> ExitCode {
+    let paths: Vec<PathBuf> = std::env::args_os().skip(1).map(PathBuf::from).collect();
+    if paths.is_empty() {
+        eprintln!("usage: frontier-scenario <scenario.toml>...");
+        return ExitCode::from(2);
+    }
+
+    let mut failures = 0;
+    for path in &paths {
+        match scenario::run_file(path) {
+            Ok(report) => {
+                println!("{report}\n");
+                if !report.passed() {
+                    failures += 1;
+                }
+            }
+            Err(err) => {
+                println!("{}\n  ERROR: {err}\n", path.display());
+                failures += 1;
+            }
+        }
+    }
+
+    println!("{} of {} scenarios passed", paths.len() - failures, paths.len());
+    if failures == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
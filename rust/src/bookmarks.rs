@@ -0,0 +1,542 @@
+// Bookmarks, kept in `bookmarks.json` in the profile directory.
+//
+// Bookmarks and folders are one flat list linked by parent id, with siblings
+// in list order, so moving an item is a remove and an insert. Every change
+// rewrites the file atomically. Import and export use the Netscape bookmark
+// file format other browsers read and write.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{about, history, host, profile, Tab, COMMAND_PALETTE_URL};
+
+pub const FILE_NAME: &str = "bookmarks.json";
+
+/// The parent id of top-level bookmarks and folders.
+pub const ROOT: u64 = 0;
+
+const VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: u64,
+    pub parent: u64,
+    pub title: String,
+    /// `None` for folders.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Milliseconds since the Unix epoch.
+    pub added: i64,
+}
+
+impl Bookmark {
+    pub fn is_folder(&self) -> bool {
+        self.url.is_none()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BookmarkError {
+    NotFound(u64),
+    NotAFolder(u64),
+    /// Moving a folder into itself or one of its descendants.
+    Cycle(u64),
+}
+
+impl std::fmt::Display for BookmarkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BookmarkError::NotFound(id) => write!(f, "no bookmark with id {id}"),
+            BookmarkError::NotAFolder(id) => write!(f, "bookmark {id} is not a folder"),
+            BookmarkError::Cycle(id) => write!(f, "folder {id} can't be moved inside itself"),
+        }
+    }
+}
+
+impl std::error::Error for BookmarkError {}
+
+#[derive(Serialize, Deserialize)]
+struct File {
+    version: u32,
+    next_id: u64,
+    items: Vec<Bookmark>,
+}
+
+struct Store {
+    path: PathBuf,
+    next_id: u64,
+    items: Vec<Bookmark>,
+}
+
+impl Store {
+    fn get(&self, id: u64) -> Option<&Bookmark> {
+        self.items.iter().find(|item| item.id == id)
+    }
+
+    fn check_folder(&self, id: u64) -> Result<(), BookmarkError> {
+        if id == ROOT {
+            return Ok(());
+        }
+        match self.get(id) {
+            Some(item) if item.is_folder() => Ok(()),
+            Some(_) => Err(BookmarkError::NotAFolder(id)),
+            None => Err(BookmarkError::NotFound(id)),
+        }
+    }
+
+    /// Whether `id` is `ancestor` or somewhere inside it.
+    fn is_within(&self, mut id: u64, ancestor: u64) -> bool {
+        while id != ROOT {
+            if id == ancestor {
+                return true;
+            }
+            id = self.get(id).map_or(ROOT, |item| item.parent);
+        }
+        ancestor == ROOT
+    }
+
+    /// Append a new item under `parent`, which must already be checked.
+    fn push(&mut self, parent: u64, title: &str, url: Option<&str>, tags: &[String]) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.items.push(Bookmark {
+            id,
+            parent,
+            title: title.to_string(),
+            url: url.map(str::to_string),
+            tags: normalize_tags(tags),
+            added: history::now(),
+        });
+        id
+    }
+
+    /// List index to insert at so the item becomes child `index` of
+    /// `parent`, or its last child.
+    fn insertion_point(&self, parent: u64, index: Option<usize>) -> usize {
+        let siblings: Vec<usize> = (0..self.items.len())
+            .filter(|&position| self.items[position].parent == parent)
+            .collect();
+        match index.and_then(|index| siblings.get(index)) {
+            Some(&position) => position,
+            None => siblings.last().map_or(self.items.len(), |&last| last + 1),
+        }
+    }
+
+    fn save(&self) {
+        let file = File {
+            version: VERSION,
+            next_id: self.next_id,
+            items: self.items.clone(),
+        };
+        let result = serde_json::to_vec_pretty(&file)
+            .map_err(io::Error::other)
+            .and_then(|json| profile::write_atomic(&self.path, &json));
+        if let Err(err) = result {
+            tracing::error!("Failed to save bookmarks to {}: {err}", self.path.display());
+        }
+    }
+}
+
+/// The store for the current profile directory, reloaded if it changed.
+fn store() -> MutexGuard<'static, Store> {
+    static STORE: OnceLock<Mutex<Store>> = OnceLock::new();
+    let mut store = STORE
+        .get_or_init(|| {
+            Mutex::new(Store {
+                path: PathBuf::new(),
+                next_id: ROOT + 1,
+                items: Vec::new(),
+            })
+        })
+        .lock()
+        .unwrap();
+
+    let path = profile::path(FILE_NAME);
+    if store.path != path {
+        let file = load(&path);
+        store.next_id = file.next_id.max(ROOT + 1);
+        store.items = file.items;
+        store.path = path;
+    }
+    store
+}
+
+fn load(path: &Path) -> File {
+    let empty = File {
+        version: VERSION,
+        next_id: ROOT + 1,
+        items: Vec::new(),
+    };
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return empty,
+        Err(err) => {
+            tracing::warn!("Failed to read {}: {err}", path.display());
+            return empty;
+        }
+    };
+    match serde_json::from_slice::<File>(&contents) {
+        Ok(file) if file.version == VERSION => file,
+        Ok(file) => {
+            tracing::warn!("Ignoring bookmarks from unsupported version {}", file.version);
+            empty
+        }
+        Err(err) => {
+            tracing::warn!("Ignoring unreadable bookmarks {}: {err}", path.display());
+            empty
+        }
+    }
+}
+
+fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+/// Split a comma-separated tag list, as used by the FFI and Netscape files.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    normalize_tags(&text.split(',').map(str::to_string).collect::<Vec<_>>())
+}
+
+/// Bookmark `url` as the last child of folder `parent`. Returns its id.
+pub fn add(parent: u64, title: &str, url: &str, tags: &[String]) -> Result<u64, BookmarkError> {
+    let mut store = store();
+    store.check_folder(parent)?;
+    let id = store.push(parent, title, Some(url), tags);
+    store.save();
+    Ok(id)
+}
+
+/// Create an empty folder as the last child of `parent`. Returns its id.
+pub fn add_folder(parent: u64, title: &str) -> Result<u64, BookmarkError> {
+    let mut store = store();
+    store.check_folder(parent)?;
+    let id = store.push(parent, title, None, &[]);
+    store.save();
+    Ok(id)
+}
+
+/// Remove a bookmark, or a folder and everything in it. Returns how many
+/// items were removed.
+pub fn remove(id: u64) -> Result<usize, BookmarkError> {
+    let mut store = store();
+    if id == ROOT || store.get(id).is_none() {
+        return Err(BookmarkError::NotFound(id));
+    }
+    let doomed: Vec<u64> = store
+        .items
+        .iter()
+        .filter(|item| store.is_within(item.id, id))
+        .map(|item| item.id)
+        .collect();
+    store.items.retain(|item| !doomed.contains(&item.id));
+    store.save();
+    Ok(doomed.len())
+}
+
+/// Move `id` into folder `parent` at position `index` among its children,
+/// or to the end if `index` is `None` or past the last child.
+pub fn move_to(id: u64, parent: u64, index: Option<usize>) -> Result<(), BookmarkError> {
+    let mut store = store();
+    let Some(position) = store.items.iter().position(|item| item.id == id) else {
+        return Err(BookmarkError::NotFound(id));
+    };
+    store.check_folder(parent)?;
+    if store.is_within(parent, id) {
+        return Err(BookmarkError::Cycle(id));
+    }
+
+    let mut item = store.items.remove(position);
+    item.parent = parent;
+    let at = store.insertion_point(parent, index);
+    store.items.insert(at, item);
+    store.save();
+    Ok(())
+}
+
+/// Replace a bookmark's tags.
+pub fn set_tags(id: u64, tags: &[String]) -> Result<(), BookmarkError> {
+    let mut store = store();
+    let Some(item) = store.items.iter_mut().find(|item| item.id == id) else {
+        return Err(BookmarkError::NotFound(id));
+    };
+    item.tags = normalize_tags(tags);
+    store.save();
+    Ok(())
+}
+
+pub fn get(id: u64) -> Option<Bookmark> {
+    store().get(id).cloned()
+}
+
+/// The direct children of folder `parent`, in order.
+pub fn children(parent: u64) -> Result<Vec<Bookmark>, BookmarkError> {
+    let store = store();
+    store.check_folder(parent)?;
+    Ok(store.items.iter().filter(|item| item.parent == parent).cloned().collect())
+}
+
+/// Every bookmark and folder, parents before their children.
+pub fn all() -> Vec<Bookmark> {
+    let store = store();
+    let mut ordered = Vec::with_capacity(store.items.len());
+    let mut stack = vec![ROOT];
+    // Depth-first so each folder is followed by its contents
+    while let Some(parent) = stack.pop() {
+        let children: Vec<&Bookmark> = store.items.iter().filter(|item| item.parent == parent).collect();
+        for child in children.iter().rev() {
+            if child.is_folder() {
+                stack.push(child.id);
+            }
+        }
+        ordered.extend(children.into_iter().cloned());
+    }
+    ordered
+}
+
+/// Bookmarks (not folders) matching every word of `query`, ignoring case.
+/// Plain words match the title, URL or a tag; `#word` only matches a tag
+/// exactly. An empty query matches every bookmark.
+pub fn search(query: &str, limit: usize) -> Vec<Bookmark> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    store()
+        .items
+        .iter()
+        .filter(|item| !item.is_folder())
+        .filter(|item| {
+            let haystack = format!("{} {}", item.title, item.url.as_deref().unwrap_or_default()).to_lowercase();
+            words.iter().all(|word| match word.strip_prefix('#') {
+                Some(tag) => item.tags.iter().any(|own| own == tag),
+                None => haystack.contains(word.as_str()) || item.tags.iter().any(|tag| tag.contains(word.as_str())),
+            })
+        })
+        .take(limit)
+        .cloned()
+        .collect()
+}
+
+/// The first bookmark for `url`, if there is one.
+pub fn find_url(url: &str) -> Option<Bookmark> {
+    store().items.iter().find(|item| item.url.as_deref() == Some(url)).cloned()
+}
+
+#[derive(Serialize)]
+struct BookmarkAddedEvent {
+    window_id: u64,
+    id: u64,
+    url: String,
+    title: String,
+    /// The page was already bookmarked, so nothing was added.
+    existing: bool,
+}
+
+/// Bookmark the page `tab` is showing, for Ctrl/Cmd+D. Pages that are
+/// already bookmarked aren't added twice. Returns the bookmark's id.
+pub(crate) fn bookmark_tab(tab: &Tab, window_id: u64) -> Option<u64> {
+    let (url, title) = {
+        let state = tab.state.lock().unwrap();
+        (state.current_url.clone(), history::title_of(&state.current_html))
+    };
+    if url.is_empty() || url == COMMAND_PALETTE_URL {
+        return None;
+    }
+
+    let (id, existing) = match find_url(&url) {
+        Some(bookmark) => (bookmark.id, true),
+        None => {
+            let name = if title.is_empty() { url.as_str() } else { title.as_str() };
+            match add(ROOT, name, &url, &[]) {
+                Ok(id) => (id, false),
+                Err(err) => {
+                    tracing::error!("Failed to bookmark {}: {err}", url);
+                    return None;
+                }
+            }
+        }
+    };
+    tracing::info!("Bookmarked {} as {}", url, id);
+    host::emit(
+        "bookmark-added",
+        &BookmarkAddedEvent {
+            window_id,
+            id,
+            url,
+            title,
+            existing,
+        },
+    );
+    Some(id)
+}
+
+/// Every bookmark in the Netscape bookmark file format.
+pub fn export_html() -> String {
+    let store = store();
+    let mut out = String::from(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>Bookmarks</TITLE>\n\
+         <H1>Bookmarks</H1>\n",
+    );
+    export_folder(&store, ROOT, 0, &mut out);
+    out
+}
+
+fn export_folder(store: &Store, parent: u64, depth: usize, out: &mut String) {
+    let indent = "    ".repeat(depth);
+    out.push_str(&format!("{indent}<DL><p>\n"));
+    for item in store.items.iter().filter(|item| item.parent == parent) {
+        let add_date = item.added / 1000;
+        match &item.url {
+            Some(url) => {
+                let tags = if item.tags.is_empty() {
+                    String::new()
+                } else {
+                    format!(" TAGS=\"{}\"", about::escape_html(&item.tags.join(",")))
+                };
+                out.push_str(&format!(
+                    "{indent}    <DT><A HREF=\"{}\" ADD_DATE=\"{add_date}\"{tags}>{}</A>\n",
+                    about::escape_html(url),
+                    about::escape_html(&item.title),
+                ));
+            }
+            None => {
+                out.push_str(&format!(
+                    "{indent}    <DT><H3 ADD_DATE=\"{add_date}\">{}</H3>\n",
+                    about::escape_html(&item.title),
+                ));
+                export_folder(store, item.id, depth + 1, out);
+            }
+        }
+    }
+    out.push_str(&format!("{indent}</DL><p>\n"));
+}
+
+/// Import a Netscape bookmark file into folder `parent`, keeping its folder
+/// structure. Returns how many bookmarks (not folders) were added.
+pub fn import_html(html: &str, parent: u64) -> Result<usize, BookmarkError> {
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    let tag = TAG.get_or_init(|| Regex::new(r"(?is)<(/?)([a-z0-9]+)([^>]*)>").unwrap());
+
+    let mut store = store();
+    store.check_folder(parent)?;
+
+    // The folder each open <DL> adds to. The outermost list is `parent`
+    let mut folders: Vec<u64> = Vec::new();
+    // A folder heading seen but whose <DL> hasn't opened yet
+    let mut pending_folder: Option<u64> = None;
+    // The <A> or <H3> whose text runs up to its end tag: name, attributes
+    // and where the text starts
+    let mut open_item: Option<(String, String, usize)> = None;
+    let mut imported = 0;
+    for captures in tag.captures_iter(html) {
+        let whole = captures.get(0).unwrap();
+        let closing = !captures[1].is_empty();
+        let name = captures[2].to_ascii_lowercase();
+        let current = folders.last().copied().unwrap_or(parent);
+
+        match (name.as_str(), closing) {
+            ("dl", false) => {
+                folders.push(pending_folder.take().unwrap_or(current));
+            }
+            ("dl", true) => {
+                folders.pop();
+            }
+            ("h3", false) | ("a", false) => {
+                open_item = Some((name, captures[3].to_string(), whole.end()));
+            }
+            ("h3", true) | ("a", true) => {
+                let Some((open_name, attrs, text_start)) = open_item.take_if(|(open_name, ..)| *open_name == name) else {
+                    continue;
+                };
+                let text = decode_entities(html[text_start..whole.start()].trim());
+                if open_name == "h3" {
+                    pending_folder = Some(store.push(current, &text, None, &[]));
+                    continue;
+                }
+                let Some(href) = attribute(&attrs, "href") else {
+                    continue;
+                };
+                let title = if text.is_empty() { href.clone() } else { text };
+                let tags = attribute(&attrs, "tags").map(|tags| parse_tags(&tags)).unwrap_or_default();
+                let id = store.push(current, &title, Some(&href), &tags);
+                if let Some(added) = attribute(&attrs, "add_date").and_then(|date| date.parse::<i64>().ok()) {
+                    if let Some(item) = store.items.iter_mut().find(|item| item.id == id) {
+                        item.added = added * 1000;
+                    }
+                }
+                imported += 1;
+            }
+            _ => {}
+        }
+    }
+
+    store.save();
+    tracing::info!("Imported {} bookmarks", imported);
+    Ok(imported)
+}
+
+/// The value of attribute `name` in the inside of a start tag.
+fn attribute(attrs: &str, name: &str) -> Option<String> {
+    static ATTR: OnceLock<Regex> = OnceLock::new();
+    let attr = ATTR.get_or_init(|| Regex::new(r#"(?i)([a-z_:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#).unwrap());
+    attr.captures_iter(attrs)
+        .find(|captures| captures[1].eq_ignore_ascii_case(name))
+        .and_then(|captures| captures.get(2).or(captures.get(3)).or(captures.get(4)))
+        .map(|value| decode_entities(value.as_str()))
+}
+
+/// Decode the named entities bookmark files use and numeric ones like
+/// `&#8211;` and `&#x2013;`. Anything else is left as written.
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..].find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..=end];
+            let ch = match entity {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => '\u{a0}',
+                _ => {
+                    let number = entity.strip_prefix('#')?;
+                    let code = match number.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => number.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((ch, end + 2))
+        });
+        match decoded {
+            Some((ch, len)) => {
+                out.push(ch);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,173 @@
+// Run work against the live `FrontierApplication` from FFI calls.
+//
+// The host's run call blocks inside winit's event loop, so other FFI entry
+// points can't touch the application directly. They queue a closure here and
+// wake the loop; `user_event` drains the queue on the loop thread.
+//
+// The headless `driver` has no event loop; the thread it runs on stands in
+// for the loop thread and its commands are drained after every event.
+
+#[cfg(feature = "test-host")]
+use std::cell::RefCell;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::ThreadId;
+use std::time::Duration;
+
+use blitz_shell::BlitzShellEvent;
+use winit::event_loop::EventLoopProxy;
+
+use crate::FrontierApplication;
+
+type Command = Box<dyn FnOnce(&mut FrontierApplication) + Send>;
+
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct LoopHandle {
+    proxy: EventLoopProxy<BlitzShellEvent>,
+    thread: ThreadId,
+}
+
+static HANDLE: Mutex<Option<LoopHandle>> = Mutex::new(None);
+static QUEUE: Mutex<Vec<Command>> = Mutex::new(Vec::new());
+
+#[cfg(feature = "test-host")]
+#[derive(Default)]
+struct Headless {
+    drivers: usize,
+    queue: Vec<Command>,
+}
+
+#[cfg(feature = "test-host")]
+thread_local! {
+    static HEADLESS: RefCell<Headless> = RefCell::new(Headless::default());
+}
+
+/// Called on the loop thread right before the event loop starts.
+pub(crate) fn attach(proxy: EventLoopProxy<BlitzShellEvent>) {
+    *HANDLE.lock().unwrap() = Some(LoopHandle {
+        proxy,
+        thread: std::thread::current().id(),
+    });
+}
+
+pub(crate) fn detach() {
+    *HANDLE.lock().unwrap() = None;
+    QUEUE.lock().unwrap().clear();
+}
+
+/// Treat the current thread as the loop thread of a headless application
+/// until the matching `detach_headless`.
+#[cfg(feature = "test-host")]
+pub(crate) fn attach_headless() {
+    HEADLESS.with(|headless| headless.borrow_mut().drivers += 1);
+}
+
+#[cfg(feature = "test-host")]
+pub(crate) fn detach_headless() {
+    HEADLESS.with(|headless| {
+        let mut headless = headless.borrow_mut();
+        headless.drivers -= 1;
+        if headless.drivers == 0 {
+            headless.queue.clear();
+        }
+    });
+}
+
+/// Whether there is an application to run commands against.
+pub(crate) fn running() -> bool {
+    #[cfg(feature = "test-host")]
+    if HEADLESS.with(|headless| headless.borrow().drivers > 0) {
+        return true;
+    }
+    HANDLE.lock().unwrap().is_some()
+}
+
+fn on_loop_thread() -> bool {
+    #[cfg(feature = "test-host")]
+    if HEADLESS.with(|headless| headless.borrow().drivers > 0) {
+        return true;
+    }
+    HANDLE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|handle| handle.thread == std::thread::current().id())
+}
+
+pub(crate) fn drain(app: &mut FrontierApplication) {
+    #[cfg(feature = "test-host")]
+    for command in HEADLESS.with(|headless| std::mem::take(&mut headless.borrow_mut().queue)) {
+        command(app);
+    }
+    let commands = std::mem::take(&mut *QUEUE.lock().unwrap());
+    for command in commands {
+        command(app);
+    }
+}
+
+/// Queue `f` without waiting for it to run. Safe to call from the loop thread
+/// (e.g. from inside a host event callback).
+pub(crate) fn post(caller: &str, f: impl FnOnce(&mut FrontierApplication) + Send + 'static) -> bool {
+    #[cfg(feature = "test-host")]
+    if HEADLESS.with(|headless| headless.borrow().drivers > 0) {
+        HEADLESS.with(|headless| headless.borrow_mut().queue.push(Box::new(f)));
+        return true;
+    }
+
+    let handle = HANDLE.lock().unwrap();
+    let Some(handle) = handle.as_ref() else {
+        tracing::error!("{caller} called while no window is running");
+        return false;
+    };
+
+    QUEUE.lock().unwrap().push(Box::new(f));
+    let _ = handle.proxy.send_event(BlitzShellEvent::Embedder(Arc::new(())));
+    true
+}
+
+/// Run `f` on the loop thread and wait for its result.
+///
+/// Must be called from a thread other than the one running the event loop,
+/// since that thread is the one that would have to run `f`.
+pub(crate) fn call<R: Send + 'static>(
+    caller: &str,
+    f: impl FnOnce(&mut FrontierApplication) -> R + Send + 'static,
+) -> Option<R> {
+    if on_loop_thread() {
+        tracing::error!("{caller} can't wait for a result on the event loop thread; call it from another thread");
+        return None;
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    if !post(caller, move |app| {
+        let _ = sender.send(f(app));
+    }) {
+        return None;
+    }
+
+    match receiver.recv_timeout(REPLY_TIMEOUT) {
+        Ok(result) => Some(result),
+        Err(err) => {
+            tracing::error!("{caller} timed out waiting for the event loop: {err}");
+            None
+        }
+    }
+}
+
+/// Run `f` like `call` does, except on the loop thread (e.g. from inside a
+/// host event callback), where `f` is queued to run once control is back in
+/// the event loop and `queued` is returned in place of its result.
+pub(crate) fn call_or_post<R: Send + 'static>(
+    caller: &str,
+    queued: R,
+    f: impl FnOnce(&mut FrontierApplication) -> R + Send + 'static,
+) -> Option<R> {
+    if !on_loop_thread() {
+        return call(caller, f);
+    }
+    post(caller, move |app| {
+        let _ = f(app);
+    })
+    .then_some(queued)
+}
@@ -0,0 +1,97 @@
+// Bridge-wide settings the host can change before (or while) the event loop runs.
+
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+/// Where links that ask for a new browsing context (`target="_blank"`,
+/// middle-click, Ctrl/Cmd+click) are opened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NewWindowPolicy {
+    NewTab,
+    NewWindow,
+    CurrentWindow,
+}
+
+impl NewWindowPolicy {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::NewTab),
+            1 => Some(Self::NewWindow),
+            2 => Some(Self::CurrentWindow),
+            _ => None,
+        }
+    }
+}
+
+pub struct BridgeConfig {
+    pub profile_dir: PathBuf,
+    pub download_dir: PathBuf,
+    pub new_window_policy: NewWindowPolicy,
+    pub show_status_bar: bool,
+    /// `<script type>` values reported to the host when a document loads
+    pub script_types: Vec<String>,
+    /// Snapshot the session periodically and reopen it on launch
+    pub restore_session: bool,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        Self {
+            profile_dir: default_profile_dir(),
+            download_dir: default_download_dir(),
+            new_window_policy: NewWindowPolicy::NewTab,
+            show_status_bar: true,
+            script_types: vec!["text/typescript".to_string()],
+            restore_session: true,
+        }
+    }
+}
+
+// Test-host builds run under `cargo test` and never touch the user's real
+// profile or downloads
+#[cfg(feature = "test-host")]
+fn default_profile_dir() -> PathBuf {
+    crate::test_host::thread_dir().join("profile")
+}
+
+#[cfg(feature = "test-host")]
+fn default_download_dir() -> PathBuf {
+    crate::test_host::thread_dir().join("downloads")
+}
+
+#[cfg(not(feature = "test-host"))]
+fn default_profile_dir() -> PathBuf {
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(".frontier-zig"),
+        None => std::env::temp_dir().join("frontier-zig-profile"),
+    }
+}
+
+#[cfg(not(feature = "test-host"))]
+fn default_download_dir() -> PathBuf {
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join("Downloads"),
+        None => std::env::temp_dir().join("frontier-downloads"),
+    }
+}
+
+pub fn get() -> MutexGuard<'static, BridgeConfig> {
+    config().lock().unwrap()
+}
+
+#[cfg(not(feature = "test-host"))]
+fn config() -> &'static Mutex<BridgeConfig> {
+    static CONFIG: std::sync::OnceLock<Mutex<BridgeConfig>> = std::sync::OnceLock::new();
+    CONFIG.get_or_init(|| Mutex::new(BridgeConfig::default()))
+}
+
+// Tests run in parallel on their own threads, and the headless driver runs
+// the event loop on the test's thread, so test-host builds keep settings per
+// thread. One test changing the profile or policy can't affect another.
+#[cfg(feature = "test-host")]
+fn config() -> &'static Mutex<BridgeConfig> {
+    thread_local! {
+        static CONFIG: &'static Mutex<BridgeConfig> = Box::leak(Box::new(Mutex::new(BridgeConfig::default())));
+    }
+    CONFIG.with(|config| *config)
+}
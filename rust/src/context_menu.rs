@@ -0,0 +1,293 @@
+// Right-click context menu drawn as a bridge overlay.
+//
+// Built-in actions are handled by `FrontierApplication`; hosts can add their
+// own items, which are reported back through a `context-menu-action` event
+// carrying the id they registered.
+
+use std::sync::{Mutex, OnceLock};
+
+use blitz_dom::BaseDocument;
+use serde::Serialize;
+
+use crate::{dom, overlay};
+
+pub const OVERLAY_NAME: &str = "context-menu";
+
+const ACTION_ATTR: &str = "data-frontier-menu-action";
+
+const MENU_STYLE: &str = "position: fixed; min-width: 200px; padding: 4px 0; background: white; \
+    border: 1px solid #cbd5e1; border-radius: 6px; box-shadow: 0 8px 24px rgba(15, 23, 42, 0.18); \
+    font: 13px -apple-system, sans-serif; color: #0f172a; z-index: 2147483647;";
+const ITEM_STYLE: &str = "padding: 6px 16px; cursor: default;";
+const DISABLED_ITEM_STYLE: &str = "padding: 6px 16px; color: #94a3b8;";
+const SEPARATOR_STYLE: &str = "height: 1px; margin: 4px 0; background: #e5e7eb;";
+
+/// Which kind of element a menu item applies to (bit flags, shared with the host).
+pub const CONTEXT_PAGE: u8 = 1;
+pub const CONTEXT_LINK: u8 = 2;
+pub const CONTEXT_IMAGE: u8 = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MenuAction {
+    OpenLinkInNewTab,
+    CopyLinkAddress,
+    CopyImageUrl,
+    ViewSource,
+    Back,
+    Forward,
+    Reload,
+    InspectElement,
+    Host(String),
+}
+
+impl MenuAction {
+    fn key(&self) -> String {
+        match self {
+            MenuAction::OpenLinkInNewTab => "open-link-new-tab".into(),
+            MenuAction::CopyLinkAddress => "copy-link".into(),
+            MenuAction::CopyImageUrl => "copy-image-url".into(),
+            MenuAction::ViewSource => "view-source".into(),
+            MenuAction::Back => "back".into(),
+            MenuAction::Forward => "forward".into(),
+            MenuAction::Reload => "reload".into(),
+            MenuAction::InspectElement => "inspect".into(),
+            MenuAction::Host(id) => format!("host:{id}"),
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Some(match key {
+            "open-link-new-tab" => MenuAction::OpenLinkInNewTab,
+            "copy-link" => MenuAction::CopyLinkAddress,
+            "copy-image-url" => MenuAction::CopyImageUrl,
+            "view-source" => MenuAction::ViewSource,
+            "back" => MenuAction::Back,
+            "forward" => MenuAction::Forward,
+            "reload" => MenuAction::Reload,
+            "inspect" => MenuAction::InspectElement,
+            other => MenuAction::Host(other.strip_prefix("host:")?.to_string()),
+        })
+    }
+}
+
+/// What was under the cursor when the menu was opened.
+#[derive(Clone, Debug, Serialize)]
+pub struct ContextTarget {
+    pub node_id: Option<usize>,
+    pub page_url: String,
+    pub link_url: Option<String>,
+    pub image_url: Option<String>,
+}
+
+impl ContextTarget {
+    pub fn from_hit(doc: &BaseDocument, node_id: Option<usize>, page_url: &str) -> Self {
+        let link_url = node_id
+            .and_then(|id| dom::enclosing_link(doc, id, page_url))
+            .map(|link| link.href);
+        let image_url = node_id
+            .and_then(|id| dom::closest(doc, id, "img"))
+            .and_then(|img| dom::attr(doc, img, "src"))
+            .map(|src| dom::resolve_url(page_url, src));
+
+        Self {
+            node_id,
+            page_url: page_url.to_string(),
+            link_url,
+            image_url,
+        }
+    }
+
+    fn contexts(&self) -> u8 {
+        let mut contexts = CONTEXT_PAGE;
+        if self.link_url.is_some() {
+            contexts |= CONTEXT_LINK;
+        }
+        if self.image_url.is_some() {
+            contexts |= CONTEXT_IMAGE;
+        }
+        contexts
+    }
+}
+
+struct HostMenuItem {
+    id: String,
+    label: String,
+    contexts: u8,
+}
+
+fn host_items() -> &'static Mutex<Vec<HostMenuItem>> {
+    static ITEMS: OnceLock<Mutex<Vec<HostMenuItem>>> = OnceLock::new();
+    ITEMS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Add (or relabel) a host item shown when the target matches `contexts`.
+pub fn register_host_item(id: &str, label: &str, contexts: u8) {
+    let mut items = host_items().lock().unwrap();
+    items.retain(|item| item.id != id);
+    items.push(HostMenuItem {
+        id: id.to_string(),
+        label: label.to_string(),
+        contexts,
+    });
+}
+
+pub fn unregister_host_item(id: &str) -> bool {
+    let mut items = host_items().lock().unwrap();
+    let before = items.len();
+    items.retain(|item| item.id != id);
+    items.len() != before
+}
+
+enum Entry {
+    Item {
+        action: MenuAction,
+        label: String,
+        enabled: bool,
+    },
+    Separator,
+}
+
+fn entries(target: &ContextTarget, can_go_back: bool, can_go_forward: bool) -> Vec<Entry> {
+    let item = |action, label: &str, enabled| Entry::Item {
+        action,
+        label: label.to_string(),
+        enabled,
+    };
+
+    let mut entries = Vec::new();
+    if target.link_url.is_some() {
+        entries.push(item(MenuAction::OpenLinkInNewTab, "Open Link in New Tab", true));
+        entries.push(item(MenuAction::CopyLinkAddress, "Copy Link Address", true));
+        entries.push(Entry::Separator);
+    }
+    if target.image_url.is_some() {
+        entries.push(item(MenuAction::CopyImageUrl, "Copy Image Address", true));
+        entries.push(Entry::Separator);
+    }
+
+    entries.push(item(MenuAction::Back, "Back", can_go_back));
+    entries.push(item(MenuAction::Forward, "Forward", can_go_forward));
+    entries.push(item(MenuAction::Reload, "Reload", true));
+    entries.push(Entry::Separator);
+    entries.push(item(MenuAction::ViewSource, "View Page Source", true));
+    entries.push(item(MenuAction::InspectElement, "Inspect Element", target.node_id.is_some()));
+
+    let contexts = target.contexts();
+    let registered = host_items().lock().unwrap();
+    let mut host_entries = registered
+        .iter()
+        .filter(|item| item.contexts & contexts != 0)
+        .peekable();
+    if host_entries.peek().is_some() {
+        entries.push(Entry::Separator);
+        for host_item in host_entries {
+            entries.push(item(MenuAction::Host(host_item.id.clone()), &host_item.label, true));
+        }
+    }
+
+    entries
+}
+
+/// Draw the menu with its top-left corner at (`x`, `y`) in viewport CSS
+/// pixels. Where it would run past the right or bottom edge it opens to the
+/// left of or above that point instead, staying inside the viewport.
+pub fn show(
+    doc: &mut BaseDocument,
+    target: &ContextTarget,
+    x: f64,
+    y: f64,
+    can_go_back: bool,
+    can_go_forward: bool,
+) {
+    let entries = entries(target, can_go_back, can_go_forward);
+    let style = |x: f64, y: f64| format!("{MENU_STYLE} left: {x:.0}px; top: {y:.0}px;");
+
+    let mounted = overlay::mount(doc, OVERLAY_NAME, &style(x, y), |mutator, menu| {
+        for entry in &entries {
+            match entry {
+                Entry::Item { action, label, enabled: true } => {
+                    let key = action.key();
+                    overlay::append_element(
+                        mutator,
+                        menu,
+                        "div",
+                        &[(ACTION_ATTR, &key), ("style", ITEM_STYLE)],
+                        Some(label),
+                    );
+                }
+                Entry::Item { label, enabled: false, .. } => {
+                    overlay::append_element(
+                        mutator,
+                        menu,
+                        "div",
+                        &[("style", DISABLED_ITEM_STYLE)],
+                        Some(label),
+                    );
+                }
+                Entry::Separator => {
+                    overlay::append_element(mutator, menu, "div", &[("style", SEPARATOR_STYLE)], None);
+                }
+            }
+        }
+    });
+    let Some(menu) = mounted else {
+        return;
+    };
+
+    // Its size is only known once it's laid out
+    doc.resolve(0.0);
+    let Some(size) = doc.get_node(menu).map(|node| node.final_layout.size) else {
+        return;
+    };
+    let (width, height) = {
+        let viewport = doc.viewport();
+        let scale = viewport.scale_f64() * viewport.zoom() as f64;
+        (viewport.window_size.0 as f64 / scale, viewport.window_size.1 as f64 / scale)
+    };
+    let placed = (place(x, size.width as f64, width), place(y, size.height as f64, height));
+    if placed != (x, y) {
+        doc.mutate().set_attribute(menu, overlay::attr_name("style"), &style(placed.0, placed.1));
+    }
+}
+
+/// Where a menu `size` long that opens at `at` starts along an axis of the
+/// viewport `limit` long: at `at` if it fits, otherwise ending there, and
+/// never before 0.
+fn place(at: f64, size: f64, limit: f64) -> f64 {
+    if at + size <= limit {
+        at
+    } else {
+        (at - size).min(limit - size).max(0.0)
+    }
+}
+
+pub fn hide(doc: &mut BaseDocument) {
+    overlay::remove(doc, OVERLAY_NAME);
+}
+
+/// The action for the menu item containing `node_id`, if any.
+pub fn action_at(doc: &BaseDocument, node_id: usize) -> Option<MenuAction> {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        if let Some(key) = dom::attr(doc, id, ACTION_ATTR) {
+            return MenuAction::from_key(key);
+        }
+        if dom::attr(doc, id, overlay::OVERLAY_ATTR).is_some() {
+            return None;
+        }
+        current = doc.get_node(id).and_then(|node| node.parent);
+    }
+    None
+}
+
+/// Whether `node_id` is inside the context menu.
+pub fn contains(doc: &BaseDocument, node_id: usize) -> bool {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        if dom::attr(doc, id, overlay::OVERLAY_ATTR) == Some(OVERLAY_NAME) {
+            return true;
+        }
+        current = doc.get_node(id).and_then(|node| node.parent);
+    }
+    false
+}
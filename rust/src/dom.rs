@@ -0,0 +1,64 @@
+// Small helpers for reading Blitz's DOM from bridge code.
+
+use blitz_dom::BaseDocument;
+use winit::dpi::PhysicalPosition;
+
+/// Look up an attribute on an element node by its local name.
+pub fn attr<'a>(doc: &'a BaseDocument, node_id: usize, name: &str) -> Option<&'a str> {
+    let element = doc.get_node(node_id)?.element_data()?;
+    element
+        .attrs()
+        .iter()
+        .find(|attr| attr.name.local.as_ref() == name)
+        .map(|attr| attr.value.as_str())
+}
+
+pub fn tag_name(doc: &BaseDocument, node_id: usize) -> Option<&str> {
+    let element = doc.get_node(node_id)?.element_data()?;
+    Some(element.name.local.as_ref())
+}
+
+/// Walk from `node_id` up through its ancestors, returning the first element
+/// with the given tag name.
+pub fn closest(doc: &BaseDocument, node_id: usize, tag: &str) -> Option<usize> {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        if tag_name(doc, id).is_some_and(|name| name.eq_ignore_ascii_case(tag)) {
+            return Some(id);
+        }
+        current = doc.get_node(id)?.parent;
+    }
+    None
+}
+
+/// Convert a window cursor position into document coordinates and hit-test it.
+pub fn hit_node(doc: &BaseDocument, position: PhysicalPosition<f64>, scale_factor: f64) -> Option<usize> {
+    let logical = position.to_logical::<f64>(scale_factor);
+    let zoom = doc.viewport().zoom() as f64;
+    let scroll = doc.viewport_scroll();
+    let x = logical.x / zoom + scroll.x;
+    let y = logical.y / zoom + scroll.y;
+    doc.hit(x as f32, y as f32).map(|hit| hit.node_id)
+}
+
+pub struct Link {
+    pub node_id: usize,
+    pub href: String,
+}
+
+/// Find the anchor enclosing `node_id` and resolve its `href` against `base_url`.
+pub fn enclosing_link(doc: &BaseDocument, node_id: usize, base_url: &str) -> Option<Link> {
+    let anchor = closest(doc, node_id, "a")?;
+    let raw_href = attr(doc, anchor, "href")?;
+    Some(Link {
+        node_id: anchor,
+        href: resolve_url(base_url, raw_href),
+    })
+}
+
+pub fn resolve_url(base_url: &str, raw: &str) -> String {
+    url::Url::parse(base_url)
+        .and_then(|base| base.join(raw))
+        .map(|url| url.to_string())
+        .unwrap_or_else(|_| raw.to_string())
+}
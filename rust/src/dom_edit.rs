@@ -0,0 +1,101 @@
+// Host-requested edits to the live DOM, addressed by CSS selector.
+//
+// Edits go through Blitz's mutator, which marks the touched nodes dirty so
+// the next frame restyles and relays out only what changed.
+
+use std::fmt;
+
+use blitz_dom::BaseDocument;
+
+use crate::{dom, overlay};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Edit {
+    SetText(String),
+    SetAttribute { name: String, value: String },
+    RemoveAttribute(String),
+    SetInnerHtml(String),
+    AddClass(String),
+    RemoveClass(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidSelector(pub String);
+
+impl fmt::Display for InvalidSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid selector {:?}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidSelector {}
+
+/// Elements matching `selector`, in document order. Bridge overlays are
+/// never matched so hosts can't break them by accident.
+pub fn select(doc: &BaseDocument, selector: &str) -> Result<Vec<usize>, InvalidSelector> {
+    let matches = doc
+        .query_selector_all(selector)
+        .map_err(|_| InvalidSelector(selector.to_string()))?;
+    Ok(matches
+        .into_iter()
+        .filter(|node_id| !overlay::contains(doc, *node_id))
+        .collect())
+}
+
+/// Text content of the first element matching `selector`.
+pub fn query_text(doc: &BaseDocument, selector: &str) -> Result<Option<String>, InvalidSelector> {
+    let first = select(doc, selector)?.into_iter().next();
+    Ok(first.and_then(|node_id| doc.get_node(node_id)).map(|node| node.text_content()))
+}
+
+/// Apply `edit` to every element matching `selector`. Returns how many matched.
+pub fn apply(doc: &mut BaseDocument, selector: &str, edit: &Edit) -> Result<usize, InvalidSelector> {
+    let targets = select(doc, selector)?;
+    for &node_id in &targets {
+        apply_to(doc, node_id, edit);
+    }
+    Ok(targets.len())
+}
+
+fn apply_to(doc: &mut BaseDocument, node_id: usize, edit: &Edit) {
+    match edit {
+        Edit::SetText(text) => {
+            let children = children(doc, node_id);
+            let mut mutator = doc.mutate();
+            for child in children {
+                mutator.remove_node(child);
+            }
+            let text_node = mutator.create_text_node(text);
+            mutator.append_children(node_id, &[text_node]);
+        }
+        Edit::SetAttribute { name, value } => {
+            doc.mutate().set_attribute(node_id, overlay::attr_name(name), value);
+        }
+        Edit::RemoveAttribute(name) => {
+            doc.mutate().clear_attribute(node_id, overlay::attr_name(name));
+        }
+        Edit::SetInnerHtml(html) => {
+            doc.mutate().set_inner_html(node_id, html);
+        }
+        Edit::AddClass(class) | Edit::RemoveClass(class) => {
+            let current = dom::attr(doc, node_id, "class").unwrap_or_default().to_string();
+            let mut classes: Vec<&str> = current.split_ascii_whitespace().collect();
+            let adding = matches!(edit, Edit::AddClass(_));
+            if adding && !classes.contains(&class.as_str()) {
+                classes.push(class);
+            } else if !adding {
+                classes.retain(|existing| existing != class);
+            }
+            let updated = classes.join(" ");
+            if updated != current {
+                doc.mutate().set_attribute(node_id, overlay::attr_name("class"), &updated);
+            }
+        }
+    }
+}
+
+fn children(doc: &BaseDocument, node_id: usize) -> Vec<usize> {
+    doc.get_node(node_id)
+        .map(|node| node.children.clone())
+        .unwrap_or_default()
+}
@@ -0,0 +1,619 @@
+// Download handling for responses that Blitz can't render.
+//
+// Navigation responses are classified before they are handed to Blitz. Anything
+// that isn't a document is written to the configured download directory on a
+// worker thread, reporting progress to the host as it goes. The host hands
+// over the whole body once it's fetched, so downloads don't stream: progress
+// covers writing that body to disk, not receiving it.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::Serialize;
+
+use crate::{about, config, host};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+pub const PAGE_URL: &str = "about:downloads";
+
+const ACTION_PREFIX: &str = "frontier://downloads/";
+
+pub enum ResponseKind {
+    Document,
+    Download { mime: String, filename: String },
+}
+
+/// Decide whether a navigation response should be rendered or downloaded.
+///
+/// Explicit headers win (`Content-Disposition: attachment`, then the MIME
+/// type). Without headers the body is sniffed first: magic bytes, then
+/// whether it's HTML or other text, so a page served from `…/file.zip` (like
+/// the host's error page for a failed fetch) still renders. Only a body that
+/// sniffing can't place falls back to the URL extension, and after that
+/// anything that isn't UTF-8 is treated as binary.
+pub fn classify(
+    url: &str,
+    body: &[u8],
+    content_type: Option<&str>,
+    content_disposition: Option<&str>,
+) -> ResponseKind {
+    let disposition_name = content_disposition.and_then(disposition_filename);
+    let is_attachment = content_disposition
+        .map(|value| value.trim().to_ascii_lowercase().starts_with("attachment"))
+        .unwrap_or(false);
+
+    let mime = content_type
+        .map(|value| value.split(';').next().unwrap_or("").trim().to_ascii_lowercase())
+        .filter(|value| !value.is_empty())
+        .or_else(|| sniff_mime(body).or_else(|| sniff_text(body)).map(str::to_owned))
+        .or_else(|| mime_from_extension(url).map(str::to_owned));
+
+    let renderable = match mime.as_deref() {
+        Some(mime) => is_renderable_mime(mime),
+        None => std::str::from_utf8(body).is_ok(),
+    };
+
+    if renderable && !is_attachment {
+        return ResponseKind::Document;
+    }
+
+    ResponseKind::Download {
+        mime: mime.unwrap_or_else(|| "application/octet-stream".to_string()),
+        filename: disposition_name.unwrap_or_else(|| filename_from_url(url)),
+    }
+}
+
+fn is_renderable_mime(mime: &str) -> bool {
+    matches!(
+        mime,
+        "text/html" | "application/xhtml+xml" | "text/plain" | "image/svg+xml"
+    )
+}
+
+fn mime_from_extension(url: &str) -> Option<&'static str> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let (_, ext) = name.rsplit_once('.')?;
+
+    let mime = match ext.to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html",
+        "xhtml" => "application/xhtml+xml",
+        "txt" => "text/plain",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" | "tgz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "7z" => "application/x-7z-compressed",
+        "dmg" => "application/x-apple-diskimage",
+        "exe" | "msi" => "application/vnd.microsoft.portable-executable",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "wasm" => "application/wasm",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+fn sniff_mime(body: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"\0asm", "application/wasm"),
+    ];
+
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| body.starts_with(magic))
+        .map(|(_, mime)| *mime)
+}
+
+/// `text/html` or `text/plain` for a non-empty UTF-8 body without NUL
+/// bytes, which binary formats almost always contain.
+fn sniff_text(body: &[u8]) -> Option<&'static str> {
+    const HTML_STARTS: &[&[u8]] = &[b"<!doctype html", b"<html", b"<head", b"<body", b"<!--"];
+
+    if body.is_empty() || body.contains(&0) {
+        return None;
+    }
+    let text = std::str::from_utf8(body).ok()?;
+    let start = text.trim_start_matches('\u{feff}').trim_start().as_bytes();
+    let html = HTML_STARTS
+        .iter()
+        .any(|prefix| start.len() >= prefix.len() && start[..prefix.len()].eq_ignore_ascii_case(prefix));
+    Some(if html { "text/html" } else { "text/plain" })
+}
+
+fn disposition_filename(value: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|param| {
+        let (key, raw) = param.trim().split_once('=')?;
+        if !key.trim().eq_ignore_ascii_case("filename") {
+            return None;
+        }
+        let name = raw.trim().trim_matches('"');
+        sanitize_filename(name)
+    })
+}
+
+fn filename_from_url(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit('/')
+        .find(|segment| !segment.is_empty() && !segment.contains(':'))
+        .and_then(sanitize_filename)
+        .unwrap_or_else(|| "download".to_string())
+}
+
+fn sanitize_filename(name: &str) -> Option<String> {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '\0' => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim_start_matches('.').trim().to_string();
+    (!cleaned.is_empty()).then_some(cleaned)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadStatus {
+    InProgress,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+#[derive(Clone, Serialize)]
+pub struct DownloadEntry {
+    pub id: u64,
+    pub url: String,
+    pub mime: String,
+    pub path: PathBuf,
+    pub total_bytes: u64,
+    pub received_bytes: u64,
+    pub status: DownloadStatus,
+    pub error: Option<String>,
+    #[serde(skip)]
+    cancel: Arc<AtomicBool>,
+}
+
+#[derive(Serialize)]
+struct ProgressEvent<'a> {
+    id: u64,
+    url: &'a str,
+    path: &'a Path,
+    received_bytes: u64,
+    total_bytes: u64,
+}
+
+#[derive(Default)]
+pub struct DownloadManager {
+    next_id: AtomicU64,
+    entries: Mutex<Vec<DownloadEntry>>,
+}
+
+pub fn manager() -> &'static DownloadManager {
+    static MANAGER: OnceLock<DownloadManager> = OnceLock::new();
+    MANAGER.get_or_init(DownloadManager::default)
+}
+
+impl DownloadManager {
+    /// Start writing `body`, already fully in memory, to the download
+    /// directory in `CHUNK_SIZE` pieces. Returns the download id.
+    pub fn start(&'static self, url: String, body: Vec<u8>, mime: String, filename: String) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let dir = config::get().download_dir.clone();
+        let reserved = reserve_path(&dir, &filename);
+        let path = match &reserved {
+            Ok((path, _)) => path.clone(),
+            Err(_) => dir.join(&filename),
+        };
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let entry = DownloadEntry {
+            id,
+            url,
+            mime,
+            path,
+            total_bytes: body.len() as u64,
+            received_bytes: 0,
+            status: DownloadStatus::InProgress,
+            error: None,
+            cancel: cancel.clone(),
+        };
+
+        tracing::info!("Starting download {} of {} to {}", id, entry.url, entry.path.display());
+        host::emit("download-started", &entry);
+        self.entries.lock().unwrap().push(entry.clone());
+
+        std::thread::spawn(move || {
+            let result = reserved
+                .map_err(DownloadError::from)
+                .and_then(|(_, file)| self.write_chunks(&entry, file, &body, &cancel));
+            self.finish(id, result);
+        });
+
+        id
+    }
+
+    /// Request cancellation. The worker notices before its next chunk.
+    pub fn cancel(&self, id: u64) -> bool {
+        let entries = self.entries.lock().unwrap();
+        match entries.iter().find(|entry| entry.id == id) {
+            Some(entry) if entry.status == DownloadStatus::InProgress => {
+                entry.cancel.store(true, Ordering::Relaxed);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<DownloadEntry> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|entry| entry.id == id)
+            .cloned()
+    }
+
+    pub fn list(&self) -> Vec<DownloadEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    fn write_chunks(
+        &self,
+        entry: &DownloadEntry,
+        mut file: File,
+        body: &[u8],
+        cancel: &AtomicBool,
+    ) -> Result<(), DownloadError> {
+        let partial = partial_path(&entry.path);
+        let mut written = 0u64;
+
+        for chunk in body.chunks(CHUNK_SIZE) {
+            if cancel.load(Ordering::Relaxed) {
+                drop(file);
+                let _ = fs::remove_file(&partial);
+                return Err(DownloadError::Cancelled);
+            }
+
+            file.write_all(chunk)?;
+            written += chunk.len() as u64;
+            self.update(entry.id, |e| e.received_bytes = written);
+
+            host::emit(
+                "download-progress",
+                &ProgressEvent {
+                    id: entry.id,
+                    url: &entry.url,
+                    path: &entry.path,
+                    received_bytes: written,
+                    total_bytes: entry.total_bytes,
+                },
+            );
+        }
+
+        file.sync_all()?;
+        fs::rename(&partial, &entry.path)?;
+        Ok(())
+    }
+
+    fn finish(&self, id: u64, result: Result<(), DownloadError>) {
+        let (status, error, name) = match result {
+            Ok(()) => (DownloadStatus::Completed, None, "download-finished"),
+            Err(DownloadError::Cancelled) => (DownloadStatus::Cancelled, None, "download-cancelled"),
+            Err(DownloadError::Io(err)) => {
+                (DownloadStatus::Failed, Some(err.to_string()), "download-failed")
+            }
+        };
+
+        self.update(id, |entry| {
+            entry.status = status;
+            entry.error = error;
+        });
+
+        if let Some(entry) = self.get(id) {
+            tracing::info!("Download {} finished with status {}", id, status_label(status));
+            host::emit(name, &entry);
+        }
+    }
+
+    fn update(&self, id: u64, f: impl FnOnce(&mut DownloadEntry)) {
+        if let Some(entry) = self.entries.lock().unwrap().iter_mut().find(|e| e.id == id) {
+            f(entry);
+        }
+    }
+}
+
+enum DownloadError {
+    Cancelled,
+    Io(io::Error),
+}
+
+impl From<io::Error> for DownloadError {
+    fn from(err: io::Error) -> Self {
+        DownloadError::Io(err)
+    }
+}
+
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+/// Pick `name`, or `name (1)`, `name (2)`, ... so existing files are never
+/// overwritten, and reserve it by creating its `.part` file. Creating the
+/// file with `create_new` is what makes the choice stick: two downloads of
+/// the same name can't both claim it.
+fn reserve_path(dir: &Path, name: &str) -> io::Result<(PathBuf, File)> {
+    fs::create_dir_all(dir)?;
+
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+        _ => (name, String::new()),
+    };
+    let candidates = std::iter::once(dir.join(name)).chain((1..).map(|n| dir.join(format!("{stem} ({n}){ext}"))));
+
+    for candidate in candidates {
+        if candidate.exists() {
+            continue;
+        }
+        match OpenOptions::new().write(true).create_new(true).open(partial_path(&candidate)) {
+            Ok(file) => return Ok((candidate, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    unreachable!("unbounded range always yields a free name")
+}
+
+fn status_label(status: DownloadStatus) -> &'static str {
+    match status {
+        DownloadStatus::InProgress => "In progress",
+        DownloadStatus::Completed => "Completed",
+        DownloadStatus::Cancelled => "Cancelled",
+        DownloadStatus::Failed => "Failed",
+    }
+}
+
+/// Handle `frontier://downloads/<action>/<id>` links from the `about:downloads` page.
+///
+/// Cancel is applied here and reported to the host; opening the folder is
+/// entirely up to the host since it owns the platform integration.
+pub fn handle_action(url: &str) {
+    let Some(rest) = url.strip_prefix(ACTION_PREFIX) else {
+        return;
+    };
+    let mut parts = rest.trim_end_matches('/').splitn(2, '/');
+    let action = parts.next().unwrap_or("");
+    let Some(id) = parts.next().and_then(|id| id.parse::<u64>().ok()) else {
+        tracing::warn!("Download action without a valid id: {}", url);
+        return;
+    };
+    let Some(entry) = manager().get(id) else {
+        tracing::warn!("Download action for unknown id {}", id);
+        return;
+    };
+
+    match action {
+        "cancel" => {
+            manager().cancel(id);
+            host::emit("download-cancel", &entry);
+        }
+        "open-folder" => host::emit("download-open-folder", &entry),
+        other => tracing::warn!("Unknown download action: {}", other),
+    }
+}
+
+pub fn is_action(url: &str) -> bool {
+    url.starts_with(ACTION_PREFIX)
+}
+
+pub fn render_downloads_page() -> String {
+    let entries = manager().list();
+    let mut rows = String::new();
+
+    for entry in entries.iter().rev() {
+        let percent = if entry.total_bytes == 0 {
+            100
+        } else {
+            entry.received_bytes * 100 / entry.total_bytes
+        };
+        let mut actions = String::new();
+        if entry.status == DownloadStatus::InProgress {
+            actions.push_str(&format!(
+                r#"<a href="frontier://downloads/cancel/{}">Cancel</a> "#,
+                entry.id
+            ));
+        }
+        actions.push_str(&format!(
+            r#"<a href="frontier://downloads/open-folder/{}">Show in folder</a>"#,
+            entry.id
+        ));
+
+        rows.push_str(&format!(
+            r#"<li class="download">
+      <div class="name">{name}</div>
+      <div class="meta">{status} &middot; {percent}% of {total} bytes &middot; {url}</div>
+      {error}
+      <div class="actions">{actions}</div>
+    </li>
+"#,
+            name = about::escape_html(&entry.path.file_name().unwrap_or_default().to_string_lossy()),
+            status = status_label(entry.status),
+            total = entry.total_bytes,
+            url = about::escape_html(&entry.url),
+            error = entry
+                .error
+                .as_deref()
+                .map(|err| format!(r#"<div class="error">{}</div>"#, about::escape_html(err)))
+                .unwrap_or_default(),
+        ));
+    }
+
+    if rows.is_empty() {
+        rows.push_str(r#"<li class="empty">No downloads yet</li>"#);
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8" />
+  <title>Downloads</title>
+  <style>
+    body {{ font-family: -apple-system, sans-serif; margin: 0; padding: 40px; background: #f8fafc; color: #0f172a; }}
+    h1 {{ margin: 0 0 24px 0; font-size: 24px; }}
+    ul {{ list-style: none; margin: 0; padding: 0; max-width: 720px; }}
+    .download {{ background: white; border: 1px solid #e5e7eb; border-radius: 8px; padding: 16px; margin-bottom: 12px; }}
+    .name {{ font-weight: 600; }}
+    .meta {{ font-size: 12px; color: #64748b; margin-top: 4px; word-break: break-all; }}
+    .error {{ font-size: 12px; color: #e53e3e; margin-top: 4px; }}
+    .actions {{ margin-top: 8px; font-size: 13px; }}
+    .actions a {{ color: #667eea; margin-right: 12px; }}
+    .empty {{ color: #94a3b8; }}
+  </style>
+</head>
+<body>
+  <h1>Downloads</h1>
+  <ul>
+    {rows}
+  </ul>
+</body>
+</html>"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("frontier-download-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn download(kind: ResponseKind) -> Option<(String, String)> {
+        match kind {
+            ResponseKind::Document => None,
+            ResponseKind::Download { mime, filename } => Some((mime, filename)),
+        }
+    }
+
+    #[test]
+    fn classify_renders_documents() {
+        let html = classify("https://example.com/", b"<p>Hi</p>", Some("text/html; charset=utf-8"), None);
+        assert!(download(html).is_none());
+        let text = classify("https://example.com/notes", b"plain words", None, None);
+        assert!(download(text).is_none());
+        let svg = classify("https://example.com/logo.svg", b"<svg/>", None, None);
+        assert!(download(svg).is_none());
+    }
+
+    #[test]
+    fn classify_prefers_headers() {
+        let attachment = classify(
+            "https://example.com/report",
+            b"<p>Hi</p>",
+            Some("text/html"),
+            Some(r#"attachment; filename="q3 report.html""#),
+        );
+        assert_eq!(
+            download(attachment),
+            Some(("text/html".to_string(), "q3 report.html".to_string()))
+        );
+
+        // The header's MIME type beats the URL's extension
+        let pdf = classify("https://example.com/page.html", b"%PDF-1.7", Some("application/pdf"), None);
+        assert_eq!(download(pdf), Some(("application/pdf".to_string(), "page.html".to_string())));
+    }
+
+    #[test]
+    fn classify_falls_back_to_extension_and_magic_bytes() {
+        let zip = classify("https://example.com/files/archive.zip?token=1", b"", None, None);
+        assert_eq!(download(zip), Some(("application/zip".to_string(), "archive.zip".to_string())));
+
+        let png = classify("https://example.com/image", b"\x89PNG\r\n\x1a\n....", None, None);
+        assert_eq!(download(png), Some(("image/png".to_string(), "image".to_string())));
+
+        let binary = classify("https://example.com/", &[0xff, 0xfe, 0x00, 0x80], None, None);
+        assert_eq!(
+            download(binary),
+            Some(("application/octet-stream".to_string(), "example.com".to_string()))
+        );
+    }
+
+    #[test]
+    fn sniffed_documents_render_whatever_the_extension() {
+        let page = b"<!DOCTYPE html><html><body><h1>Navigation Error</h1></body></html>";
+        let zip = classify("https://example.com/files/archive.zip", page, None, None);
+        assert!(download(zip).is_none());
+        let png = classify("https://example.com/file.png", b"Not found", None, None);
+        assert!(download(png).is_none());
+
+        // Real archives are still downloads, whatever the URL says
+        let archive = classify("https://example.com/page.html", b"PK\x03\x04rest", None, None);
+        assert_eq!(download(archive), Some(("application/zip".to_string(), "page.html".to_string())));
+    }
+
+    #[test]
+    fn sanitize_filename_strips_paths_and_hidden_prefixes() {
+        assert_eq!(sanitize_filename("report.pdf").as_deref(), Some("report.pdf"));
+        assert_eq!(sanitize_filename("../../etc/passwd").as_deref(), Some("_.._etc_passwd"));
+        assert_eq!(sanitize_filename("C:\\temp\\a.txt").as_deref(), Some("C__temp_a.txt"));
+        assert_eq!(sanitize_filename(".bashrc").as_deref(), Some("bashrc"));
+        assert_eq!(sanitize_filename("  ").as_deref(), None);
+        assert_eq!(sanitize_filename("...").as_deref(), None);
+    }
+
+    #[test]
+    fn reserve_path_skips_taken_names() {
+        let dir = temp_dir("taken");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("file.txt"), "existing").unwrap();
+        fs::write(partial_path(&dir.join("file (1).txt")), "in flight").unwrap();
+
+        let (path, _) = reserve_path(&dir, "file.txt").unwrap();
+        assert_eq!(path, dir.join("file (2).txt"));
+        assert!(partial_path(&path).exists());
+
+        let (path, _) = reserve_path(&dir, "README").unwrap();
+        assert_eq!(path, dir.join("README"));
+        let (path, _) = reserve_path(&dir, "README").unwrap();
+        assert_eq!(path, dir.join("README (1)"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn concurrent_reservations_get_distinct_paths() {
+        let dir = temp_dir("concurrent");
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let dir = dir.clone();
+                std::thread::spawn(move || reserve_path(&dir, "same.bin").unwrap().0)
+            })
+            .collect();
+        let mut paths: Vec<PathBuf> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+        paths.sort();
+        paths.dedup();
+        assert_eq!(paths.len(), 8);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,683 @@
+// Synthetic input for end-to-end tests, against headless windows.
+//
+// `TestDriver` runs a real `FrontierApplication` whose windows are headless
+// documents (see `shell`). Input goes through the same
+// `FrontierApplication::handle_input` that `window_event` uses, so key
+// bindings, overlays, the context menu, `data-frontier-action` elements,
+// new-window links and link hovering all behave as they do in a native
+// window; whatever the bridge passes on is delivered to Blitz as DOM events.
+// After every event the driver does what the event loop does when it wakes
+// up: run queued FFI commands, apply navigations and give new windows a view.
+//
+// winit's `KeyEvent` can't be constructed outside winit, so input is
+// described with `Input` instead of `WindowEvent`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use blitz_dom::BaseDocument;
+use blitz_html::HtmlDocument;
+use blitz_traits::events::{
+    BlitzKeyEvent, BlitzMouseButtonEvent, KeyState, MouseEventButton, MouseEventButtons, UiEvent,
+};
+use keyboard_types::{Code, Key as DomKey, Location, Modifiers as DomModifiers};
+use winit::dpi::PhysicalPosition;
+use winit::event::MouseButton;
+use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey};
+use winit::window::WindowId;
+
+use crate::dom_edit::{self, Edit, InvalidSelector};
+use crate::find::FindState;
+use crate::forms;
+use crate::input::{self, WindowInput};
+use crate::palette::PaletteState;
+use crate::patch::PatchStats;
+use crate::session::{self, Session, WindowSnapshot};
+use crate::shell::{HeadlessView, Shell};
+use crate::{commands, headless, keymap, messages, overlay, serialize, test_host, zoom};
+use crate::{BrowserWindow, Dispatch, FrontierApplication, NavigationState, Tab};
+
+pub use crate::headless::HeadlessError;
+
+const DEFAULT_WIDTH: u32 = 800;
+const DEFAULT_HEIGHT: u32 = 600;
+
+/// One piece of synthetic input. Positions are in CSS pixels relative to the
+/// viewport.
+#[derive(Clone, Debug)]
+pub enum Input {
+    Modifiers(ModifiersState),
+    /// A key press and release. `text` is what the key types, if anything.
+    Key {
+        code: KeyCode,
+        key: Key,
+        text: Option<String>,
+    },
+    /// Type `text` one character at a time.
+    Text(String),
+    MouseMove { x: f64, y: f64 },
+    MouseDown { x: f64, y: f64, button: MouseButton },
+    MouseUp { x: f64, y: f64, button: MouseButton },
+    /// Vertical wheel movement in lines; positive scrolls up.
+    Wheel { lines: f64 },
+    Resize { width: u32, height: u32 },
+}
+
+/// A host event emitted while the driver was running.
+#[derive(Clone, Debug)]
+pub struct HostEvent {
+    pub name: String,
+    pub payload: serde_json::Value,
+}
+
+pub struct TestDriver {
+    app: FrontierApplication,
+    // The window input goes to: the first one, with host id 0
+    window_id: WindowId,
+    next_window_id: u64,
+    width: u32,
+    height: u32,
+    scale: f64,
+    buttons: MouseEventButtons,
+    // Host events this thread had already seen when the driver started
+    events_before: usize,
+}
+
+impl TestDriver {
+    /// Load `html` as if it had been fetched from `url`, in an 800x600 viewport.
+    pub fn new(html: &str, url: &str) -> Result<Self, HeadlessError> {
+        Self::with_viewport(html, url, DEFAULT_WIDTH, DEFAULT_HEIGHT, 1.0)
+    }
+
+    pub fn with_viewport(html: &str, url: &str, width: u32, height: u32, scale: f64) -> Result<Self, HeadlessError> {
+        let state = Arc::new(Mutex::new(NavigationState::new(html.to_owned(), url.to_owned())));
+        let tab = Tab::new(state, None);
+        Self::from_window(BrowserWindow::new(tab, None), width, height, scale)
+    }
+
+    /// Reopen a window from a session snapshot like a restore on launch does:
+    /// the active tab is fetched from the host now, the others once they're
+    /// switched to. `None` if it has no usable tabs.
+    pub fn restore(snapshot: &WindowSnapshot) -> Result<Option<Self>, HeadlessError> {
+        let Some(window) = session::restore_window(snapshot, None) else {
+            return Ok(None);
+        };
+        Self::from_window(window, DEFAULT_WIDTH, DEFAULT_HEIGHT, 1.0).map(Some)
+    }
+
+    fn from_window(window: BrowserWindow, width: u32, height: u32, scale: f64) -> Result<Self, HeadlessError> {
+        // FFI calls from this thread now queue commands for this driver
+        commands::attach_headless();
+        let mut driver = Self {
+            app: FrontierApplication::new(Shell::Headless(HashMap::new())),
+            window_id: WindowId::from(0),
+            next_window_id: 0,
+            width,
+            height,
+            scale,
+            buttons: MouseEventButtons::None,
+            events_before: test_host::events().len(),
+        };
+        driver.app.open_window(window);
+        driver.create_pending_windows();
+        driver.app.focused_window = Some(driver.window_id);
+
+        headless::layout(&mut driver.view_mut().document, width, height, scale)?;
+        // Restored tabs scroll back once they've been laid out
+        driver.app.apply_restore_scroll(driver.window_id);
+        Ok(driver)
+    }
+
+    // --- Input ---
+
+    pub fn send(&mut self, input: Input) {
+        // Like `new_events` when the event loop wakes up
+        self.app.expire_key_sequences();
+
+        match input {
+            Input::Modifiers(modifiers) => self.dispatch(WindowInput::Modifiers(modifiers), |_| {}),
+            Input::Key { code, key, text } => self.key(Some(code), key, text),
+            Input::Text(text) => {
+                for ch in text.chars() {
+                    let text = ch.to_string();
+                    self.key(input::key_for_char(ch), Key::Character(text.as_str().into()), Some(text));
+                }
+            }
+            Input::MouseMove { x, y } => self.mouse_move(x, y),
+            Input::MouseDown { x, y, button } => self.mouse_button(x, y, button, true),
+            Input::MouseUp { x, y, button } => self.mouse_button(x, y, button, false),
+            Input::Wheel { lines } => {
+                // Same line height Blitz uses for line-based wheel deltas
+                self.dispatch(WindowInput::Wheel(lines), |doc| doc.scroll_viewport_by(0.0, -lines * 20.0));
+            }
+            Input::Resize { width, height } => {
+                self.width = width;
+                self.height = height;
+            }
+        }
+
+        self.wake();
+    }
+
+    /// Press and release a key that types nothing, e.g. `press(KeyCode::Enter)`.
+    pub fn press(&mut self, code: KeyCode) {
+        let key = named_key(code).map(Key::Named).unwrap_or(Key::Unidentified(
+            winit::keyboard::NativeKey::Unidentified,
+        ));
+        self.send(Input::Key { code, key, text: None });
+    }
+
+    /// Press `code` with `modifiers` held, then release the modifiers.
+    pub fn press_with(&mut self, modifiers: ModifiersState, code: KeyCode) {
+        self.send(Input::Modifiers(modifiers));
+        self.press(code);
+        self.send(Input::Modifiers(ModifiersState::empty()));
+    }
+
+    /// Press `code` with the keymap's `Mod` held: Cmd on macOS, Ctrl elsewhere,
+    /// unless the profile's `keymap.toml` says otherwise. Test-host profiles
+    /// live in a temporary directory, so that's only a keymap the test wrote.
+    pub fn press_mod(&mut self, code: KeyCode) {
+        self.press_with(keymap::mod_modifier(), code);
+    }
+
+    /// Navigate to `url` as if it had been typed into the address bar.
+    pub fn load(&mut self, url: &str) {
+        self.window().active().nav_provider.load(url.to_string());
+        self.wake();
+    }
+
+    pub fn type_text(&mut self, text: &str) {
+        self.send(Input::Text(text.to_string()));
+    }
+
+    pub fn click(&mut self, x: f64, y: f64) {
+        self.send(Input::MouseMove { x, y });
+        self.send(Input::MouseDown { x, y, button: MouseButton::Left });
+        self.send(Input::MouseUp { x, y, button: MouseButton::Left });
+    }
+
+    /// Click the centre of the first element matching `selector`. Returns
+    /// false if nothing matches.
+    pub fn click_selector(&mut self, selector: &str) -> bool {
+        let Some((x, y)) = self.center_of(selector) else {
+            return false;
+        };
+        self.click(x, y);
+        true
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.send(Input::Resize { width, height });
+    }
+
+    /// Apply a DOM edit the way the `frontier_blitz_set_*` FFI functions do.
+    pub fn edit(&mut self, selector: &str, edit: &Edit) -> Result<usize, InvalidSelector> {
+        let count = dom_edit::apply(&mut self.view_mut().document, selector, edit)?;
+        self.relayout();
+        Ok(count)
+    }
+
+    /// Deliver a host message to the page, like `frontier_blitz_post_message`.
+    pub fn post_message(&mut self, name: &str, payload: &serde_json::Value) -> Result<usize, InvalidSelector> {
+        let updated = messages::deliver(&mut self.view_mut().document, name, payload)?;
+        self.relayout();
+        Ok(updated)
+    }
+
+    /// Fill in form controls, like `frontier_blitz_set_form_values`.
+    pub fn set_form_values(
+        &mut self,
+        scope: Option<&str>,
+        values: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Option<usize>, InvalidSelector> {
+        let count = forms::write(&mut self.view_mut().document, scope, values)?;
+        self.relayout();
+        Ok(count)
+    }
+
+    /// Patch the document to match `html`, like `frontier_blitz_patch_document`.
+    pub fn patch(&mut self, html: &str) -> PatchStats {
+        let stats = self
+            .app
+            .patch_document(self.window_id, html)
+            .expect("the driver's window stays open");
+        self.relayout();
+        stats
+    }
+
+    /// Run `input` through the bridge like `window_event` does, and let
+    /// `forward` hand it to Blitz if the bridge passes it on.
+    fn dispatch(&mut self, input: WindowInput, forward: impl FnOnce(&mut HtmlDocument)) {
+        if let Dispatch::Forward(pending) = self.app.handle_input(self.window_id, input) {
+            forward(&mut self.view_mut().document);
+            if let Some(pending) = pending {
+                self.app.finish_action(self.window_id, pending);
+            }
+        }
+    }
+
+    fn key(&mut self, code: Option<KeyCode>, key: Key, text: Option<String>) {
+        let modifiers = dom_modifiers(self.app.keyboard_modifiers);
+        for pressed in [true, false] {
+            let event = BlitzKeyEvent {
+                key: dom_key(&key),
+                code: dom_code(code),
+                modifiers,
+                location: Location::Standard,
+                is_auto_repeating: false,
+                is_composing: false,
+                state: if pressed { KeyState::Pressed } else { KeyState::Released },
+                text: text.as_deref().map(Into::into),
+            };
+            let input = WindowInput::Key {
+                pressed,
+                key: key.clone(),
+                code,
+                text: text.clone(),
+            };
+            self.dispatch(input, |doc| {
+                doc.handle_ui_event(if pressed { UiEvent::KeyDown(event) } else { UiEvent::KeyUp(event) })
+            });
+        }
+    }
+
+    fn mouse_move(&mut self, x: f64, y: f64) {
+        let event = self.mouse_event(x, y, MouseButton::Left);
+        let position = self.physical(x, y);
+        self.dispatch(WindowInput::CursorMoved(position), |doc| {
+            doc.handle_ui_event(UiEvent::MouseMove(event))
+        });
+    }
+
+    fn mouse_button(&mut self, x: f64, y: f64, button: MouseButton, pressed: bool) {
+        // winit reports the cursor moving before a button changes under it
+        if self.window().cursor_position != self.physical(x, y) {
+            self.mouse_move(x, y);
+        }
+
+        let flag = match button {
+            MouseButton::Left => MouseEventButtons::Primary,
+            MouseButton::Right => MouseEventButtons::Secondary,
+            MouseButton::Middle => MouseEventButtons::Auxiliary,
+            _ => MouseEventButtons::None,
+        };
+        if pressed {
+            self.buttons |= flag;
+        }
+        let event = self.mouse_event(x, y, button);
+        if !pressed {
+            self.buttons &= !flag;
+        }
+
+        self.dispatch(WindowInput::Mouse { pressed, button }, |doc| {
+            doc.handle_ui_event(if pressed { UiEvent::MouseDown(event) } else { UiEvent::MouseUp(event) })
+        });
+    }
+
+    fn mouse_event(&self, x: f64, y: f64, button: MouseButton) -> BlitzMouseButtonEvent {
+        let zoom = self.zoom() as f64;
+        BlitzMouseButtonEvent {
+            x: (x / zoom) as f32,
+            y: (y / zoom) as f32,
+            button: match button {
+                MouseButton::Right => MouseEventButton::Secondary,
+                MouseButton::Middle => MouseEventButton::Auxiliary,
+                _ => MouseEventButton::Main,
+            },
+            buttons: self.buttons,
+            mods: dom_modifiers(self.app.keyboard_modifiers),
+        }
+    }
+
+    fn physical(&self, x: f64, y: f64) -> PhysicalPosition<f64> {
+        PhysicalPosition::new(x * self.scale, y * self.scale)
+    }
+
+    /// What the event loop does after an event, with a layout pass in place
+    /// of the redraw.
+    fn wake(&mut self) {
+        self.app.run_queued();
+        self.create_pending_windows();
+        self.relayout();
+        self.app.apply_restore_scroll(self.window_id);
+    }
+
+    /// Give windows the app opened a headless view, where the event loop
+    /// would create a native one.
+    fn create_pending_windows(&mut self) {
+        for window in std::mem::take(&mut self.app.pending_windows) {
+            let window_id = WindowId::from(self.next_window_id);
+            self.next_window_id += 1;
+            let document = window.active().create_document();
+            if let Shell::Headless(views) = &mut self.app.shell {
+                views.insert(window_id, HeadlessView { document, scale: self.scale });
+            }
+            self.app.adopt_window(window_id, window);
+        }
+    }
+
+    fn relayout(&mut self) {
+        let (width, height, scale) = (self.width, self.height, self.scale);
+        if let Err(err) = headless::layout(&mut self.view_mut().document, width, height, scale) {
+            tracing::error!("Test driver layout failed: {err}");
+        }
+    }
+
+    fn window(&self) -> &BrowserWindow {
+        &self.app.windows[&self.window_id]
+    }
+
+    fn view(&self) -> &HeadlessView {
+        match &self.app.shell {
+            Shell::Headless(views) => &views[&self.window_id],
+            Shell::Native { .. } => unreachable!("the test driver only uses headless windows"),
+        }
+    }
+
+    fn view_mut(&mut self) -> &mut HeadlessView {
+        match &mut self.app.shell {
+            Shell::Headless(views) => views.get_mut(&self.window_id).expect("the driver's window stays open"),
+            Shell::Native { .. } => unreachable!("the test driver only uses headless windows"),
+        }
+    }
+
+    // --- Inspection ---
+
+    pub fn document(&self) -> &BaseDocument {
+        &self.view().document
+    }
+
+    pub fn navigation(&self) -> MutexGuard<'_, NavigationState> {
+        self.window().active().state.lock().unwrap()
+    }
+
+    /// The command palette, if it's open.
+    pub fn palette(&self) -> Option<&PaletteState> {
+        self.window().palette.as_ref()
+    }
+
+    pub fn url(&self) -> String {
+        self.window().active().current_url()
+    }
+
+    pub fn history(&self) -> Vec<String> {
+        self.navigation().history().to_vec()
+    }
+
+    /// Generation of the current document, as sent in `document-loaded`.
+    pub fn generation(&self) -> u64 {
+        self.window().generation
+    }
+
+    pub fn zoom(&self) -> f32 {
+        zoom::current(self.document())
+    }
+
+    /// The window's key mode, see `keymap`.
+    pub fn key_mode(&self) -> &str {
+        self.window().keys.mode()
+    }
+
+    /// Text of the pending key sequence and mode indicator, if it's showing.
+    pub fn key_indicator(&self) -> Option<String> {
+        let doc = self.document();
+        overlay::find(doc, overlay::KEY_INDICATOR)
+            .and_then(|node_id| doc.get_node(node_id))
+            .map(|node| node.text_content())
+    }
+
+    pub fn find_match_count(&self) -> Option<usize> {
+        self.window().find.as_ref().map(FindState::match_count)
+    }
+
+    pub fn hovered_link(&self) -> Option<&str> {
+        self.window().hovered_link.as_deref()
+    }
+
+    pub fn query_selector(&self, selector: &str) -> Option<usize> {
+        self.document().query_selector(selector).ok().flatten()
+    }
+
+    /// Text content of the first element matching `selector`.
+    pub fn text(&self, selector: &str) -> Option<String> {
+        let node_id = self.query_selector(selector)?;
+        Some(self.document().get_node(node_id)?.text_content())
+    }
+
+    /// Snapshot the open windows, like the event loop does for `session.json`.
+    pub fn session(&self) -> Session {
+        self.app.session()
+    }
+
+    pub fn scroll(&self) -> (f64, f64) {
+        let scroll = self.document().viewport_scroll();
+        (scroll.x, scroll.y)
+    }
+
+    /// Form control values, like `frontier_blitz_get_form_values`.
+    pub fn form_values(
+        &self,
+        scope: Option<&str>,
+    ) -> Result<Option<serde_json::Map<String, serde_json::Value>>, InvalidSelector> {
+        forms::read(self.document(), scope)
+    }
+
+    /// The live document as HTML, or the outer HTML of the first element
+    /// matching `selector`, like `frontier_blitz_serialize_dom`.
+    pub fn html(&self, selector: Option<&str>) -> Option<String> {
+        match selector {
+            Some(selector) => serialize::outer_html(self.document(), selector).ok().flatten(),
+            None => Some(serialize::document(self.document())),
+        }
+    }
+
+    /// The document's `<title>`, trimmed.
+    pub fn title(&self) -> Option<String> {
+        self.text("title").map(|title| title.trim().to_string())
+    }
+
+    /// Host events emitted on this thread since the driver was created.
+    pub fn host_events(&self) -> Vec<HostEvent> {
+        test_host::events()
+            .into_iter()
+            .skip(self.events_before)
+            .map(|(name, payload)| HostEvent {
+                name,
+                payload: serde_json::from_str(&payload).unwrap_or(serde_json::Value::Null),
+            })
+            .collect()
+    }
+
+    /// Render the current viewport, e.g. for a screenshot step.
+    pub fn screenshot_png(&mut self) -> Result<Vec<u8>, HeadlessError> {
+        let (width, height, scale) = (self.width, self.height, self.scale);
+        headless::layout(&mut self.view_mut().document, width, height, scale)?;
+        headless::encode_png(&headless::paint(&self.view().document, scale))
+    }
+
+    fn center_of(&self, selector: &str) -> Option<(f64, f64)> {
+        let node_id = self.query_selector(selector)?;
+        let doc = self.document();
+        let node = doc.get_node(node_id)?;
+        let position = node.absolute_position(0.0, 0.0);
+        let size = node.final_layout.size;
+        let scroll = doc.viewport_scroll();
+        let zoom = self.zoom() as f64;
+        let x = (position.x as f64 + size.width as f64 / 2.0 - scroll.x) * zoom;
+        let y = (position.y as f64 + size.height as f64 / 2.0 - scroll.y) * zoom;
+        Some((x, y))
+    }
+}
+
+impl Drop for TestDriver {
+    fn drop(&mut self) {
+        commands::detach_headless();
+    }
+}
+
+fn named_key(code: KeyCode) -> Option<NamedKey> {
+    Some(match code {
+        KeyCode::Enter | KeyCode::NumpadEnter => NamedKey::Enter,
+        KeyCode::Escape => NamedKey::Escape,
+        KeyCode::Backspace => NamedKey::Backspace,
+        KeyCode::Delete => NamedKey::Delete,
+        KeyCode::Tab => NamedKey::Tab,
+        KeyCode::ArrowUp => NamedKey::ArrowUp,
+        KeyCode::ArrowDown => NamedKey::ArrowDown,
+        KeyCode::ArrowLeft => NamedKey::ArrowLeft,
+        KeyCode::ArrowRight => NamedKey::ArrowRight,
+        KeyCode::Home => NamedKey::Home,
+        KeyCode::End => NamedKey::End,
+        _ => return None,
+    })
+}
+
+/// The DOM `code` for a physical key, as Blitz's winit conversion gives it.
+/// winit calls the Meta keys Super. Media, language and other keys a test
+/// can't sensibly press stay `Unidentified`.
+fn dom_code(code: Option<KeyCode>) -> Code {
+    let Some(code) = code else {
+        return Code::Unidentified;
+    };
+    match code {
+        KeyCode::Backquote => Code::Backquote,
+        KeyCode::Backslash => Code::Backslash,
+        KeyCode::BracketLeft => Code::BracketLeft,
+        KeyCode::BracketRight => Code::BracketRight,
+        KeyCode::Comma => Code::Comma,
+        KeyCode::Equal => Code::Equal,
+        KeyCode::IntlBackslash => Code::IntlBackslash,
+        KeyCode::IntlRo => Code::IntlRo,
+        KeyCode::IntlYen => Code::IntlYen,
+        KeyCode::Minus => Code::Minus,
+        KeyCode::Period => Code::Period,
+        KeyCode::Quote => Code::Quote,
+        KeyCode::Semicolon => Code::Semicolon,
+        KeyCode::Slash => Code::Slash,
+        KeyCode::Digit0 => Code::Digit0,
+        KeyCode::Digit1 => Code::Digit1,
+        KeyCode::Digit2 => Code::Digit2,
+        KeyCode::Digit3 => Code::Digit3,
+        KeyCode::Digit4 => Code::Digit4,
+        KeyCode::Digit5 => Code::Digit5,
+        KeyCode::Digit6 => Code::Digit6,
+        KeyCode::Digit7 => Code::Digit7,
+        KeyCode::Digit8 => Code::Digit8,
+        KeyCode::Digit9 => Code::Digit9,
+        KeyCode::KeyA => Code::KeyA,
+        KeyCode::KeyB => Code::KeyB,
+        KeyCode::KeyC => Code::KeyC,
+        KeyCode::KeyD => Code::KeyD,
+        KeyCode::KeyE => Code::KeyE,
+        KeyCode::KeyF => Code::KeyF,
+        KeyCode::KeyG => Code::KeyG,
+        KeyCode::KeyH => Code::KeyH,
+        KeyCode::KeyI => Code::KeyI,
+        KeyCode::KeyJ => Code::KeyJ,
+        KeyCode::KeyK => Code::KeyK,
+        KeyCode::KeyL => Code::KeyL,
+        KeyCode::KeyM => Code::KeyM,
+        KeyCode::KeyN => Code::KeyN,
+        KeyCode::KeyO => Code::KeyO,
+        KeyCode::KeyP => Code::KeyP,
+        KeyCode::KeyQ => Code::KeyQ,
+        KeyCode::KeyR => Code::KeyR,
+        KeyCode::KeyS => Code::KeyS,
+        KeyCode::KeyT => Code::KeyT,
+        KeyCode::KeyU => Code::KeyU,
+        KeyCode::KeyV => Code::KeyV,
+        KeyCode::KeyW => Code::KeyW,
+        KeyCode::KeyX => Code::KeyX,
+        KeyCode::KeyY => Code::KeyY,
+        KeyCode::KeyZ => Code::KeyZ,
+        KeyCode::AltLeft => Code::AltLeft,
+        KeyCode::AltRight => Code::AltRight,
+        KeyCode::Backspace => Code::Backspace,
+        KeyCode::CapsLock => Code::CapsLock,
+        KeyCode::ContextMenu => Code::ContextMenu,
+        KeyCode::ControlLeft => Code::ControlLeft,
+        KeyCode::ControlRight => Code::ControlRight,
+        KeyCode::Enter => Code::Enter,
+        KeyCode::ShiftLeft => Code::ShiftLeft,
+        KeyCode::ShiftRight => Code::ShiftRight,
+        KeyCode::Space => Code::Space,
+        KeyCode::Tab => Code::Tab,
+        KeyCode::Delete => Code::Delete,
+        KeyCode::End => Code::End,
+        KeyCode::Help => Code::Help,
+        KeyCode::Home => Code::Home,
+        KeyCode::Insert => Code::Insert,
+        KeyCode::PageDown => Code::PageDown,
+        KeyCode::PageUp => Code::PageUp,
+        KeyCode::ArrowDown => Code::ArrowDown,
+        KeyCode::ArrowLeft => Code::ArrowLeft,
+        KeyCode::ArrowRight => Code::ArrowRight,
+        KeyCode::ArrowUp => Code::ArrowUp,
+        KeyCode::NumLock => Code::NumLock,
+        KeyCode::Numpad0 => Code::Numpad0,
+        KeyCode::Numpad1 => Code::Numpad1,
+        KeyCode::Numpad2 => Code::Numpad2,
+        KeyCode::Numpad3 => Code::Numpad3,
+        KeyCode::Numpad4 => Code::Numpad4,
+        KeyCode::Numpad5 => Code::Numpad5,
+        KeyCode::Numpad6 => Code::Numpad6,
+        KeyCode::Numpad7 => Code::Numpad7,
+        KeyCode::Numpad8 => Code::Numpad8,
+        KeyCode::Numpad9 => Code::Numpad9,
+        KeyCode::NumpadAdd => Code::NumpadAdd,
+        KeyCode::NumpadComma => Code::NumpadComma,
+        KeyCode::NumpadDecimal => Code::NumpadDecimal,
+        KeyCode::NumpadDivide => Code::NumpadDivide,
+        KeyCode::NumpadEnter => Code::NumpadEnter,
+        KeyCode::NumpadEqual => Code::NumpadEqual,
+        KeyCode::NumpadMultiply => Code::NumpadMultiply,
+        KeyCode::NumpadSubtract => Code::NumpadSubtract,
+        KeyCode::Escape => Code::Escape,
+        KeyCode::PrintScreen => Code::PrintScreen,
+        KeyCode::ScrollLock => Code::ScrollLock,
+        KeyCode::Pause => Code::Pause,
+        KeyCode::F1 => Code::F1,
+        KeyCode::F2 => Code::F2,
+        KeyCode::F3 => Code::F3,
+        KeyCode::F4 => Code::F4,
+        KeyCode::F5 => Code::F5,
+        KeyCode::F6 => Code::F6,
+        KeyCode::F7 => Code::F7,
+        KeyCode::F8 => Code::F8,
+        KeyCode::F9 => Code::F9,
+        KeyCode::F10 => Code::F10,
+        KeyCode::F11 => Code::F11,
+        KeyCode::F12 => Code::F12,
+        KeyCode::SuperLeft => Code::MetaLeft,
+        KeyCode::SuperRight => Code::MetaRight,
+        _ => Code::Unidentified,
+    }
+}
+
+fn dom_key(key: &Key) -> DomKey {
+    match key {
+        Key::Character(text) => DomKey::Character(text.to_string()),
+        Key::Named(NamedKey::Enter) => DomKey::Enter,
+        Key::Named(NamedKey::Escape) => DomKey::Escape,
+        Key::Named(NamedKey::Backspace) => DomKey::Backspace,
+        Key::Named(NamedKey::Delete) => DomKey::Delete,
+        Key::Named(NamedKey::Tab) => DomKey::Tab,
+        Key::Named(NamedKey::ArrowUp) => DomKey::ArrowUp,
+        Key::Named(NamedKey::ArrowDown) => DomKey::ArrowDown,
+        Key::Named(NamedKey::ArrowLeft) => DomKey::ArrowLeft,
+        Key::Named(NamedKey::ArrowRight) => DomKey::ArrowRight,
+        Key::Named(NamedKey::Home) => DomKey::Home,
+        Key::Named(NamedKey::End) => DomKey::End,
+        _ => DomKey::Unidentified,
+    }
+}
+
+fn dom_modifiers(mods: ModifiersState) -> DomModifiers {
+    let mut out = DomModifiers::empty();
+    out.set(DomModifiers::CONTROL, mods.control_key());
+    out.set(DomModifiers::SHIFT, mods.shift_key());
+    out.set(DomModifiers::ALT, mods.alt_key());
+    out.set(DomModifiers::META, mods.super_key());
+    out
+}
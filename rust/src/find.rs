@@ -0,0 +1,305 @@
+// Find-in-page: search text nodes, highlight matches and drive the find bar overlay.
+//
+// Matches are highlighted by splitting each matching text node into plain
+// text and `<mark>` elements. The original text node is restored when the
+// search changes or the bar closes, so the page DOM is left as we found it.
+
+use blitz_dom::{BaseDocument, NodeData};
+use regex::{Regex, RegexBuilder};
+use winit::keyboard::{Key, NamedKey};
+
+use crate::{dom, overlay};
+
+pub const FIND_CASE_SENSITIVE: u32 = 1;
+pub const FIND_REGEX: u32 = 2;
+
+pub const OVERLAY_NAME: &str = "find-bar";
+
+/// Set on the `<mark>` elements wrapping matches, which aren't page content.
+pub const MATCH_ATTR: &str = "data-frontier-find-match";
+
+const BAR_STYLE: &str = "position: fixed; top: 8px; right: 16px; display: flex; gap: 12px; \
+    align-items: center; padding: 8px 12px; background: white; border: 1px solid #cbd5e1; \
+    border-radius: 8px; box-shadow: 0 8px 24px rgba(15, 23, 42, 0.18); \
+    font: 13px -apple-system, sans-serif; color: #0f172a; z-index: 2147483647;";
+const QUERY_STYLE: &str = "min-width: 180px; padding: 4px 8px; border: 1px solid #667eea; \
+    border-radius: 4px; font-family: SFMono-Regular, Consolas, monospace; white-space: pre;";
+const COUNTER_STYLE: &str = "color: #64748b; min-width: 64px; text-align: right;";
+const MATCH_STYLE: &str = "background: #fde68a; color: inherit;";
+const CURRENT_MATCH_STYLE: &str = "background: #f97316; color: white;";
+
+// Elements whose text isn't visible page content
+const SKIPPED_TAGS: &[&str] = &["head", "script", "style", "title", "template", "noscript"];
+
+/// A text node we replaced with highlighted pieces.
+struct Split {
+    original: String,
+    nodes: Vec<usize>,
+}
+
+struct Match {
+    mark: usize,
+    // The element containing the original text node, used for scrolling since
+    // it's already laid out when the marks are inserted
+    container: Option<usize>,
+}
+
+#[derive(Default)]
+pub struct FindState {
+    pub query: String,
+    pub flags: u32,
+    splits: Vec<Split>,
+    matches: Vec<Match>,
+    current: usize,
+    invalid_pattern: bool,
+}
+
+impl FindState {
+    pub fn new(flags: u32) -> Self {
+        Self {
+            flags,
+            ..Default::default()
+        }
+    }
+
+    pub fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Re-run the search for `self.query`, replacing previous highlights.
+    /// Returns the number of matches.
+    pub fn search(&mut self, doc: &mut BaseDocument) -> usize {
+        self.clear_highlights(doc);
+        self.invalid_pattern = false;
+
+        if self.query.is_empty() {
+            return 0;
+        }
+
+        let pattern = match build_pattern(&self.query, self.flags) {
+            Ok(pattern) => pattern,
+            Err(err) => {
+                tracing::debug!("Invalid find pattern {:?}: {err}", self.query);
+                self.invalid_pattern = true;
+                return 0;
+            }
+        };
+
+        for (node_id, text) in text_nodes(doc) {
+            let ranges: Vec<(usize, usize)> = pattern
+                .find_iter(&text)
+                .filter(|m| !m.is_empty())
+                .map(|m| (m.start(), m.end()))
+                .collect();
+            if !ranges.is_empty() {
+                self.highlight(doc, node_id, &text, &ranges);
+            }
+        }
+
+        self.current = 0;
+        self.update_current(doc);
+        self.matches.len()
+    }
+
+    /// Move to the next (or previous) match, wrapping around.
+    pub fn step(&mut self, doc: &mut BaseDocument, forward: bool) {
+        let count = self.matches.len();
+        if count == 0 {
+            return;
+        }
+        self.current = if forward {
+            (self.current + 1) % count
+        } else {
+            (self.current + count - 1) % count
+        };
+        self.update_current(doc);
+    }
+
+    pub fn clear_highlights(&mut self, doc: &mut BaseDocument) {
+        if self.splits.is_empty() {
+            return;
+        }
+
+        let mut mutator = doc.mutate();
+        for split in self.splits.drain(..) {
+            let Some(first) = split.nodes.first() else {
+                continue;
+            };
+            let restored = mutator.create_text_node(&split.original);
+            mutator.insert_nodes_before(*first, &[restored]);
+            for node_id in split.nodes {
+                mutator.remove_node(node_id);
+            }
+        }
+        self.matches.clear();
+        self.current = 0;
+    }
+
+    fn highlight(&mut self, doc: &mut BaseDocument, node_id: usize, text: &str, ranges: &[(usize, usize)]) {
+        let container = doc.get_node(node_id).and_then(|node| node.parent);
+        let mut mutator = doc.mutate();
+        let mut nodes = Vec::new();
+        let mut cursor = 0;
+
+        for &(start, end) in ranges {
+            if start > cursor {
+                nodes.push(mutator.create_text_node(&text[cursor..start]));
+            }
+            let mark = mutator.create_element(overlay::html_name("mark"), Vec::new());
+            mutator.set_attribute(mark, overlay::attr_name("style"), MATCH_STYLE);
+            mutator.set_attribute(mark, overlay::attr_name(MATCH_ATTR), "");
+            let inner = mutator.create_text_node(&text[start..end]);
+            mutator.append_children(mark, &[inner]);
+            nodes.push(mark);
+            self.matches.push(Match { mark, container });
+            cursor = end;
+        }
+        if cursor < text.len() {
+            nodes.push(mutator.create_text_node(&text[cursor..]));
+        }
+
+        mutator.insert_nodes_before(node_id, &nodes);
+        mutator.remove_node(node_id);
+        drop(mutator);
+
+        self.splits.push(Split {
+            original: text.to_string(),
+            nodes,
+        });
+    }
+
+    fn update_current(&mut self, doc: &mut BaseDocument) {
+        {
+            let mut mutator = doc.mutate();
+            for (index, m) in self.matches.iter().enumerate() {
+                let style = if index == self.current {
+                    CURRENT_MATCH_STYLE
+                } else {
+                    MATCH_STYLE
+                };
+                mutator.set_attribute(m.mark, overlay::attr_name("style"), style);
+            }
+        }
+
+        if let Some(container) = self.matches.get(self.current).and_then(|m| m.container) {
+            scroll_into_view(doc, container);
+        }
+    }
+
+    /// Apply a key typed while the bar is open. Returns false if the key
+    /// closes the bar.
+    pub fn handle_key(&mut self, doc: &mut BaseDocument, key: &Key, text: Option<&str>, shift: bool) -> bool {
+        match key {
+            Key::Named(NamedKey::Escape) => return false,
+            Key::Named(NamedKey::Enter) => self.step(doc, !shift),
+            Key::Named(NamedKey::Backspace) => {
+                self.query.pop();
+                self.search(doc);
+            }
+            _ => {
+                if let Some(text) = text.filter(|text| !text.chars().any(char::is_control)) {
+                    self.query.push_str(text);
+                    self.search(doc);
+                }
+            }
+        }
+        true
+    }
+
+    fn counter_text(&self) -> String {
+        if self.invalid_pattern {
+            "Invalid pattern".to_string()
+        } else if self.query.is_empty() {
+            String::new()
+        } else if self.matches.is_empty() {
+            "No matches".to_string()
+        } else {
+            format!("{} of {}", self.current + 1, self.matches.len())
+        }
+    }
+
+    /// Draw (or redraw) the find bar for the current query and position.
+    pub fn render_bar(&self, doc: &mut BaseDocument) {
+        let counter = self.counter_text();
+        let query = if self.query.is_empty() {
+            "Find in page"
+        } else {
+            &self.query
+        };
+        let mode = match (self.flags & FIND_CASE_SENSITIVE != 0, self.flags & FIND_REGEX != 0) {
+            (false, false) => "",
+            (true, false) => "Aa",
+            (false, true) => ".*",
+            (true, true) => "Aa .*",
+        };
+
+        overlay::mount(doc, OVERLAY_NAME, BAR_STYLE, |mutator, bar| {
+            overlay::append_element(mutator, bar, "span", &[("style", QUERY_STYLE)], Some(query));
+            if !mode.is_empty() {
+                overlay::append_element(mutator, bar, "span", &[("style", COUNTER_STYLE)], Some(mode));
+            }
+            overlay::append_element(mutator, bar, "span", &[("style", COUNTER_STYLE)], Some(&counter));
+        });
+    }
+
+    /// Remove highlights and the bar.
+    pub fn close(&mut self, doc: &mut BaseDocument) {
+        self.clear_highlights(doc);
+        overlay::remove(doc, OVERLAY_NAME);
+    }
+}
+
+fn build_pattern(query: &str, flags: u32) -> Result<Regex, regex::Error> {
+    let source = if flags & FIND_REGEX != 0 {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    RegexBuilder::new(&source)
+        .case_insensitive(flags & FIND_CASE_SENSITIVE == 0)
+        .build()
+}
+
+/// Visible text nodes in document order.
+fn text_nodes(doc: &BaseDocument) -> Vec<(usize, String)> {
+    let mut found = Vec::new();
+    let mut stack = vec![0usize];
+
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+
+        if let NodeData::Text(text) = &node.data {
+            if !text.content.trim().is_empty() {
+                found.push((node_id, text.content.clone()));
+            }
+            continue;
+        }
+
+        if let Some(tag) = dom::tag_name(doc, node_id) {
+            if SKIPPED_TAGS.contains(&tag) || dom::attr(doc, node_id, overlay::OVERLAY_ATTR).is_some() {
+                continue;
+            }
+        }
+
+        stack.extend(node.children.iter().rev().copied());
+    }
+
+    found
+}
+
+/// Scroll the viewport so `node_id` sits about a third of the way down.
+fn scroll_into_view(doc: &mut BaseDocument, node_id: usize) {
+    let Some(node) = doc.get_node(node_id) else {
+        return;
+    };
+    let position = node.absolute_position(0.0, 0.0);
+
+    let viewport = doc.viewport();
+    let viewport_height = viewport.window_size.1 as f64 / viewport.scale_f64() / viewport.zoom() as f64;
+    let current = doc.viewport_scroll();
+    let target = position.y as f64 - viewport_height / 3.0;
+
+    doc.scroll_viewport_by(0.0, target - current.y);
+}
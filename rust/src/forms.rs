@@ -0,0 +1,270 @@
+// Reading and filling in form controls: the values actions report with a
+// submit, and the host's read-back and pre-fill API.
+//
+// Controls are keyed by `name`, falling back to `id`. Text fields and selects
+// have string values, radio groups the checked radio's value (or null), a
+// lone checkbox a bool and a group of same-named checkboxes an array of the
+// checked values. `write` accepts the same shapes back.
+
+use std::collections::BTreeMap;
+
+use blitz_dom::BaseDocument;
+use serde_json::{Map, Value};
+
+use crate::dom;
+use crate::dom_edit::{self, InvalidSelector};
+use crate::overlay;
+
+pub fn input_type(doc: &BaseDocument, node_id: usize) -> Option<String> {
+    (dom::tag_name(doc, node_id) == Some("input"))
+        .then(|| dom::attr(doc, node_id, "type").unwrap_or("text").to_ascii_lowercase())
+}
+
+pub fn is_text_field(doc: &BaseDocument, node_id: usize) -> bool {
+    if dom::tag_name(doc, node_id) == Some("textarea") {
+        return true;
+    }
+    match input_type(doc, node_id) {
+        Some(kind) => !matches!(
+            kind.as_str(),
+            "checkbox" | "radio" | "submit" | "button" | "reset" | "image" | "file" | "hidden"
+        ),
+        None => false,
+    }
+}
+
+/// Current value of a form control.
+pub fn value(doc: &BaseDocument, node_id: usize) -> Option<String> {
+    let element = doc.get_node(node_id)?.element_data()?;
+    if let Some(input) = element.text_input_data() {
+        return Some(input.editor.text().to_string());
+    }
+    if dom::tag_name(doc, node_id) == Some("select") {
+        return selected_option(doc, node_id).and_then(|option| option_value(doc, option));
+    }
+    dom::attr(doc, node_id, "value").map(str::to_owned)
+}
+
+pub fn checked(doc: &BaseDocument, node_id: usize) -> Option<bool> {
+    doc.get_node(node_id)?.element_data()?.checkbox_input_checked()
+}
+
+fn options(doc: &BaseDocument, select: usize) -> Vec<usize> {
+    descendants(doc, select)
+        .into_iter()
+        .filter(|id| dom::tag_name(doc, *id) == Some("option"))
+        .collect()
+}
+
+fn selected_option(doc: &BaseDocument, select: usize) -> Option<usize> {
+    let options = options(doc, select);
+    options
+        .iter()
+        .copied()
+        .find(|id| dom::attr(doc, *id, "selected").is_some())
+        .or_else(|| options.first().copied())
+}
+
+fn option_value(doc: &BaseDocument, option: usize) -> Option<String> {
+    dom::attr(doc, option, "value")
+        .map(str::to_owned)
+        .or_else(|| Some(doc.get_node(option)?.text_content().trim().to_string()))
+}
+
+pub fn descendants(doc: &BaseDocument, node_id: usize) -> Vec<usize> {
+    let mut found = Vec::new();
+    let mut stack = vec![node_id];
+    while let Some(id) = stack.pop() {
+        let Some(node) = doc.get_node(id) else {
+            continue;
+        };
+        if id != node_id {
+            found.push(id);
+        }
+        stack.extend(node.children.iter().rev().copied());
+    }
+    found
+}
+
+/// Named control values in `form`, like a form submission would send them.
+/// Names that appear more than once (checkbox groups) become arrays.
+pub fn submission(doc: &BaseDocument, form: usize) -> BTreeMap<String, Value> {
+    let mut values: BTreeMap<String, Value> = BTreeMap::new();
+
+    for id in descendants(doc, form) {
+        let Some(tag) = dom::tag_name(doc, id) else {
+            continue;
+        };
+        if !matches!(tag, "input" | "select" | "textarea") {
+            continue;
+        }
+        let Some(name) = dom::attr(doc, id, "name").filter(|name| !name.is_empty()) else {
+            continue;
+        };
+        if dom::attr(doc, id, "disabled").is_some() {
+            continue;
+        }
+
+        let value = match input_type(doc, id).as_deref() {
+            Some("submit" | "button" | "reset" | "image" | "file") => continue,
+            Some("checkbox" | "radio") => {
+                if checked(doc, id) != Some(true) {
+                    continue;
+                }
+                dom::attr(doc, id, "value").unwrap_or("on").to_string()
+            }
+            _ => value(doc, id).unwrap_or_default(),
+        };
+
+        match values.get_mut(name) {
+            Some(Value::Array(existing)) => existing.push(Value::String(value)),
+            Some(existing) => {
+                let first = existing.take();
+                *existing = Value::Array(vec![first, Value::String(value)]);
+            }
+            None => {
+                values.insert(name.to_string(), Value::String(value));
+            }
+        }
+    }
+
+    values
+}
+
+struct Control {
+    node_id: usize,
+    key: String,
+    kind: String,
+}
+
+/// Form controls inside `scope` (the whole document if `None`), grouped by
+/// key in document order. `None` if the selector matches nothing.
+fn controls(
+    doc: &BaseDocument,
+    scope: Option<&str>,
+) -> Result<Option<BTreeMap<String, Vec<Control>>>, InvalidSelector> {
+    let root = match scope {
+        Some(selector) => match dom_edit::select(doc, selector)?.first() {
+            Some(root) => *root,
+            None => return Ok(None),
+        },
+        None => 0,
+    };
+
+    let mut grouped: BTreeMap<String, Vec<Control>> = BTreeMap::new();
+    for node_id in descendants(doc, root) {
+        let kind = match dom::tag_name(doc, node_id) {
+            Some("input") => input_type(doc, node_id).unwrap_or_default(),
+            Some(tag @ ("select" | "textarea")) => tag.to_string(),
+            _ => continue,
+        };
+        if matches!(kind.as_str(), "submit" | "button" | "reset" | "image" | "file") || overlay::contains(doc, node_id) {
+            continue;
+        }
+        let key = dom::attr(doc, node_id, "name")
+            .filter(|name| !name.is_empty())
+            .or_else(|| dom::attr(doc, node_id, "id"));
+        let Some(key) = key else {
+            continue;
+        };
+        grouped.entry(key.to_string()).or_default().push(Control {
+            node_id,
+            key: key.to_string(),
+            kind,
+        });
+    }
+    Ok(Some(grouped))
+}
+
+/// Current values of the controls in `scope`, as a JSON object.
+pub fn read(doc: &BaseDocument, scope: Option<&str>) -> Result<Option<Map<String, Value>>, InvalidSelector> {
+    let Some(grouped) = controls(doc, scope)? else {
+        return Ok(None);
+    };
+
+    let mut values = Map::new();
+    for (key, group) in grouped {
+        let value = match group[0].kind.as_str() {
+            "radio" => group
+                .iter()
+                .find(|control| checked(doc, control.node_id) == Some(true))
+                .and_then(|control| dom::attr(doc, control.node_id, "value"))
+                .map_or(Value::Null, |value| Value::String(value.to_string())),
+            "checkbox" if group.len() == 1 => Value::Bool(checked(doc, group[0].node_id) == Some(true)),
+            "checkbox" => Value::Array(
+                group
+                    .iter()
+                    .filter(|control| checked(doc, control.node_id) == Some(true))
+                    .map(|control| Value::String(dom::attr(doc, control.node_id, "value").unwrap_or("on").to_string()))
+                    .collect(),
+            ),
+            _ => Value::String(value(doc, group[0].node_id).unwrap_or_default()),
+        };
+        values.insert(key, value);
+    }
+    Ok(Some(values))
+}
+
+/// Fill in the controls in `scope` from a JSON object shaped like `read`'s
+/// output. Keys without a matching control are ignored. Returns how many
+/// controls were set, or `None` if the selector matches nothing.
+pub fn write(
+    doc: &mut BaseDocument,
+    scope: Option<&str>,
+    values: &Map<String, Value>,
+) -> Result<Option<usize>, InvalidSelector> {
+    let Some(grouped) = controls(doc, scope)? else {
+        return Ok(None);
+    };
+
+    let mut set = 0;
+    for control in grouped.values().flatten() {
+        let Some(wanted) = values.get(&control.key) else {
+            continue;
+        };
+        match control.kind.as_str() {
+            "checkbox" | "radio" => {
+                let own_value = dom::attr(doc, control.node_id, "value").unwrap_or("on").to_string();
+                let on = match wanted {
+                    Value::Bool(on) => *on,
+                    Value::String(value) => *value == own_value,
+                    Value::Array(values) => values.iter().any(|value| value.as_str() == Some(own_value.as_str())),
+                    _ => false,
+                };
+                let mut mutator = doc.mutate();
+                if on {
+                    mutator.set_attribute(control.node_id, overlay::attr_name("checked"), "");
+                } else {
+                    mutator.clear_attribute(control.node_id, overlay::attr_name("checked"));
+                }
+            }
+            "select" => {
+                let wanted = scalar(wanted);
+                let options = options(doc, control.node_id);
+                let values: Vec<Option<String>> = options.iter().map(|option| option_value(doc, *option)).collect();
+                let mut mutator = doc.mutate();
+                for (option, value) in options.iter().zip(values) {
+                    if value.as_deref() == Some(wanted.as_str()) {
+                        mutator.set_attribute(*option, overlay::attr_name("selected"), "");
+                    } else {
+                        mutator.clear_attribute(*option, overlay::attr_name("selected"));
+                    }
+                }
+            }
+            _ => {
+                doc.mutate()
+                    .set_attribute(control.node_id, overlay::attr_name("value"), &scalar(wanted));
+            }
+        }
+        set += 1;
+    }
+    Ok(Some(set))
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,120 @@
+// Offscreen rendering without a winit window or GPU.
+//
+// Documents are built the same way as for a window (same navigation provider
+// setup), laid out at a fixed viewport and painted with Vello's CPU renderer.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use anyrender::render_to_buffer;
+use anyrender_vello_cpu::VelloCpuImageRenderer;
+use blitz_html::HtmlDocument;
+use blitz_paint::paint_scene;
+use blitz_traits::shell::{ColorScheme, Viewport};
+
+use crate::{NavigationState, Tab};
+
+#[derive(Debug)]
+pub enum HeadlessError {
+    InvalidSize { width: u32, height: u32, scale: f64 },
+    Encode(png::EncodingError),
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeadlessError::InvalidSize { width, height, scale } => {
+                write!(f, "invalid viewport {width}x{height} at scale {scale}")
+            }
+            HeadlessError::Encode(err) => write!(f, "PNG encoding failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for HeadlessError {}
+
+impl From<png::EncodingError> for HeadlessError {
+    fn from(err: png::EncodingError) -> Self {
+        HeadlessError::Encode(err)
+    }
+}
+
+/// An RGBA8 image, row-major with no padding.
+pub struct RenderedImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Build a document for `html` the same way `run_event_loop` does.
+pub(crate) fn build_document(html: &str, url: &str) -> (HtmlDocument, Arc<Mutex<NavigationState>>) {
+    let state = Arc::new(Mutex::new(NavigationState::new(html.to_owned(), url.to_owned())));
+    let tab = Tab::new(state.clone(), None);
+    (tab.create_document(), state)
+}
+
+/// Physical pixel size for a CSS viewport at `scale`.
+fn physical_size(width: u32, height: u32, scale: f64) -> Result<(u32, u32), HeadlessError> {
+    let invalid = || HeadlessError::InvalidSize { width, height, scale };
+    if width == 0 || height == 0 || !scale.is_finite() || scale <= 0.0 {
+        return Err(invalid());
+    }
+
+    let physical_width = (width as f64 * scale).round() as u32;
+    let physical_height = (height as f64 * scale).round() as u32;
+    if physical_width == 0 || physical_height == 0 {
+        return Err(invalid());
+    }
+    Ok((physical_width, physical_height))
+}
+
+/// Lay out `document` for a `width`x`height` CSS pixel viewport.
+pub(crate) fn layout(document: &mut HtmlDocument, width: u32, height: u32, scale: f64) -> Result<(u32, u32), HeadlessError> {
+    let (physical_width, physical_height) = physical_size(width, height, scale)?;
+    document.set_viewport(Viewport::new(
+        physical_width,
+        physical_height,
+        scale as f32,
+        ColorScheme::Light,
+    ));
+    document.resolve(0.0);
+    Ok((physical_width, physical_height))
+}
+
+/// Paint an already laid out document.
+pub(crate) fn paint(document: &HtmlDocument, scale: f64) -> RenderedImage {
+    let viewport = document.viewport();
+    let (width, height) = viewport.window_size;
+    let pixels = render_to_buffer::<VelloCpuImageRenderer, _>(
+        |scene| paint_scene(scene, document, scale, width, height),
+        width,
+        height,
+    );
+    RenderedImage { width, height, pixels }
+}
+
+/// Render `html` to an RGBA image. `width` and `height` are in CSS pixels;
+/// the image is `scale` times larger.
+pub fn render(html: &str, url: &str, width: u32, height: u32, scale: f64) -> Result<RenderedImage, HeadlessError> {
+    let (mut document, _state) = build_document(html, url);
+    layout(&mut document, width, height, scale)?;
+    Ok(paint(&document, scale))
+}
+
+pub fn encode_png(image: &RenderedImage) -> Result<Vec<u8>, HeadlessError> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, image.width, image.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&image.pixels)?;
+        writer.finish()?;
+    }
+    Ok(out)
+}
+
+pub fn render_to_png(html: &str, url: &str, width: u32, height: u32, scale: f64) -> Result<Vec<u8>, HeadlessError> {
+    let image = render(html, url, width, height, scale)?;
+    encode_png(&image)
+}
@@ -0,0 +1,397 @@
+// Browsing history, kept across restarts in `history.jsonl` in the profile
+// directory and shown at `about:history`.
+//
+// Every committed navigation appends one visit line to the file, so a crash
+// loses at most the visit being written. Deleting rewrites the file
+// atomically. Loading keeps only the newest `MAX_VISITS` visits, compacting
+// the file if it had more. Queries read per-URL entries that are kept up to
+// date as visits come and go, so searching doesn't regroup every visit.
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{about, profile};
+
+pub const FILE_NAME: &str = "history.jsonl";
+
+pub const PAGE_URL: &str = "about:history";
+
+const ACTION_PREFIX: &str = "frontier://history/";
+
+/// How many visits are kept. Older ones are dropped when history is loaded.
+pub const MAX_VISITS: usize = 10_000;
+
+/// How a navigation was started.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transition {
+    Link,
+    /// A URL entered in the command palette.
+    Typed,
+    FormSubmit,
+    Reload,
+    BackForward,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Visit {
+    pub url: String,
+    pub title: String,
+    /// Milliseconds since the Unix epoch.
+    pub time: i64,
+    pub transition: Transition,
+}
+
+/// All visits to one URL.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Entry {
+    pub url: String,
+    /// The most recent non-empty title.
+    pub title: String,
+    pub last_visit: i64,
+    pub visit_count: usize,
+    pub last_transition: Transition,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Every word appears in the title or URL, ignoring case.
+    Text,
+    /// The URL starts with the query, ignoring the scheme and `www.`.
+    Prefix,
+}
+
+impl SearchMode {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Text),
+            1 => Some(Self::Prefix),
+            _ => None,
+        }
+    }
+}
+
+struct Store {
+    path: PathBuf,
+    visits: Vec<Visit>,
+    /// `visits` grouped by URL.
+    entries: HashMap<String, Entry>,
+}
+
+/// The store for the current profile directory, reloaded if it changed.
+fn store() -> MutexGuard<'static, Store> {
+    static STORE: OnceLock<Mutex<Store>> = OnceLock::new();
+    let mut store = STORE
+        .get_or_init(|| {
+            Mutex::new(Store {
+                path: PathBuf::new(),
+                visits: Vec::new(),
+                entries: HashMap::new(),
+            })
+        })
+        .lock()
+        .unwrap();
+
+    let path = profile::path(FILE_NAME);
+    if store.path != path {
+        let mut visits = load(&path);
+        let excess = visits.len().saturating_sub(MAX_VISITS);
+        visits.drain(..excess);
+        store.entries = group(&visits);
+        store.visits = visits;
+        store.path = path;
+        if excess > 0 {
+            rewrite(&store);
+        }
+    }
+    store
+}
+
+fn group(visits: &[Visit]) -> HashMap<String, Entry> {
+    let mut entries = HashMap::new();
+    for visit in visits {
+        add_visit(&mut entries, visit);
+    }
+    entries
+}
+
+fn add_visit(entries: &mut HashMap<String, Entry>, visit: &Visit) {
+    let entry = entries.entry(visit.url.clone()).or_insert_with(|| Entry {
+        url: visit.url.clone(),
+        title: String::new(),
+        last_visit: visit.time,
+        visit_count: 0,
+        last_transition: visit.transition,
+    });
+    entry.visit_count += 1;
+    if visit.time >= entry.last_visit {
+        entry.last_visit = visit.time;
+        entry.last_transition = visit.transition;
+    }
+    if !visit.title.is_empty() {
+        entry.title = visit.title.clone();
+    }
+}
+
+fn load(path: &PathBuf) -> Vec<Visit> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Vec::new(),
+        Err(err) => {
+            tracing::warn!("Failed to read {}: {err}", path.display());
+            return Vec::new();
+        }
+    };
+    // A line cut short by a crash is skipped rather than losing the rest
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(visit) => Some(visit),
+            Err(err) => {
+                tracing::warn!("Skipping unreadable history line: {err}");
+                None
+            }
+        })
+        .collect()
+}
+
+fn append(path: &PathBuf, visit: &Visit) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(visit).map_err(io::Error::other)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+fn rewrite(store: &Store) {
+    let mut contents = String::new();
+    for visit in &store.visits {
+        if let Ok(line) = serde_json::to_string(visit) {
+            contents.push_str(&line);
+            contents.push('\n');
+        }
+    }
+    if let Err(err) = profile::write_atomic(&store.path, contents.as_bytes()) {
+        tracing::error!("Failed to save history to {}: {err}", store.path.display());
+    }
+}
+
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// Whether visits to `url` belong in history. Built-in pages don't.
+fn is_recorded(url: &str) -> bool {
+    ["http:", "https:", "file:"].iter().any(|scheme| url.starts_with(scheme))
+}
+
+/// Record a committed navigation to `url`.
+pub fn record(url: &str, title: &str, transition: Transition) {
+    if !is_recorded(url) {
+        return;
+    }
+    let visit = Visit {
+        url: url.to_string(),
+        title: title.to_string(),
+        time: now(),
+        transition,
+    };
+    let mut store = store();
+    if let Err(err) = append(&store.path, &visit) {
+        tracing::error!("Failed to record visit to {}: {err}", url);
+    }
+    add_visit(&mut store.entries, &visit);
+    store.visits.push(visit);
+}
+
+/// Entries matching `query`, most recently visited first. An empty query
+/// matches everything.
+pub fn search(query: &str, mode: SearchMode, limit: usize) -> Vec<Entry> {
+    let query = query.trim().to_lowercase();
+    let store = store();
+    let mut entries: Vec<&Entry> = store
+        .entries
+        .values()
+        .filter(|entry| match mode {
+            SearchMode::Prefix => strip_scheme(&entry.url.to_lowercase()).starts_with(strip_scheme(&query)),
+            SearchMode::Text => {
+                let haystack = format!("{} {}", entry.title, entry.url).to_lowercase();
+                query.split_whitespace().all(|word| haystack.contains(word))
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| b.last_visit.cmp(&a.last_visit));
+    entries.into_iter().take(limit).cloned().collect()
+}
+
+fn strip_scheme(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.strip_prefix("www.").unwrap_or(rest)
+}
+
+/// Delete visits made in `[from, to)` (milliseconds since the Unix epoch).
+/// Returns how many were deleted.
+pub fn delete_range(from: i64, to: i64) -> usize {
+    delete_where(|visit| visit.time >= from && visit.time < to)
+}
+
+/// Delete every visit to `url`. Returns how many were deleted.
+pub fn delete_url(url: &str) -> usize {
+    delete_where(|visit| visit.url == url)
+}
+
+fn delete_where(matches: impl Fn(&Visit) -> bool) -> usize {
+    let mut store = store();
+    let before = store.visits.len();
+    store.visits.retain(|visit| !matches(visit));
+    let deleted = before - store.visits.len();
+    if deleted > 0 {
+        store.entries = group(&store.visits);
+        rewrite(&store);
+    }
+    deleted
+}
+
+/// The `<title>` of an HTML page, for recording before it's been parsed.
+pub fn title_of(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let Some(open) = lower.find("<title") else {
+        return String::new();
+    };
+    let Some(start) = lower[open..].find('>').map(|offset| open + offset + 1) else {
+        return String::new();
+    };
+    let Some(end) = lower[start..].find("</title").map(|offset| start + offset) else {
+        return String::new();
+    };
+    let title = html[start..end]
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    title.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Handle a `frontier://history/...` link from the history page.
+pub fn handle_action(url: &str) {
+    let Some(rest) = url.strip_prefix(ACTION_PREFIX) else {
+        return;
+    };
+    let (action, query) = rest.split_once('?').unwrap_or((rest, ""));
+    match action.trim_end_matches('/') {
+        "clear" => {
+            delete_range(i64::MIN, i64::MAX);
+        }
+        "remove" => {
+            let target = url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "url")
+                .map(|(_, value)| value.into_owned());
+            match target {
+                Some(target) => {
+                    delete_url(&target);
+                }
+                None => tracing::warn!("History remove action without a url: {}", url),
+            }
+        }
+        other => tracing::warn!("Unknown history action: {}", other),
+    }
+}
+
+pub fn is_action(url: &str) -> bool {
+    url.starts_with(ACTION_PREFIX)
+}
+
+/// Whether `url` is the history page, with or without a search.
+pub fn is_page(url: &str) -> bool {
+    url == PAGE_URL || url.strip_prefix(PAGE_URL).is_some_and(|rest| rest.starts_with('?'))
+}
+
+const PAGE_LIMIT: usize = 500;
+
+/// `about:history`, optionally `about:history?q=search+terms`.
+pub fn render_page(url: &str) -> String {
+    let query = url
+        .split_once('?')
+        .and_then(|(_, query)| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "q")
+                .map(|(_, value)| value.into_owned())
+        })
+        .unwrap_or_default();
+
+    let mut rows = String::new();
+    for entry in search(&query, SearchMode::Text, PAGE_LIMIT) {
+        let title = if entry.title.is_empty() { &entry.url } else { &entry.title };
+        let remove: String = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("url", &entry.url)
+            .finish();
+        rows.push_str(&format!(
+            r#"<li class="entry">
+      <a class="title" href="{href}">{title}</a>
+      <div class="meta">{url} &middot; {count} {visits} &middot; <a href="{ACTION_PREFIX}remove?{remove}">Remove</a></div>
+    </li>
+"#,
+            href = about::escape_html(&entry.url),
+            title = about::escape_html(title),
+            url = about::escape_html(&entry.url),
+            count = entry.visit_count,
+            visits = if entry.visit_count == 1 { "visit" } else { "visits" },
+            remove = about::escape_html(&remove),
+        ));
+    }
+
+    if rows.is_empty() {
+        let message = if query.is_empty() { "No history yet" } else { "No matching pages" };
+        rows.push_str(&format!(r#"<li class="empty">{message}</li>"#));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8" />
+  <title>History</title>
+  <style>
+    body {{ font-family: -apple-system, sans-serif; margin: 0; padding: 40px; background: #f8fafc; color: #0f172a; }}
+    h1 {{ margin: 0 0 24px 0; font-size: 24px; }}
+    form {{ margin-bottom: 24px; }}
+    input {{ width: 320px; padding: 6px 8px; border: 1px solid #cbd5e1; border-radius: 6px; }}
+    ul {{ list-style: none; margin: 0; padding: 0; max-width: 720px; }}
+    .entry {{ background: white; border: 1px solid #e5e7eb; border-radius: 8px; padding: 12px 16px; margin-bottom: 8px; }}
+    .title {{ font-weight: 600; color: #0f172a; text-decoration: none; }}
+    .meta {{ font-size: 12px; color: #64748b; margin-top: 4px; word-break: break-all; }}
+    .empty {{ color: #64748b; }}
+    .clear {{ display: inline-block; margin-bottom: 16px; font-size: 13px; }}
+  </style>
+</head>
+<body>
+  <h1>History</h1>
+  <form action="{PAGE_URL}" method="get">
+    <input id="history-search" type="search" name="q" value="{query}" placeholder="Search history" />
+  </form>
+  <a class="clear" href="{ACTION_PREFIX}clear">Clear history</a>
+  <ul>
+    {rows}
+  </ul>
+</body>
+</html>
+"#,
+        query = about::escape_html(&query),
+    )
+}
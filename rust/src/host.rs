@@ -0,0 +1,32 @@
+// Events the bridge reports back to the Zig host.
+//
+// Every event is a name plus a JSON payload so new event kinds don't need
+// new symbols on the Zig side. Events may be emitted from worker threads
+// (downloads), so the host implementation must be thread-safe.
+
+use serde::Serialize;
+
+extern "C" {
+    fn frontier_host_event(
+        name_ptr: *const u8,
+        name_len: usize,
+        payload_ptr: *const u8,
+        payload_len: usize,
+    );
+}
+
+pub(crate) fn emit<T: Serialize>(name: &str, payload: &T) {
+    let payload = match serde_json::to_string(payload) {
+        Ok(json) => json,
+        Err(err) => {
+            tracing::error!("Failed to serialize host event {}: {err}", name);
+            return;
+        }
+    };
+
+    tracing::debug!("Host event {}: {}", name, payload);
+
+    unsafe {
+        frontier_host_event(name.as_ptr(), name.len(), payload.as_ptr(), payload.len());
+    }
+}
@@ -0,0 +1,372 @@
+// Keyboard shortcuts the bridge handles itself instead of passing to Blitz,
+// and the chords they're bound to (see `keymap` for the bindings).
+//
+// Kept free of window state so the native event loop and the test driver
+// decode keys the same way.
+
+use std::fmt;
+
+use winit::dpi::PhysicalPosition;
+use winit::event::{MouseButton, MouseScrollDelta, WindowEvent};
+use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey, PhysicalKey};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shortcut {
+    CommandPalette,
+    Reload,
+    Back,
+    Forward,
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    Find,
+    Bookmark,
+    NextTab,
+    PreviousTab,
+    CloseTab,
+    ScrollUp,
+    ScrollDown,
+    ScrollTop,
+    ScrollBottom,
+}
+
+impl Shortcut {
+    pub const ALL: [Shortcut; 16] = [
+        Shortcut::CommandPalette,
+        Shortcut::Reload,
+        Shortcut::Back,
+        Shortcut::Forward,
+        Shortcut::ZoomIn,
+        Shortcut::ZoomOut,
+        Shortcut::ZoomReset,
+        Shortcut::Find,
+        Shortcut::Bookmark,
+        Shortcut::NextTab,
+        Shortcut::PreviousTab,
+        Shortcut::CloseTab,
+        Shortcut::ScrollUp,
+        Shortcut::ScrollDown,
+        Shortcut::ScrollTop,
+        Shortcut::ScrollBottom,
+    ];
+
+    /// The action name keymap files use, e.g. `command-palette`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Shortcut::CommandPalette => "command-palette",
+            Shortcut::Reload => "reload",
+            Shortcut::Back => "back",
+            Shortcut::Forward => "forward",
+            Shortcut::ZoomIn => "zoom-in",
+            Shortcut::ZoomOut => "zoom-out",
+            Shortcut::ZoomReset => "zoom-reset",
+            Shortcut::Find => "find",
+            Shortcut::Bookmark => "bookmark",
+            Shortcut::NextTab => "next-tab",
+            Shortcut::PreviousTab => "previous-tab",
+            Shortcut::CloseTab => "close-tab",
+            Shortcut::ScrollUp => "scroll-up",
+            Shortcut::ScrollDown => "scroll-down",
+            Shortcut::ScrollTop => "scroll-top",
+            Shortcut::ScrollBottom => "scroll-bottom",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|shortcut| shortcut.name() == name)
+    }
+
+    /// How far a scroll shortcut moves the viewport down from `scroll_y`, in
+    /// CSS pixels. Blitz stops at the ends of the page.
+    pub fn scroll_delta(self, scroll_y: f64) -> Option<f64> {
+        match self {
+            Shortcut::ScrollUp => Some(-SCROLL_STEP),
+            Shortcut::ScrollDown => Some(SCROLL_STEP),
+            Shortcut::ScrollTop => Some(-scroll_y),
+            Shortcut::ScrollBottom => Some(f64::MAX),
+            _ => None,
+        }
+    }
+}
+
+// What `scroll-up` and `scroll-down` move, three wheel lines
+const SCROLL_STEP: f64 = 60.0;
+
+/// A key press as bindings see it: what it typed, the physical key, and the
+/// modifiers held.
+#[derive(Clone, Debug)]
+pub struct KeyPress {
+    pub key: Key,
+    pub code: Option<KeyCode>,
+    pub mods: ModifiersState,
+}
+
+/// Whether Ctrl/Cmd is held, which keeps keys away from the find bar.
+pub fn has_command_modifier(mods: ModifiersState) -> bool {
+    mods.control_key() || mods.super_key()
+}
+
+/// The key part of a chord: a character, matched against what the key types
+/// on the current layout, or a named key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyName {
+    /// Always lowercase.
+    Char(char),
+    Named(NamedKey),
+}
+
+/// A key combination like `Ctrl+K`, `Cmd+Shift+Tab` or `Enter`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chord {
+    pub mods: ModifiersState,
+    pub key: KeyName,
+}
+
+// Names accepted for named keys, with the key they are on a US layout
+const NAMED_KEYS: &[(&[&str], NamedKey, KeyCode)] = &[
+    (&["enter", "return"], NamedKey::Enter, KeyCode::Enter),
+    (&["escape", "esc"], NamedKey::Escape, KeyCode::Escape),
+    (&["backspace"], NamedKey::Backspace, KeyCode::Backspace),
+    (&["delete"], NamedKey::Delete, KeyCode::Delete),
+    (&["tab"], NamedKey::Tab, KeyCode::Tab),
+    (&["space"], NamedKey::Space, KeyCode::Space),
+    (&["up", "arrowup"], NamedKey::ArrowUp, KeyCode::ArrowUp),
+    (&["down", "arrowdown"], NamedKey::ArrowDown, KeyCode::ArrowDown),
+    (&["left", "arrowleft"], NamedKey::ArrowLeft, KeyCode::ArrowLeft),
+    (&["right", "arrowright"], NamedKey::ArrowRight, KeyCode::ArrowRight),
+    (&["home"], NamedKey::Home, KeyCode::Home),
+    (&["end"], NamedKey::End, KeyCode::End),
+    (&["pageup"], NamedKey::PageUp, KeyCode::PageUp),
+    (&["pagedown"], NamedKey::PageDown, KeyCode::PageDown),
+];
+
+impl Chord {
+    /// Parse a chord. `Cmd`, `Super` and `Meta` all mean the platform
+    /// command key, and `Mod` means `mod_key`.
+    pub fn parse(text: &str, mod_key: ModifiersState) -> Option<Chord> {
+        let mut mods = ModifiersState::empty();
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        // "Ctrl++" names the plus key
+        if text.ends_with("++") {
+            parts.truncate(parts.len() - 2);
+            parts.push("+");
+        }
+        let (key, modifiers) = parts.split_last()?;
+
+        for modifier in modifiers {
+            mods |= match modifier.to_ascii_lowercase().as_str() {
+                "mod" => mod_key,
+                "ctrl" | "control" => ModifiersState::CONTROL,
+                "cmd" | "command" | "super" | "meta" => ModifiersState::SUPER,
+                "shift" => ModifiersState::SHIFT,
+                "alt" | "option" => ModifiersState::ALT,
+                _ => return None,
+            };
+        }
+        Some(Chord {
+            mods,
+            key: parse_key(key)?,
+        })
+    }
+
+    /// The key the chord is on with a US layout.
+    pub fn code(&self) -> Option<KeyCode> {
+        match &self.key {
+            KeyName::Char(ch) => key_for_char(*ch),
+            KeyName::Named(named) => NAMED_KEYS
+                .iter()
+                .find(|(_, candidate, _)| candidate == named)
+                .map(|(_, _, code)| *code),
+        }
+    }
+
+    /// Whether a key press matches. Keys are matched by what they type, so
+    /// bindings follow the layout; keys that don't type ASCII (other
+    /// scripts, or Alt combinations on macOS) fall back to the physical key.
+    pub fn matches(&self, key: &Key, code: Option<KeyCode>, mods: ModifiersState) -> bool {
+        let logical = match key {
+            Key::Named(named) => Some(KeyName::Named(*named)),
+            Key::Character(text) => {
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(' '), None) => Some(KeyName::Named(NamedKey::Space)),
+                    (Some(ch), None) if ch.is_ascii() => Some(KeyName::Char(ch.to_ascii_lowercase())),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        match logical {
+            Some(name) => name == self.key && self.mods_match(mods),
+            None => code.is_some() && code == self.code() && self.mods == mods,
+        }
+    }
+
+    pub fn matches_press(&self, press: &KeyPress) -> bool {
+        self.matches(&press.key, press.code, press.mods)
+    }
+
+    fn mods_match(&self, mods: ModifiersState) -> bool {
+        // Shift is part of what a symbol types ("+" is Shift+= on a US
+        // layout), so it only has to match for letters and named keys
+        let shift_matters = match self.key {
+            KeyName::Char(ch) => ch.is_ascii_alphabetic(),
+            KeyName::Named(_) => true,
+        };
+        if shift_matters {
+            self.mods == mods
+        } else {
+            self.mods.difference(ModifiersState::SHIFT) == mods.difference(ModifiersState::SHIFT)
+        }
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (ModifiersState::CONTROL, "Ctrl"),
+            (ModifiersState::SUPER, "Cmd"),
+            (ModifiersState::ALT, "Alt"),
+            (ModifiersState::SHIFT, "Shift"),
+        ] {
+            if self.mods.contains(modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+        match &self.key {
+            KeyName::Char(ch) => write!(f, "{}", ch.to_ascii_uppercase()),
+            KeyName::Named(named) => write!(f, "{named:?}"),
+        }
+    }
+}
+
+/// Parse a key combination like `Ctrl+K`, `Mod+Shift+Tab` or `Enter` into
+/// the physical key it's on with a US layout.
+pub fn parse_combo(text: &str, mod_key: ModifiersState) -> Option<(ModifiersState, KeyCode)> {
+    let chord = Chord::parse(text, mod_key)?;
+    Some((chord.mods, chord.code()?))
+}
+
+fn parse_key(name: &str) -> Option<KeyName> {
+    let mut chars = name.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        if ch == ' ' {
+            return Some(KeyName::Named(NamedKey::Space));
+        }
+        return Some(KeyName::Char(ch.to_ascii_lowercase()));
+    }
+
+    let lower = name.to_ascii_lowercase();
+    match lower.as_str() {
+        "plus" => return Some(KeyName::Char('+')),
+        "minus" => return Some(KeyName::Char('-')),
+        _ => {}
+    }
+    NAMED_KEYS
+        .iter()
+        .find(|(names, _, _)| names.contains(&lower.as_str()))
+        .map(|(_, named, _)| KeyName::Named(*named))
+}
+
+/// The key that types `ch` on a US layout.
+pub fn key_for_char(ch: char) -> Option<KeyCode> {
+    Some(match ch.to_ascii_lowercase() {
+        'a' => KeyCode::KeyA,
+        'b' => KeyCode::KeyB,
+        'c' => KeyCode::KeyC,
+        'd' => KeyCode::KeyD,
+        'e' => KeyCode::KeyE,
+        'f' => KeyCode::KeyF,
+        'g' => KeyCode::KeyG,
+        'h' => KeyCode::KeyH,
+        'i' => KeyCode::KeyI,
+        'j' => KeyCode::KeyJ,
+        'k' => KeyCode::KeyK,
+        'l' => KeyCode::KeyL,
+        'm' => KeyCode::KeyM,
+        'n' => KeyCode::KeyN,
+        'o' => KeyCode::KeyO,
+        'p' => KeyCode::KeyP,
+        'q' => KeyCode::KeyQ,
+        'r' => KeyCode::KeyR,
+        's' => KeyCode::KeyS,
+        't' => KeyCode::KeyT,
+        'u' => KeyCode::KeyU,
+        'v' => KeyCode::KeyV,
+        'w' => KeyCode::KeyW,
+        'x' => KeyCode::KeyX,
+        'y' => KeyCode::KeyY,
+        'z' => KeyCode::KeyZ,
+        '0' => KeyCode::Digit0,
+        '1' => KeyCode::Digit1,
+        '2' => KeyCode::Digit2,
+        '3' => KeyCode::Digit3,
+        '4' => KeyCode::Digit4,
+        '5' => KeyCode::Digit5,
+        '6' => KeyCode::Digit6,
+        '7' => KeyCode::Digit7,
+        '8' => KeyCode::Digit8,
+        '9' => KeyCode::Digit9,
+        ' ' => KeyCode::Space,
+        '-' => KeyCode::Minus,
+        '=' | '+' => KeyCode::Equal,
+        '[' => KeyCode::BracketLeft,
+        ']' => KeyCode::BracketRight,
+        _ => return None,
+    })
+}
+
+/// The parts of a winit `WindowEvent` the bridge looks at before Blitz does.
+/// The test driver builds these directly, since winit's `KeyEvent` can't be
+/// constructed outside winit.
+#[derive(Clone, Debug)]
+pub(crate) enum WindowInput {
+    Modifiers(ModifiersState),
+    Focused,
+    CursorMoved(PhysicalPosition<f64>),
+    CursorLeft,
+    /// Vertical wheel movement in lines or pixels; positive scrolls up.
+    Wheel(f64),
+    Mouse {
+        pressed: bool,
+        button: MouseButton,
+    },
+    Key {
+        pressed: bool,
+        key: Key,
+        code: Option<KeyCode>,
+        text: Option<String>,
+    },
+    CloseRequested,
+}
+
+impl WindowInput {
+    /// `None` for events the bridge leaves entirely to Blitz.
+    pub(crate) fn from_winit(event: &WindowEvent) -> Option<Self> {
+        Some(match event {
+            WindowEvent::ModifiersChanged(modifiers) => WindowInput::Modifiers(modifiers.state()),
+            WindowEvent::Focused(true) => WindowInput::Focused,
+            WindowEvent::CursorMoved { position, .. } => WindowInput::CursorMoved(*position),
+            WindowEvent::CursorLeft { .. } => WindowInput::CursorLeft,
+            WindowEvent::MouseWheel { delta, .. } => WindowInput::Wheel(match delta {
+                MouseScrollDelta::LineDelta(_, y) => *y as f64,
+                MouseScrollDelta::PixelDelta(position) => position.y,
+            }),
+            WindowEvent::MouseInput { state, button, .. } => WindowInput::Mouse {
+                pressed: state.is_pressed(),
+                button: *button,
+            },
+            WindowEvent::KeyboardInput { event, .. } => WindowInput::Key {
+                pressed: event.state.is_pressed(),
+                key: event.logical_key.clone(),
+                code: match event.physical_key {
+                    PhysicalKey::Code(code) => Some(code),
+                    PhysicalKey::Unidentified(_) => None,
+                },
+                text: event.text.as_ref().map(|text| text.to_string()),
+            },
+            WindowEvent::CloseRequested => WindowInput::CloseRequested,
+            _ => return None,
+        })
+    }
+}
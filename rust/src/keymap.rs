@@ -0,0 +1,334 @@
+// Keyboard shortcuts, configurable with `keymap.toml` in the profile directory.
+//
+//     platform = "macos"   # Mod is Cmd; anything else makes it Ctrl
+//     mode = "normal"      # the mode windows start in
+//     timeout_ms = 1000    # how long a sequence waits for its next key
+//
+//     [bindings]
+//     "Mod+Shift+P" = "command-palette"
+//     "Mod+K" = "none"
+//     "Ctrl+X Ctrl+F" = "find"
+//
+//     [modes.normal]
+//     "g g" = "scroll-top"
+//     "i" = "mode:insert"
+//
+//     [modes.insert]
+//     "Escape" = "mode:normal"
+//
+// A binding is a chord or a space-separated sequence of them. `[bindings]`
+// apply in every mode and `[modes.<name>]` only in that mode, where they win
+// over the global ones. Bindings in the file are layered over the defaults,
+// and "none" removes one. Lookups check the file's modification time at most
+// every `CHECK_INTERVAL` and re-parse it when that changes, so edits apply
+// within a moment without a filesystem call on every key press.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Deserialize;
+use winit::keyboard::ModifiersState;
+
+use crate::input::{Chord, KeyPress, Shortcut};
+use crate::profile;
+
+pub const FILE_NAME: &str = "keymap.toml";
+
+/// The mode windows start in unless the file says otherwise.
+pub const DEFAULT_MODE: &str = "normal";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// How long lookups trust the parsed file before checking it again.
+pub const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+const DEFAULT_BINDINGS: &[(&str, Shortcut)] = &[
+    ("Mod+K", Shortcut::CommandPalette),
+    ("Mod+R", Shortcut::Reload),
+    ("Mod+[", Shortcut::Back),
+    ("Mod+]", Shortcut::Forward),
+    ("Mod+=", Shortcut::ZoomIn),
+    ("Mod++", Shortcut::ZoomIn),
+    ("Mod+-", Shortcut::ZoomOut),
+    ("Mod+0", Shortcut::ZoomReset),
+    ("Mod+F", Shortcut::Find),
+    ("Mod+D", Shortcut::Bookmark),
+    ("Mod+Tab", Shortcut::NextTab),
+    ("Mod+Shift+Tab", Shortcut::PreviousTab),
+    ("Mod+W", Shortcut::CloseTab),
+];
+
+/// Action name that unbinds a chord.
+const UNBOUND: &str = "none";
+
+/// Prefix of the actions that switch mode, e.g. `mode:insert`.
+const MODE_ACTION: &str = "mode:";
+
+/// What a binding does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    Shortcut(Shortcut),
+    /// Switch the window to the named mode.
+    Mode(String),
+}
+
+/// How the keys pressed so far match the bindings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Match {
+    Unbound,
+    /// The start of a longer binding; holds the chords typed so far.
+    Prefix(Vec<Chord>),
+    Exact(Action),
+}
+
+#[derive(Default, Deserialize)]
+struct File {
+    platform: Option<String>,
+    mode: Option<String>,
+    timeout_ms: Option<u64>,
+    #[serde(default)]
+    bindings: BTreeMap<String, String>,
+    #[serde(default)]
+    modes: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+struct Binding {
+    // `None` for bindings that apply in every mode
+    mode: Option<String>,
+    keys: Vec<Chord>,
+    // `None` unbinds the keys in `mode` even if a global binding has them
+    action: Option<Action>,
+}
+
+struct Keymap {
+    path: PathBuf,
+    // When the file was last looked at, and its modification time and
+    // length then (`None` if there was no file)
+    checked: Option<Instant>,
+    stamp: Option<(SystemTime, u64)>,
+    // What the bindings were parsed from, `None` if there was no file
+    source: Option<String>,
+    mod_key: ModifiersState,
+    start_mode: String,
+    timeout: Duration,
+    bindings: Vec<Binding>,
+}
+
+impl Keymap {
+    /// Bindings that apply in `mode`, the mode's own first.
+    fn active<'a>(&'a self, mode: &'a str) -> impl Iterator<Item = &'a Binding> {
+        let own = self.bindings.iter().filter(move |binding| binding.mode.as_deref() == Some(mode));
+        own.chain(self.bindings.iter().filter(|binding| binding.mode.is_none()))
+    }
+}
+
+fn default_mod_key() -> ModifiersState {
+    if cfg!(target_os = "macos") {
+        ModifiersState::SUPER
+    } else {
+        ModifiersState::CONTROL
+    }
+}
+
+/// The keymap for the current profile, re-read if the file changed.
+fn keymap() -> MutexGuard<'static, Keymap> {
+    static KEYMAP: OnceLock<Mutex<Keymap>> = OnceLock::new();
+    let mut keymap = KEYMAP
+        .get_or_init(|| {
+            Mutex::new(Keymap {
+                path: PathBuf::new(),
+                checked: None,
+                stamp: None,
+                source: None,
+                mod_key: default_mod_key(),
+                start_mode: DEFAULT_MODE.to_string(),
+                timeout: DEFAULT_TIMEOUT,
+                bindings: Vec::new(),
+            })
+        })
+        .lock()
+        .unwrap();
+
+    let path = profile::path(FILE_NAME);
+    let fresh = keymap.checked.is_some_and(|checked| checked.elapsed() < CHECK_INTERVAL);
+    if keymap.path == path && fresh {
+        return keymap;
+    }
+    keymap.checked = Some(Instant::now());
+
+    let stamp = stamp(&path);
+    if keymap.path == path && keymap.stamp == stamp {
+        return keymap;
+    }
+    keymap.stamp = stamp;
+
+    let source = read(&path);
+    if keymap.path != path || keymap.source != source {
+        let file = parse(&path, source.as_deref());
+        keymap.mod_key = match file.platform.as_deref() {
+            Some("macos") => ModifiersState::SUPER,
+            Some(_) => ModifiersState::CONTROL,
+            None => default_mod_key(),
+        };
+        keymap.start_mode = file.mode.clone().unwrap_or_else(|| DEFAULT_MODE.to_string());
+        keymap.timeout = file.timeout_ms.map_or(DEFAULT_TIMEOUT, Duration::from_millis);
+        keymap.bindings = bindings(&file, keymap.mod_key);
+        keymap.path = path;
+        keymap.source = source;
+    }
+    keymap
+}
+
+/// Modification time and length of the file at `path`, if there is one.
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Forget when the file was last checked, so the next lookup re-reads it.
+pub fn reload() {
+    let mut keymap = keymap();
+    keymap.checked = None;
+    keymap.stamp = None;
+}
+
+fn read(path: &Path) -> Option<String> {
+    match fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => {
+            tracing::warn!("Failed to read {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+fn parse(path: &Path, source: Option<&str>) -> File {
+    let Some(text) = source else {
+        return File::default();
+    };
+    toml::from_str(text).unwrap_or_else(|err| {
+        tracing::warn!("Ignoring unreadable keymap {}: {err}", path.display());
+        File::default()
+    })
+}
+
+/// The defaults with the file's bindings applied on top.
+fn bindings(file: &File, mod_key: ModifiersState) -> Vec<Binding> {
+    let mut bindings: Vec<Binding> = DEFAULT_BINDINGS
+        .iter()
+        .filter_map(|(chord, shortcut)| {
+            Some(Binding {
+                mode: None,
+                keys: vec![Chord::parse(chord, mod_key)?],
+                action: Some(Action::Shortcut(*shortcut)),
+            })
+        })
+        .collect();
+
+    for (text, action) in &file.bindings {
+        bind(&mut bindings, None, text, action, mod_key);
+    }
+    for (mode, table) in &file.modes {
+        for (text, action) in table {
+            bind(&mut bindings, Some(mode.as_str()), text, action, mod_key);
+        }
+    }
+    bindings
+}
+
+fn bind(bindings: &mut Vec<Binding>, mode: Option<&str>, text: &str, action: &str, mod_key: ModifiersState) {
+    let Some(keys) = parse_sequence(text, mod_key) else {
+        tracing::warn!("Ignoring keymap binding for unknown key {text:?}");
+        return;
+    };
+    let action = match action {
+        UNBOUND => None,
+        name => match parse_action(name) {
+            Some(action) => Some(action),
+            None => {
+                tracing::warn!("Ignoring keymap binding {text:?} to unknown action {name:?}");
+                return;
+            }
+        },
+    };
+    bindings.retain(|bound| !(bound.mode.as_deref() == mode && bound.keys == keys));
+    // Global bindings are simply removed; a mode keeps its "none" so it
+    // hides the global binding
+    if action.is_some() || mode.is_some() {
+        bindings.push(Binding {
+            mode: mode.map(str::to_string),
+            keys,
+            action,
+        });
+    }
+}
+
+fn parse_sequence(text: &str, mod_key: ModifiersState) -> Option<Vec<Chord>> {
+    let keys: Option<Vec<Chord>> = text.split_whitespace().map(|chord| Chord::parse(chord, mod_key)).collect();
+    keys.filter(|keys| !keys.is_empty())
+}
+
+fn parse_action(name: &str) -> Option<Action> {
+    match name.strip_prefix(MODE_ACTION) {
+        Some(mode) if !mode.is_empty() => Some(Action::Mode(mode.to_string())),
+        Some(_) => None,
+        None => Shortcut::from_name(name).map(Action::Shortcut),
+    }
+}
+
+/// Match the keys pressed so far in `mode`. A binding that matches exactly
+/// runs even if a longer one starts the same way.
+pub fn resolve(mode: &str, presses: &[KeyPress]) -> Match {
+    let keymap = keymap();
+    let starts_with = |binding: &Binding| {
+        binding.keys.len() >= presses.len()
+            && binding.keys.iter().zip(presses).all(|(chord, press)| chord.matches_press(press))
+    };
+
+    let exact = keymap
+        .active(mode)
+        .find(|binding| binding.keys.len() == presses.len() && starts_with(binding));
+    if let Some(Binding { action: Some(action), .. }) = exact {
+        return Match::Exact(action.clone());
+    }
+
+    keymap
+        .active(mode)
+        .filter(|binding| binding.action.is_some() && binding.keys.len() > presses.len())
+        .find(|binding| starts_with(binding))
+        .map_or(Match::Unbound, |binding| Match::Prefix(binding.keys[..presses.len()].to_vec()))
+}
+
+/// What `Mod` means in the current keymap: Cmd on macOS, Ctrl elsewhere.
+pub fn mod_modifier() -> ModifiersState {
+    keymap().mod_key
+}
+
+/// The mode new windows start in.
+pub fn start_mode() -> String {
+    keymap().start_mode.clone()
+}
+
+/// How long a partly typed sequence waits for its next key.
+pub fn timeout() -> Duration {
+    keymap().timeout
+}
+
+/// How to show the first keys bound to `shortcut` in the starting mode,
+/// e.g. `Ctrl+Shift+P` or `Ctrl+X Ctrl+F`.
+pub fn hint(shortcut: Shortcut) -> Option<String> {
+    let keymap = keymap();
+    let action = Some(Action::Shortcut(shortcut));
+    let binding = keymap.active(&keymap.start_mode).find(|binding| binding.action == action)?;
+    Some(join(&binding.keys))
+}
+
+/// Chords separated by spaces, the way bindings are written.
+pub fn join(keys: &[Chord]) -> String {
+    keys.iter().map(Chord::to_string).collect::<Vec<_>>().join(" ")
+}
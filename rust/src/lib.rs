@@ -1,17 +1,63 @@
+mod about;
+mod actions;
+pub mod bookmarks;
+mod commands;
+mod config;
+mod context_menu;
+mod dom;
+pub mod dom_edit;
+mod download;
+#[cfg(feature = "test-host")]
+pub mod driver;
+mod find;
+pub mod forms;
+pub mod headless;
+pub mod history;
+mod host;
+mod input;
+pub mod keymap;
+pub mod messages;
+mod overlay;
+pub mod palette;
+pub mod patch;
+mod profile;
+pub mod scripts;
+pub mod serialize;
+pub mod session;
+#[cfg(feature = "test-host")]
+pub mod scenario;
+mod sequence;
+mod shell;
+#[cfg(feature = "test-host")]
+pub mod test_host;
+mod zoom;
+
+use std::collections::HashMap;
 use std::panic;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
 
 use anyrender_vello::VelloWindowRenderer;
-use blitz_dom::DocumentConfig;
+use blitz_dom::{BaseDocument, DocumentConfig};
 use blitz_html::HtmlDocument;
-use blitz_shell::{create_default_event_loop, BlitzApplication, BlitzShellEvent, View, WindowConfig};
+use blitz_shell::{create_default_event_loop, BlitzShellEvent, View, WindowConfig};
 use blitz_traits::navigation::{NavigationProvider, NavigationOptions};
 use blitz_traits::net::Body;
+use context_menu::{ContextTarget, MenuAction};
+use find::FindState;
+use input::{KeyPress, Shortcut, WindowInput};
+use overlay::{Layer, OverlayStack};
+use palette::PaletteState;
+use sequence::{KeySequence, Step};
+use serde::Serialize;
+use shell::Shell;
 use tracing_subscriber::EnvFilter;
 use winit::application::ApplicationHandler;
-use winit::event::{Modifiers, StartCause, WindowEvent};
-use winit::event_loop::ActiveEventLoop;
-use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::dpi::PhysicalPosition;
+use winit::event::{MouseButton, StartCause, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, ControlFlow};
+use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey};
 use winit::window::{WindowAttributes, WindowId};
 
 fn init_tracing() {
@@ -30,16 +76,90 @@ pub struct NavigationState {
     current_html: String,
     current_url: String,
     pending_navigation: Option<(String, String)>, // (html, url)
+    // Session history for back/forward, `history_index` is the current entry
+    history: Vec<String>,
+    history_index: usize,
+    // Target history index while a back/forward load is in flight
+    pending_traversal: Option<usize>,
+    // Messages the page sent through `frontier-message:` URLs, reported
+    // with the window id once the event loop picks them up
+    pending_messages: Vec<messages::Message>,
+    // Scroll offset to restore once the first layout of a restored tab is done
+    restore_scroll: Option<[f64; 2]>,
+    // How the navigation in flight was started, for the history store
+    pending_transition: Option<history::Transition>,
+    // URLs that links and forms with target="_blank" asked to open
+    pending_opens: Vec<String>,
+    // Restored tab whose page isn't fetched until it's first shown
+    unloaded: bool,
 }
 
 impl NavigationState {
     fn new(html: String, url: String) -> Self {
         Self {
             current_html: html,
-            current_url: url,
+            current_url: url.clone(),
             pending_navigation: None,
+            history: vec![url],
+            history_index: 0,
+            pending_traversal: None,
+            pending_messages: Vec::new(),
+            restore_scroll: None,
+            pending_transition: None,
+            pending_opens: Vec::new(),
+            unloaded: false,
         }
     }
+
+    pub fn current_url(&self) -> &str {
+        &self.current_url
+    }
+
+    pub fn current_html(&self) -> &str {
+        &self.current_html
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    pub fn history_index(&self) -> usize {
+        self.history_index
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.history_index > 0
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        self.history_index + 1 < self.history.len()
+    }
+
+    /// Commit a navigation to `url` to session history and the history store.
+    fn record_visit(&mut self, url: &str, title: &str) {
+        let transition = self.commit_to_session_history(url);
+        history::record(url, title, transition);
+    }
+
+    fn commit_to_session_history(&mut self, url: &str) -> history::Transition {
+        let transition = self.pending_transition.take();
+
+        if let Some(index) = self.pending_traversal.take() {
+            self.history_index = index;
+            self.history[index] = url.to_owned();
+            return history::Transition::BackForward;
+        }
+
+        // Reloads don't add an entry
+        if self.history.get(self.history_index).map(String::as_str) == Some(url) {
+            return history::Transition::Reload;
+        }
+
+        self.history.truncate(self.history_index + 1);
+        self.history.push(url.to_owned());
+        self.history_index = self.history.len() - 1;
+        transition.unwrap_or(history::Transition::Link)
+    }
 }
 
 // Keyboard shortcut IDs (shared with Zig)
@@ -53,12 +173,28 @@ struct HtmlResult {
     len: usize,
 }
 
+impl HtmlResult {
+    /// Copy the bytes out and hand the buffer back to the host.
+    ///
+    /// # Safety
+    /// `self` must come from a host function and not have been freed yet.
+    unsafe fn take(self) -> Vec<u8> {
+        let bytes = std::slice::from_raw_parts(self.ptr, self.len).to_vec();
+        frontier_free_html(self.ptr, self.len);
+        bytes
+    }
+}
+
 extern "C" {
-    fn frontier_get_command_palette_html() -> HtmlResult;
     fn frontier_free_html(ptr: *const u8, len: usize);
     fn frontier_navigate_to_url(url_ptr: *const u8, url_len: usize) -> HtmlResult;
 }
 
+// URL the host's navigator page is shown under
+const COMMAND_PALETTE_URL: &str = "http://localhost/";
+
+const WINDOW_TITLE: &str = "Frontier Zig Prototype";
+
 // Allow undefined symbols for dylib (Zig will provide frontier_handle_shortcut)
 #[used]
 static _ALLOW_UNDEFINED: () = ();
@@ -66,36 +202,69 @@ static _ALLOW_UNDEFINED: () = ();
 // Navigation provider that calls into Zig
 struct FrontierNavigationProvider {
     state: Arc<Mutex<NavigationState>>,
-    event_loop_proxy: winit::event_loop::EventLoopProxy<BlitzShellEvent>,
+    // None for headless documents that have no event loop
+    event_loop_proxy: Option<winit::event_loop::EventLoopProxy<BlitzShellEvent>>,
 }
 
-impl NavigationProvider for FrontierNavigationProvider {
-    fn navigate_to(&self, options: NavigationOptions) {
-        // Check if this is a form submission with a URL input
-        let url = if let Body::Form(ref form_data) = options.document_resource {
-            // Look for a "url" field in the form data
-            if let Some(entry) = form_data.iter().find(|e| e.name == "url") {
-                let url_string = entry.value.as_ref();
-                tracing::info!("Form submitted with URL: {}", url_string);
-                url_string.to_string()
+impl FrontierNavigationProvider {
+    /// Fetch `url` (or render a built-in page) and queue the result as this
+    /// tab's pending navigation.
+    fn load(&self, url: String) {
+        tracing::info!("Navigation requested to: {}", url);
+
+        if messages::is_message_url(&url) {
+            self.send_message(&url, &[]);
+            return;
+        }
+
+        // Download and history actions are handled here and then show their
+        // page again. Only those pages may trigger them, so other pages
+        // can't cancel downloads or clear history with a link.
+        let url = if download::is_action(&url) || history::is_action(&url) {
+            let page_url = self.state.lock().unwrap().current_url.clone();
+            if download::is_action(&url) && page_url == download::PAGE_URL {
+                download::handle_action(&url);
+                download::PAGE_URL.to_string()
+            } else if history::is_action(&url) && history::is_page(&page_url) {
+                history::handle_action(&url);
+                history::PAGE_URL.to_string()
             } else {
-                options.url.to_string()
+                tracing::warn!("Ignoring {} from {}", url, page_url);
+                return;
             }
         } else {
-            options.url.to_string()
+            url
         };
 
-        tracing::info!("Navigation requested to: {}", url);
+        if let Some(html) = about::render(&url) {
+            let mut state = self.state.lock().unwrap();
+            state.pending_navigation = Some((html, url));
+            return;
+        }
 
         // Call Zig to fetch the URL and get HTML
         let html_result = unsafe {
             frontier_navigate_to_url(url.as_ptr(), url.len())
         };
 
-        let html_slice = unsafe {
-            std::slice::from_raw_parts(html_result.ptr, html_result.len)
-        };
-        let html = std::str::from_utf8(html_slice)
+        let body = unsafe { html_result.take() };
+        let body = body.as_slice();
+
+        // Zig doesn't report response headers yet, so classify from the URL and body
+        if let download::ResponseKind::Download { mime, filename } =
+            download::classify(&url, body, None, None)
+        {
+            tracing::info!("Response for {} is {}, downloading instead of rendering", url, mime);
+            {
+                let mut state = self.state.lock().unwrap();
+                state.pending_traversal = None;
+                state.pending_transition = None;
+            }
+            download::manager().start(url, body.to_vec(), mime, filename);
+            return;
+        }
+
+        let html = std::str::from_utf8(body)
             .unwrap_or("<html><body><h1>Invalid UTF-8 in navigation response</h1></body></html>")
             .to_owned();
 
@@ -112,305 +281,2587 @@ impl NavigationProvider for FrontierNavigationProvider {
         // The pending navigation will be applied in the next new_events() call
         // For now, rely on the next event loop iteration
     }
+
+    /// Queue the message a `frontier-message:` URL sends instead of navigating.
+    fn send_message(&self, url: &str, fields: &[(String, String)]) {
+        match messages::from_url(url, fields) {
+            Some(message) => self.state.lock().unwrap().pending_messages.push(message),
+            None => tracing::warn!("Ignoring message URL without a name: {}", url),
+        }
+    }
 }
 
-pub struct FrontierApplication {
-    inner: BlitzApplication<VelloWindowRenderer>,
-    keyboard_modifiers: Modifiers,
+impl NavigationProvider for FrontierNavigationProvider {
+    fn navigate_to(&self, options: NavigationOptions) {
+        // Forms posting to frontier-message: send their fields as the payload
+        if messages::is_message_url(options.url.as_str()) {
+            let fields: Vec<(String, String)> = match &options.document_resource {
+                Body::Form(form_data) => form_data
+                    .iter()
+                    .map(|entry| {
+                        let value: &str = entry.value.as_ref();
+                        (entry.name.to_string(), value.to_string())
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            self.send_message(options.url.as_str(), &fields);
+            return;
+        }
+
+        // Check if this is a form submission with a URL input
+        let url = if let Body::Form(ref form_data) = options.document_resource {
+            // Look for a "url" field in the form data
+            if let Some(entry) = form_data.iter().find(|e| e.name == "url") {
+                let url_string = entry.value.as_ref();
+                tracing::info!("Form submitted with URL: {}", url_string);
+                self.state.lock().unwrap().pending_transition = Some(history::Transition::Typed);
+                url_string.to_string()
+            } else {
+                self.state.lock().unwrap().pending_transition = Some(history::Transition::FormSubmit);
+                options.url.to_string()
+            }
+        } else {
+            options.url.to_string()
+        };
+
+        // Links and forms targeting a new browsing context are opened by the
+        // event loop, which owns the windows
+        let blank = options
+            .target
+            .as_deref()
+            .is_some_and(|target| target.eq_ignore_ascii_case("_blank"));
+        if blank {
+            let mut state = self.state.lock().unwrap();
+            state.pending_transition = None;
+            state.pending_opens.push(url);
+            return;
+        }
+
+        self.load(url);
+    }
+}
+
+/// One page inside a window, with its own navigation state and provider.
+struct Tab {
     state: Arc<Mutex<NavigationState>>,
-    last_rendered_url: String,
     nav_provider: Arc<FrontierNavigationProvider>,
 }
 
-// No custom events needed - we update documents directly in window_event()
-
-impl FrontierApplication {
+impl Tab {
     fn new(
-        blitz_proxy: winit::event_loop::EventLoopProxy<BlitzShellEvent>,
         state: Arc<Mutex<NavigationState>>,
-        nav_provider: Arc<FrontierNavigationProvider>,
+        proxy: Option<winit::event_loop::EventLoopProxy<BlitzShellEvent>>,
     ) -> Self {
+        let nav_provider = Arc::new(FrontierNavigationProvider {
+            state: state.clone(),
+            event_loop_proxy: proxy,
+        });
+        Self { state, nav_provider }
+    }
+
+    fn create_document(&self) -> HtmlDocument {
+        self.load_if_unloaded();
+        let state = self.state.lock().unwrap();
+        HtmlDocument::from_html(
+            &state.current_html,
+            DocumentConfig {
+                base_url: Some(state.current_url.clone()),
+                navigation_provider: Some(self.nav_provider.clone()),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Fetch a restored tab's page the first time it's shown.
+    fn load_if_unloaded(&self) {
+        let url = {
+            let mut state = self.state.lock().unwrap();
+            if !std::mem::take(&mut state.unloaded) {
+                return;
+            }
+            state.current_url.clone()
+        };
+        self.nav_provider.load(url);
+        let mut state = self.state.lock().unwrap();
+        if let Some((html, loaded_url)) = state.pending_navigation.take() {
+            state.current_html = html;
+            state.current_url = loaded_url;
+        }
+    }
+
+    fn current_url(&self) -> String {
+        self.state.lock().unwrap().current_url.clone()
+    }
+
+    /// Start loading the history entry `offset` steps away. Returns false if there isn't one.
+    fn traverse(&self, offset: isize) -> bool {
+        let url = {
+            let mut state = self.state.lock().unwrap();
+            let Some(target) = state.history_index.checked_add_signed(offset) else {
+                return false;
+            };
+            let Some(url) = state.history.get(target).cloned() else {
+                return false;
+            };
+            state.pending_traversal = Some(target);
+            url
+        };
+        self.nav_provider.load(url);
+        true
+    }
+
+    /// Navigate to `url` as if it had been typed into the palette.
+    fn open_typed(&self, url: String) {
+        self.state.lock().unwrap().pending_transition = Some(history::Transition::Typed);
+        self.nav_provider.load(url);
+    }
+
+    fn reload(&self) {
+        let url = self.current_url();
+        self.nav_provider.load(url);
+    }
+}
+
+/// Bridge-side state for a native window. Only the active tab's document is
+/// loaded into the Blitz view; other tabs are re-parsed when activated.
+struct BrowserWindow {
+    tabs: Vec<Tab>,
+    active_tab: usize,
+    // The window and tab of the page that opened this window
+    opener: Option<(WindowId, usize)>,
+    // Link a middle or Mod+click was pressed on, opened on release
+    background_link: Option<String>,
+    cursor_position: PhysicalPosition<f64>,
+    hovered_link: Option<String>,
+    context_menu: Option<ContextTarget>,
+    find: Option<FindState>,
+    palette: Option<PaletteState>,
+    // Which of the above are open, in the order they opened
+    overlays: OverlayStack,
+    // Key mode and any partly typed key sequence
+    keys: KeySequence,
+    // Set when we consumed a mouse press so the matching release isn't seen as a click
+    swallow_release: bool,
+    // Generation of the document in the view, see `scripts`
+    generation: u64,
+}
+
+impl BrowserWindow {
+    fn new(tab: Tab, opener: Option<(WindowId, usize)>) -> Self {
         Self {
-            inner: BlitzApplication::new(blitz_proxy),
-            keyboard_modifiers: Default::default(),
-            state,
-            last_rendered_url: String::new(),
-            nav_provider,
+            tabs: vec![tab],
+            active_tab: 0,
+            opener,
+            background_link: None,
+            cursor_position: PhysicalPosition::new(0.0, 0.0),
+            hovered_link: None,
+            context_menu: None,
+            find: None,
+            palette: None,
+            overlays: OverlayStack::default(),
+            keys: KeySequence::default(),
+            swallow_release: false,
+            generation: 0,
         }
     }
 
-    fn add_window(&mut self, window_config: WindowConfig<VelloWindowRenderer>) {
-        self.inner.add_window(window_config);
+    fn active(&self) -> &Tab {
+        &self.tabs[self.active_tab]
     }
+}
 
-    fn window_mut(&mut self) -> &mut View<VelloWindowRenderer> {
-        self.inner
-            .windows
-            .values_mut()
-            .next()
-            .expect("window available")
+#[derive(Serialize)]
+struct WindowOpenedEvent {
+    window_id: u64,
+    tab_index: usize,
+    // Set when a page opened it, `None` for tabs the user or host opened
+    opener_window_id: Option<u64>,
+    opener_tab_index: Option<usize>,
+    url: String,
+    disposition: &'static str,
+}
+
+#[derive(Serialize)]
+struct ClipboardEvent {
+    window_id: u64,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct InspectElementEvent {
+    window_id: u64,
+    page_url: String,
+    node_id: usize,
+    tag: Option<String>,
+    id: Option<String>,
+    class: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ContextMenuActionEvent {
+    id: String,
+    window_id: u64,
+    target: ContextTarget,
+}
+
+#[derive(Serialize)]
+struct ZoomChangedEvent {
+    window_id: u64,
+    origin: String,
+    level: f32,
+}
+
+#[derive(Serialize)]
+struct LinkHoveredEvent {
+    window_id: u64,
+    url: Option<String>,
+}
+
+/// What `FrontierApplication::handle_input` did with a piece of input.
+enum Dispatch {
+    /// The bridge used it up; Blitz never sees it.
+    Handled,
+    /// Pass it on to Blitz, then finish the action (if any) it started.
+    Forward(Option<actions::Pending>),
+}
+
+pub struct FrontierApplication {
+    shell: Shell,
+    keyboard_modifiers: ModifiersState,
+    windows: HashMap<WindowId, BrowserWindow>,
+    focused_window: Option<WindowId>,
+    // Windows opened since the last event, waiting for their view
+    pending_windows: Vec<BrowserWindow>,
+    last_session_save: Instant,
+    // Saved session the host asked to reopen, opened on the next user event
+    pending_restore: Option<session::Session>,
+}
+
+// No custom events needed - we update documents directly in window_event()
+
+impl FrontierApplication {
+    fn new(shell: Shell) -> Self {
+        Self {
+            shell,
+            keyboard_modifiers: ModifiersState::empty(),
+            windows: HashMap::new(),
+            focused_window: None,
+            pending_windows: Vec::new(),
+            last_session_save: Instant::now(),
+            pending_restore: None,
+        }
+    }
+
+    /// Queue `window` to be shown. Its view is created once control is back
+    /// in the event loop, see `create_pending_windows`.
+    fn open_window(&mut self, window: BrowserWindow) {
+        self.pending_windows.push(window);
+    }
+
+    /// Give windows opened since the last event a native window.
+    fn create_pending_windows(&mut self, event_loop: &ActiveEventLoop) {
+        for window in std::mem::take(&mut self.pending_windows) {
+            let Shell::Native { app, proxy } = &mut self.shell else {
+                return;
+            };
+            let document = window.active().create_document();
+            let renderer = VelloWindowRenderer::new();
+            let attrs = WindowAttributes::default().with_title(WINDOW_TITLE);
+            let config = WindowConfig::with_attributes(Box::new(document) as _, renderer, attrs);
+
+            let mut view = View::init(config, event_loop, proxy);
+            view.resume();
+            let window_id = view.window_id();
+            app.windows.insert(window_id, view);
+            self.adopt_window(window_id, window);
+        }
+    }
+
+    /// Start tracking `window` once its view exists, and tell the host about
+    /// windows a page opened.
+    fn adopt_window(&mut self, window_id: WindowId, mut window: BrowserWindow) {
+        let url = window.active().current_url();
+        if let Some(doc) = self.shell.document_mut(window_id) {
+            zoom::apply(doc, zoom::level_for(&url));
+            window.generation = scripts::document_loaded(doc, u64::from(window_id), &url);
+        }
+
+        if let Some((opener, opener_tab)) = window.opener {
+            host::emit(
+                "window-opened",
+                &WindowOpenedEvent {
+                    window_id: u64::from(window_id),
+                    tab_index: 0,
+                    opener_window_id: Some(u64::from(opener)),
+                    opener_tab_index: Some(opener_tab),
+                    url,
+                    disposition: "new_window",
+                },
+            );
+        }
+        self.windows.insert(window_id, window);
     }
 
-    fn update_document(&mut self, html: &str, url: &str) {
+    fn update_document(&mut self, window_id: WindowId, html: &str, url: &str) {
+        let Some(window) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+        window.hovered_link = None;
+        window.context_menu = None;
+        window.find = None;
+        window.palette = None;
+        window.overlays.clear();
+
+        let tab = window.active();
         let doc = HtmlDocument::from_html(
             html,
             DocumentConfig {
                 base_url: Some(url.to_string()),
-                navigation_provider: Some(self.nav_provider.clone()),
+                navigation_provider: Some(tab.nav_provider.clone()),
                 ..Default::default()
             },
         );
-        self.window_mut().replace_document(Box::new(doc) as _, false);
+        self.shell.replace_document(window_id, doc);
+        let Some(doc) = self.shell.document_mut(window_id) else {
+            return;
+        };
+        zoom::apply(doc, zoom::level_for(url));
 
         // Update state
-        let mut state_lock = self.state.lock().unwrap();
-        state_lock.current_html = html.to_owned();
-        state_lock.current_url = url.to_owned();
+        {
+            let mut state_lock = tab.state.lock().unwrap();
+            state_lock.current_html = html.to_owned();
+            state_lock.current_url = url.to_owned();
+        }
 
-        // Track last rendered
-        self.last_rendered_url = url.to_owned();
+        window.keys.reset();
+        overlay::set_key_indicator(doc, window.keys.indicator().as_deref());
+        window.generation = scripts::document_loaded(doc, u64::from(window_id), url);
     }
 
-}
-
-impl ApplicationHandler<BlitzShellEvent> for FrontierApplication {
-    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        self.inner.resumed(event_loop);
+    /// Bring the live document in line with `html` in place, keeping focus,
+    /// scroll position and form state. The URL and generation don't change.
+    fn patch_document(&mut self, window_id: WindowId, html: &str) -> Option<patch::PatchStats> {
+        self.close_find_bar(window_id);
+        self.close_context_menu(window_id, false);
+        let window = self.windows.get(&window_id)?;
+        let doc = self.shell.document_mut(window_id)?;
+
+        let stats = patch::apply(doc, html);
+        window.active().state.lock().unwrap().current_html = html.to_owned();
+        self.shell.request_redraw(window_id);
+        Some(stats)
     }
 
-    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
-        self.inner.suspended(event_loop);
+    /// Reload the active tab's document from its stored state, e.g. after switching tabs.
+    fn show_active_tab(&mut self, window_id: WindowId) {
+        let Some(window) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+        window.hovered_link = None;
+        window.context_menu = None;
+        window.find = None;
+        window.palette = None;
+        window.overlays.clear();
+        let doc = window.active().create_document();
+        let url = window.active().current_url();
+        self.shell.replace_document(window_id, doc);
+        let Some(doc) = self.shell.document_mut(window_id) else {
+            return;
+        };
+        zoom::apply(doc, zoom::level_for(&url));
+        window.keys.reset();
+        overlay::set_key_indicator(doc, window.keys.indicator().as_deref());
+        window.generation = scripts::document_loaded(doc, u64::from(window_id), &url);
     }
 
-    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: StartCause) {
-        // Check for pending navigation and apply it
-        let pending = {
-            let mut state = self.state.lock().unwrap();
-            state.pending_navigation.take()
-        };
+    fn apply_pending_navigations(&mut self) {
+        let mut to_render = Vec::new();
+        let mut to_open = Vec::new();
+
+        for (window_id, window) in &self.windows {
+            for (index, tab) in window.tabs.iter().enumerate() {
+                let (pending, sent, page_url) = {
+                    let mut state = tab.state.lock().unwrap();
+                    let sent = std::mem::take(&mut state.pending_messages);
+                    let opens = std::mem::take(&mut state.pending_opens);
+                    to_open.extend(opens.into_iter().map(|url| (*window_id, url)));
+                    (state.pending_navigation.take(), sent, state.current_url.clone())
+                };
+                for message in &sent {
+                    messages::emit(u64::from(*window_id), &page_url, message);
+                }
+                let Some((html, url)) = pending else {
+                    continue;
+                };
+
+                if index == window.active_tab {
+                    to_render.push((*window_id, tab.state.clone(), html, url));
+                } else {
+                    // Background tabs just remember the page until they're shown
+                    let mut state = tab.state.lock().unwrap();
+                    state.record_visit(&url, &history::title_of(&html));
+                    state.current_html = html;
+                    state.current_url = url;
+                }
+            }
+        }
 
-        if let Some((html, url)) = pending {
+        for (window_id, state, html, url) in to_render {
             tracing::info!("Applying pending navigation to: {}", url);
-            self.update_document(&html, &url);
+            self.update_document(window_id, &html, &url);
+            state.lock().unwrap().record_visit(&url, &history::title_of(&html));
         }
 
-        self.inner.new_events(event_loop, cause);
+        for (window_id, url) in to_open {
+            let policy = config::get().new_window_policy;
+            self.open_link(window_id, url, true, policy);
+        }
     }
 
-    fn window_event(
-        &mut self,
-        event_loop: &ActiveEventLoop,
-        window_id: WindowId,
-        event: WindowEvent,
-    ) {
-        // Track modifier keys BEFORE processing
-        if let WindowEvent::ModifiersChanged(new_state) = &event {
-            self.keyboard_modifiers = *new_state;
-            tracing::info!("Modifiers changed: ctrl={}, super={}",
-                new_state.state().control_key(),
-                new_state.state().super_key());
+    /// The link under the cursor in `window_id`, if any.
+    fn link_at_cursor(&self, window_id: WindowId) -> Option<dom::Link> {
+        let window = self.windows.get(&window_id)?;
+        let doc = self.shell.document(window_id)?;
+        let node_id = dom::hit_node(doc, window.cursor_position, self.shell.scale_factor(window_id))?;
+        dom::enclosing_link(doc, node_id, &window.active().current_url())
+    }
+
+    /// Track the link under the cursor, mirroring it into the status bar
+    /// overlay and reporting changes to the host.
+    fn update_hovered_link(&mut self, window_id: WindowId, cursor_left: bool) {
+        let Some(window) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+        let base_url = window.active().current_url();
+        let scale_factor = self.shell.scale_factor(window_id);
+        let Some(doc) = self.shell.document_mut(window_id) else {
+            return;
+        };
+
+        let hovered = if cursor_left {
+            None
+        } else {
+            match dom::hit_node(doc, window.cursor_position, scale_factor) {
+                // Hovering the status bar itself shouldn't hide it
+                Some(node_id) if overlay::contains(doc, node_id) => return,
+                Some(node_id) => dom::enclosing_link(doc, node_id, &base_url).map(|link| link.href),
+                None => None,
+            }
+        };
+
+        if hovered == window.hovered_link {
+            return;
         }
 
-        // Intercept keyboard shortcuts BEFORE passing to inner
-        // This is critical because BlitzShell consumes keyboard events
-        if let WindowEvent::KeyboardInput { event: key_event, .. } = &event {
-            tracing::info!("Keyboard input: {:?}, pressed={}", key_event.physical_key, key_event.state.is_pressed());
-            let mods = self.keyboard_modifiers.state();
+        if config::get().show_status_bar {
+            overlay::set_status_text(doc, hovered.as_deref());
+            self.shell.request_redraw(window_id);
+        }
 
-            if key_event.state.is_pressed() && (mods.control_key() || mods.super_key()) {
-                match key_event.physical_key {
-                    PhysicalKey::Code(KeyCode::KeyK) => {
-                        tracing::info!("Cmd+K detected! Calling Zig for command palette HTML");
+        host::emit(
+            "link-hovered",
+            &LinkHoveredEvent {
+                window_id: u64::from(window_id),
+                url: hovered.clone(),
+            },
+        );
+        window.hovered_link = hovered;
+    }
 
-                        // Call Zig to get the HTML
-                        let html_result = unsafe { frontier_get_command_palette_html() };
-                        let html_slice = unsafe { std::slice::from_raw_parts(html_result.ptr, html_result.len) };
-                        let html = std::str::from_utf8(html_slice).unwrap_or("<html><body>Invalid UTF-8</body></html>");
+    /// Remove the status bar from every window, once it has been disabled.
+    fn hide_status_bars(&mut self) {
+        for window_id in self.windows.keys() {
+            if let Some(doc) = self.shell.document_mut(*window_id) {
+                overlay::set_status_text(doc, None);
+                self.shell.request_redraw(*window_id);
+            }
+        }
+    }
 
-                        tracing::info!("Got HTML from Zig ({} bytes)", html.len());
+    /// Open `url` in a new browsing context according to `policy`.
+    fn open_link(
+        &mut self,
+        opener: WindowId,
+        url: String,
+        foreground: bool,
+        policy: config::NewWindowPolicy,
+    ) {
+        tracing::info!("Opening {} ({:?}, foreground={})", url, policy, foreground);
 
-                        // Update document directly
-                        self.update_document(html, "http://localhost/");
+        // Messages go to the host from the page that sent them, whatever the target
+        if messages::is_message_url(&url) {
+            if let Some(window) = self.windows.get(&opener) {
+                window.active().nav_provider.load(url);
+            }
+            return;
+        }
 
-                        // Note: Not freeing since Zig returns static strings for now
-                        // unsafe { frontier_free_html(html_result.ptr, html_result.len) };
+        let state = Arc::new(Mutex::new(NavigationState::new(String::new(), url.clone())));
+        let tab = Tab::new(state, self.shell.proxy());
+        tab.nav_provider.load(url);
 
-                        tracing::info!("Command palette toggled, not passing event to BlitzShell");
-                        return; // Don't pass to inner - we handled it
-                    }
-                    PhysicalKey::Code(KeyCode::KeyR) => {
-                        tracing::info!("Cmd+R detected (reload not implemented yet)");
-                        return;
-                    }
-                    _ => {}
+        // Apply the fetched page up front so the new context never shows a blank document
+        {
+            let mut state = tab.state.lock().unwrap();
+            if let Some((html, loaded_url)) = state.pending_navigation.take() {
+                state.commit_to_session_history(&loaded_url);
+                // Current-window opens are recorded against the opener below
+                if policy != config::NewWindowPolicy::CurrentWindow {
+                    history::record(&loaded_url, &history::title_of(&html), history::Transition::Link);
                 }
+                state.current_html = html;
+                state.current_url = loaded_url;
             }
         }
 
-        // Pass to BlitzApplication
-        self.inner.window_event(event_loop, window_id, event);
+        let opener_tab = self.windows.get(&opener).map(|window| window.active_tab);
+        match policy {
+            config::NewWindowPolicy::CurrentWindow => {
+                let (html, loaded_url) = {
+                    let state = tab.state.lock().unwrap();
+                    (state.current_html.clone(), state.current_url.clone())
+                };
+                self.update_document(opener, &html, &loaded_url);
+                if let Some(window) = self.windows.get(&opener) {
+                    window
+                        .active()
+                        .state
+                        .lock()
+                        .unwrap()
+                        .record_visit(&loaded_url, &history::title_of(&html));
+                }
+            }
+            config::NewWindowPolicy::NewTab => self.add_tab(opener, tab, foreground, opener_tab),
+            config::NewWindowPolicy::NewWindow => {
+                let opener = opener_tab.map(|opener_tab| (opener, opener_tab));
+                self.open_window(BrowserWindow::new(tab, opener));
+            }
+        }
     }
 
-    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: BlitzShellEvent) {
-        // Check for pending navigation before passing to inner handler
-        let pending = {
-            let mut state = self.state.lock().unwrap();
-            state.pending_navigation.take()
+    /// Add `tab` to the window. `opener_tab` is the tab whose page opened it, if any.
+    fn add_tab(&mut self, window_id: WindowId, tab: Tab, foreground: bool, opener_tab: Option<usize>) {
+        let Some(window) = self.windows.get_mut(&window_id) else {
+            return;
         };
-
-        if let Some((html, url)) = pending {
-            tracing::info!("Applying pending navigation to: {}", url);
-            self.update_document(&html, &url);
+        let url = tab.current_url();
+        window.tabs.push(tab);
+        let tab_index = window.tabs.len() - 1;
+        if foreground {
+            window.active_tab = tab_index;
         }
 
-        // Pass to inner handler
-        self.inner.user_event(event_loop, event);
+        host::emit(
+            "window-opened",
+            &WindowOpenedEvent {
+                window_id: u64::from(window_id),
+                tab_index,
+                opener_window_id: opener_tab.map(|_| u64::from(window_id)),
+                opener_tab_index: opener_tab,
+                url,
+                disposition: "new_tab",
+            },
+        );
+
+        if foreground {
+            self.show_active_tab(window_id);
+        }
     }
-}
 
-fn run_event_loop(
-    html: &str,
-    url: &str,
-    state: Arc<Mutex<NavigationState>>,
-) -> Result<(), winit::error::EventLoopError> {
-    let event_loop = create_default_event_loop::<BlitzShellEvent>();
-    let proxy = event_loop.create_proxy();
+    fn open_context_menu(&mut self, window_id: WindowId) {
+        let Some(window) = self.windows.get_mut(&window_id) else {
+            return;
+        };
 
-    // Create navigation provider that calls into Zig
-    let nav_provider = Arc::new(FrontierNavigationProvider {
-        state: state.clone(),
-        event_loop_proxy: proxy.clone(),
-    });
+        let page_url = window.active().current_url();
+        let (can_go_back, can_go_forward) = {
+            let state = window.active().state.lock().unwrap();
+            (state.can_go_back(), state.can_go_forward())
+        };
+        let scale_factor = self.shell.scale_factor(window_id);
+        let Some(doc) = self.shell.document_mut(window_id) else {
+            return;
+        };
 
-    let mut application = FrontierApplication::new(proxy, state.clone(), nav_provider.clone());
+        let node_id = dom::hit_node(doc, window.cursor_position, scale_factor)
+            .filter(|node_id| !overlay::contains(doc, *node_id));
+        let target = ContextTarget::from_hit(doc, node_id, &page_url);
+
+        // The menu is position: fixed, so it's placed in viewport CSS pixels
+        let logical = window.cursor_position.to_logical::<f64>(scale_factor);
+        let zoom = doc.viewport().zoom() as f64;
+        context_menu::show(doc, &target, logical.x / zoom, logical.y / zoom, can_go_back, can_go_forward);
+        window.context_menu = Some(target);
+        window.overlays.open(doc, Layer::ContextMenu);
+        self.shell.request_redraw(window_id);
+    }
 
-    let document = HtmlDocument::from_html(
-        html,
-        DocumentConfig {
-            base_url: Some(url.to_string()),
-            navigation_provider: Some(nav_provider),
-            ..Default::default()
-        },
-    );
+    /// Close the context menu, returning the action under the cursor if the
+    /// close was caused by picking an item.
+    fn close_context_menu(&mut self, window_id: WindowId, pick: bool) -> Option<(ContextTarget, MenuAction)> {
+        let window = self.windows.get_mut(&window_id)?;
+        let target = window.context_menu.take()?;
+        let scale_factor = self.shell.scale_factor(window_id);
+        let doc = self.shell.document_mut(window_id)?;
+
+        let action = if pick {
+            dom::hit_node(doc, window.cursor_position, scale_factor)
+                .filter(|node_id| context_menu::contains(doc, *node_id))
+                .and_then(|node_id| context_menu::action_at(doc, node_id))
+        } else {
+            None
+        };
 
-    let renderer = VelloWindowRenderer::new();
-    let attrs = WindowAttributes::default().with_title("Frontier Zig Prototype");
-    let window = WindowConfig::with_attributes(Box::new(document) as _, renderer, attrs);
+        context_menu::hide(doc);
+        window.overlays.close(doc, Layer::ContextMenu);
+        self.shell.request_redraw(window_id);
+        action.map(|action| (target, action))
+    }
 
-    application.add_window(window);
+    fn run_menu_action(&mut self, window_id: WindowId, target: ContextTarget, action: MenuAction) {
+        tracing::info!("Context menu action: {:?}", action);
 
-    // Update state
-    {
-        let mut state_lock = state.lock().unwrap();
-        state_lock.current_html = html.to_owned();
-        state_lock.current_url = url.to_owned();
+        match action {
+            MenuAction::OpenLinkInNewTab => {
+                if let Some(url) = target.link_url {
+                    self.open_link(window_id, url, false, config::NewWindowPolicy::NewTab);
+                }
+            }
+            MenuAction::CopyLinkAddress | MenuAction::CopyImageUrl => {
+                // The host owns the platform clipboard
+                let text = if action == MenuAction::CopyLinkAddress {
+                    target.link_url
+                } else {
+                    target.image_url
+                };
+                if let Some(text) = text {
+                    host::emit(
+                        "copy-to-clipboard",
+                        &ClipboardEvent {
+                            window_id: u64::from(window_id),
+                            text,
+                        },
+                    );
+                }
+            }
+            MenuAction::ViewSource => self.view_source(window_id),
+            MenuAction::Back | MenuAction::Forward | MenuAction::Reload => {
+                let Some(window) = self.windows.get(&window_id) else {
+                    return;
+                };
+                match action {
+                    MenuAction::Back => {
+                        window.active().traverse(-1);
+                    }
+                    MenuAction::Forward => {
+                        window.active().traverse(1);
+                    }
+                    _ => window.active().reload(),
+                }
+            }
+            MenuAction::InspectElement => {
+                let (Some(node_id), Some(doc)) = (target.node_id, self.shell.document(window_id)) else {
+                    return;
+                };
+                host::emit(
+                    "inspect-element",
+                    &InspectElementEvent {
+                        window_id: u64::from(window_id),
+                        page_url: target.page_url,
+                        node_id,
+                        tag: dom::tag_name(doc, node_id).map(str::to_owned),
+                        id: dom::attr(doc, node_id, "id").map(str::to_owned),
+                        class: dom::attr(doc, node_id, "class").map(str::to_owned),
+                    },
+                );
+            }
+            MenuAction::Host(id) => host::emit(
+                "context-menu-action",
+                &ContextMenuActionEvent {
+                    id,
+                    window_id: u64::from(window_id),
+                    target,
+                },
+            ),
+        }
     }
 
-    event_loop.run_app(&mut application)
-}
+    fn view_source(&mut self, window_id: WindowId) {
+        let Some(window) = self.windows.get(&window_id) else {
+            return;
+        };
+        let (source, url) = {
+            let state = window.active().state.lock().unwrap();
+            (state.current_html.clone(), state.current_url.clone())
+        };
 
-#[no_mangle]
-pub extern "C" fn frontier_blitz_run_static_html(html_ptr: *const u8, len: usize) -> bool {
-    init_tracing();
+        let html = format!(
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\" /><title>Source of {title}</title></head>\
+             <body style=\"margin: 0; padding: 16px;\"><pre style=\"font: 12px SFMono-Regular, Consolas, monospace; white-space: pre-wrap;\">{source}</pre></body></html>",
+            title = about::escape_html(&url),
+            source = about::escape_html(&source),
+        );
+        let state = Arc::new(Mutex::new(NavigationState::new(html, format!("view-source:{url}"))));
+        let tab = Tab::new(state, self.shell.proxy());
+        let opener_tab = self.windows.get(&window_id).map(|window| window.active_tab);
+        self.add_tab(window_id, tab, true, opener_tab);
+    }
 
-    if html_ptr.is_null() {
-        tracing::error!("frontier_blitz_run_static_html received null pointer");
-        return false;
+    /// Open a blank foreground tab, as asked for from the palette.
+    fn new_tab(&mut self, window_id: WindowId) {
+        let url = palette::NEW_TAB_URL.to_string();
+        let html = about::render(&url).unwrap_or_default();
+        let tab = Tab::new(Arc::new(Mutex::new(NavigationState::new(html, url))), self.shell.proxy());
+        self.add_tab(window_id, tab, true, None);
     }
 
-    let html_slice = unsafe { std::slice::from_raw_parts(html_ptr, len) };
-    let html_owned = match std::str::from_utf8(html_slice) {
-        Ok(content) => content.to_owned(),
+    /// The window FFI calls without a window id act on: the focused one, or any.
+    fn primary_window(&self) -> Option<WindowId> {
+        self.focused_window
+            .filter(|id| self.windows.contains_key(id))
+            .or_else(|| self.windows.keys().next().copied())
+    }
+
+    fn click_action(&self, window_id: WindowId) -> actions::Outcome {
+        let (Some(window), Some(doc)) = (self.windows.get(&window_id), self.shell.document(window_id)) else {
+            return actions::Outcome::Unhandled;
+        };
+        let node_id = dom::hit_node(doc, window.cursor_position, self.shell.scale_factor(window_id));
+        if let Some(message) = node_id.and_then(|node_id| messages::from_click(doc, node_id)) {
+            messages::emit(u64::from(window_id), &window.active().current_url(), &message);
+            return actions::Outcome::Handled;
+        }
+        actions::before_click(doc, node_id, u64::from(window_id))
+    }
+
+    fn key_action(&self, window_id: WindowId, enter: bool) -> actions::Outcome {
+        let Some(doc) = self.shell.document(window_id) else {
+            return actions::Outcome::Unhandled;
+        };
+        actions::before_key(doc, enter, u64::from(window_id))
+    }
+
+    fn finish_action(&self, window_id: WindowId, pending: actions::Pending) {
+        if let Some(doc) = self.shell.document(window_id) {
+            actions::after(doc, pending, u64::from(window_id));
+        }
+    }
+
+    /// The window a host-supplied id refers to; 0 means the primary window.
+    fn window_for_host_id(&self, id: u64) -> Option<WindowId> {
+        if id == 0 {
+            return self.primary_window();
+        }
+        self.windows.keys().find(|window_id| u64::from(**window_id) == id).copied()
+    }
+
+    /// Run `f` against the live document of the window with host id `id`,
+    /// then redraw so restyle and relayout pick up any edits.
+    fn with_document<R>(&mut self, id: u64, f: impl FnOnce(&mut BaseDocument) -> R) -> Option<R> {
+        let window_id = self.window_for_host_id(id)?;
+        let result = f(self.shell.document_mut(window_id)?);
+        self.shell.request_redraw(window_id);
+        Some(result)
+    }
+
+    /// Run `f` against the window's find state and document, then redraw the bar.
+    fn with_find<R>(
+        &mut self,
+        window_id: WindowId,
+        f: impl FnOnce(&mut FindState, &mut BaseDocument) -> R,
+    ) -> Option<R> {
+        let window = self.windows.get_mut(&window_id)?;
+        let find = window.find.as_mut()?;
+        let doc = self.shell.document_mut(window_id)?;
+
+        let result = f(find, doc);
+        find.render_bar(doc);
+        window.overlays.open(doc, Layer::Find);
+        self.shell.request_redraw(window_id);
+        Some(result)
+    }
+
+    fn open_find_bar(&mut self, window_id: WindowId) {
+        let Some(window) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+        if window.find.is_none() {
+            window.find = Some(FindState::new(0));
+        }
+        self.with_find(window_id, |_, _| ());
+    }
+
+    fn close_find_bar(&mut self, window_id: WindowId) {
+        let Some(window) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+        let Some(mut find) = window.find.take() else {
+            return;
+        };
+        if let Some(doc) = self.shell.document_mut(window_id) {
+            find.close(doc);
+            window.overlays.close(doc, Layer::Find);
+            self.shell.request_redraw(window_id);
+        }
+    }
+
+    /// Keys typed while the find bar is open edit the query instead of the page.
+    fn handle_find_key(&mut self, window_id: WindowId, key: &Key, text: Option<&str>, shift: bool) {
+        let keep_open = self.with_find(window_id, |find, doc| find.handle_key(doc, key, text, shift));
+        if keep_open == Some(false) {
+            self.close_find_bar(window_id);
+        }
+    }
+
+    /// Search the page and show the find bar. Returns the number of matches.
+    fn find_in_page(&mut self, window_id: WindowId, query: String, flags: u32) -> Option<usize> {
+        let window = self.windows.get_mut(&window_id)?;
+        let find = window.find.get_or_insert_with(|| FindState::new(flags));
+        find.flags = flags;
+        find.query = query;
+        self.with_find(window_id, |find, doc| find.search(doc))
+    }
+
+    fn toggle_palette(&mut self, window_id: WindowId) {
+        if self.windows.get(&window_id).is_some_and(|window| window.palette.is_some()) {
+            self.close_palette(window_id);
+            return;
+        }
+        let Some(window) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+        let Some(doc) = self.shell.document_mut(window_id) else {
+            return;
+        };
+        let palette = PaletteState::new();
+        palette.render(doc);
+        window.palette = Some(palette);
+        window.overlays.open(doc, Layer::Palette);
+        self.shell.request_redraw(window_id);
+    }
+
+    fn close_palette(&mut self, window_id: WindowId) {
+        let Some(window) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+        let Some(palette) = window.palette.take() else {
+            return;
+        };
+        if let Some(doc) = self.shell.document_mut(window_id) {
+            palette.close(doc);
+            window.overlays.close(doc, Layer::Palette);
+            self.shell.request_redraw(window_id);
+        }
+    }
+
+    /// Keys typed while the palette is open edit its query or pick an item.
+    fn handle_palette_key(&mut self, window_id: WindowId, key: &Key, text: Option<&str>) {
+        let Some(window) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+        let Some(doc) = self.shell.document_mut(window_id) else {
+            return;
+        };
+        let Some(palette) = window.palette.as_mut() else {
+            return;
+        };
+        let outcome = palette.handle_key(key, text);
+        if outcome == palette::Outcome::Keep {
+            palette.render(doc);
+            self.shell.request_redraw(window_id);
+            return;
+        }
+
+        self.close_palette(window_id);
+        if let palette::Outcome::Run(command) = outcome {
+            self.run_palette_command(window_id, command);
+        }
+    }
+
+    /// What a click at the cursor would do to the palette, if it's open.
+    fn palette_click_at_cursor(&self, window_id: WindowId) -> Option<palette::Outcome> {
+        let window = self.windows.get(&window_id)?;
+        let palette = window.palette.as_ref()?;
+        let doc = self.shell.document(window_id)?;
+        let outcome = match dom::hit_node(doc, window.cursor_position, self.shell.scale_factor(window_id)) {
+            Some(node_id) => palette.click(doc, node_id),
+            None => palette::Outcome::Keep,
+        };
+        Some(outcome)
+    }
+
+    /// Close `layer`, e.g. because Escape was pressed while it was on top.
+    fn dismiss_overlay(&mut self, window_id: WindowId, layer: Layer) {
+        tracing::info!("Dismissing {:?} overlay", layer);
+        match layer {
+            Layer::ContextMenu => {
+                self.close_context_menu(window_id, false);
+            }
+            Layer::Find => self.close_find_bar(window_id),
+            Layer::Palette => self.close_palette(window_id),
+        }
+    }
+
+    fn run_palette_command(&mut self, window_id: WindowId, command: palette::Command) {
+        tracing::info!("Palette command {:?}", command);
+        match command {
+            palette::Command::Shortcut(shortcut) => {
+                self.run_shortcut(window_id, shortcut);
+            }
+            palette::Command::NewTab => self.new_tab(window_id),
+            palette::Command::Open(url) => {
+                if let Some(window) = self.windows.get(&window_id) {
+                    window.active().open_typed(url);
+                }
+            }
+            palette::Command::Host(id) => {
+                if let Some(window) = self.windows.get(&window_id) {
+                    palette::run_host_command(u64::from(window_id), &window.active().current_url(), &id);
+                }
+            }
+        }
+    }
+
+    /// Snapshot every window, the primary one first.
+    fn session(&self) -> session::Session {
+        let primary = self.primary_window();
+        let mut ids: Vec<WindowId> = self.windows.keys().copied().collect();
+        ids.sort_by_key(|id| (Some(*id) != primary, u64::from(*id)));
+
+        let windows = ids
+            .iter()
+            .map(|id| session::snapshot_window(&self.windows[id], self.shell.document(*id)))
+            .collect();
+        session::Session::new(windows)
+    }
+
+    fn save_session(&mut self) -> bool {
+        self.last_session_save = Instant::now();
+        match session::save(&self.session()) {
+            Ok(()) => true,
+            Err(err) => {
+                tracing::error!("Failed to save session: {err}");
+                false
+            }
+        }
+    }
+
+    fn autosave_session(&mut self) {
+        if config::get().restore_session && self.last_session_save.elapsed() >= session::SAVE_INTERVAL {
+            self.save_session();
+        }
+    }
+
+    /// Open the windows of a saved session alongside the current ones.
+    fn restore_session(&mut self, session: session::Session) {
+        for snapshot in &session.windows {
+            if let Some(window) = session::restore_window(snapshot, self.shell.proxy()) {
+                self.open_window(window);
+            }
+        }
+    }
+
+    /// Scroll a restored tab back to where it was, once it has been laid out.
+    fn apply_restore_scroll(&mut self, window_id: WindowId) {
+        let Some(window) = self.windows.get(&window_id) else {
+            return;
+        };
+        let Some([x, y]) = window.active().state.lock().unwrap().restore_scroll.take() else {
+            return;
+        };
+        if let Some(doc) = self.shell.document_mut(window_id) {
+            doc.scroll_viewport_by(x, y);
+            self.shell.request_redraw(window_id);
+        }
+    }
+
+    /// Set the zoom for the window's page and remember it for the page's origin.
+    fn set_zoom(&mut self, window_id: WindowId, level: f32) -> bool {
+        let Some(window) = self.windows.get(&window_id) else {
+            return false;
+        };
+        let Some(doc) = self.shell.document_mut(window_id) else {
+            return false;
+        };
+
+        let level = zoom::clamp(level);
+        let url = window.active().current_url();
+        zoom::apply(doc, level);
+        zoom::remember(&url, level);
+        self.shell.request_redraw(window_id);
+
+        tracing::info!("Zoom for {} set to {:.0}%", zoom::origin_key(&url), level * 100.0);
+        host::emit(
+            "zoom-changed",
+            &ZoomChangedEvent {
+                window_id: u64::from(window_id),
+                origin: zoom::origin_key(&url),
+                level,
+            },
+        );
+        true
+    }
+
+    fn zoom_level(&self, window_id: WindowId) -> Option<f32> {
+        Some(zoom::current(self.shell.document(window_id)?))
+    }
+
+    fn step_zoom(&mut self, window_id: WindowId, zoom_in: bool) {
+        if let Some(current) = self.zoom_level(window_id) {
+            self.set_zoom(window_id, zoom::step(current, zoom_in));
+        }
+    }
+
+    fn scroll_page(&mut self, window_id: WindowId, shortcut: Shortcut) {
+        let Some(doc) = self.shell.document_mut(window_id) else {
+            return;
+        };
+        if let Some(delta) = shortcut.scroll_delta(doc.viewport_scroll().y) {
+            doc.scroll_viewport_by(0.0, delta);
+            self.shell.request_redraw(window_id);
+        }
+    }
+
+    /// Feed a key press to the window's key sequence, unless it's typing
+    /// into a text field, and update the indicator.
+    fn press_key_sequence(&mut self, window_id: WindowId, press: KeyPress) -> Step {
+        let Some(window) = self.windows.get_mut(&window_id) else {
+            return Step::Unhandled;
+        };
+        let Some(doc) = self.shell.document_mut(window_id) else {
+            return Step::Unhandled;
+        };
+
+        let shown = window.keys.indicator();
+        let step = if sequence::passes_through(doc, press.mods) {
+            window.keys.reset();
+            Step::Unhandled
+        } else {
+            window.keys.press(press, Instant::now())
+        };
+        let indicator = window.keys.indicator();
+        if indicator != shown {
+            overlay::set_key_indicator(doc, indicator.as_deref());
+            self.shell.request_redraw(window_id);
+        }
+        step
+    }
+
+    /// Drop key sequences that have waited too long for their next key.
+    fn expire_key_sequences(&mut self) {
+        let now = Instant::now();
+        for (window_id, window) in &mut self.windows {
+            if !window.keys.expire(now) {
+                continue;
+            }
+            if let Some(doc) = self.shell.document_mut(*window_id) {
+                overlay::set_key_indicator(doc, window.keys.indicator().as_deref());
+                self.shell.request_redraw(*window_id);
+            }
+        }
+    }
+
+    fn switch_tab(&mut self, window_id: WindowId, forward: bool) {
+        let Some(window) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+        let count = window.tabs.len();
+        if count < 2 {
+            return;
+        }
+        window.active_tab = if forward {
+            (window.active_tab + 1) % count
+        } else {
+            (window.active_tab + count - 1) % count
+        };
+        tracing::info!("Switched to tab {} of {}", window.active_tab + 1, count);
+        self.show_active_tab(window_id);
+    }
+
+    /// Close the active tab. Returns false if it was the window's last tab.
+    fn close_tab(&mut self, window_id: WindowId) -> bool {
+        let Some(window) = self.windows.get_mut(&window_id) else {
+            return false;
+        };
+        if window.tabs.len() < 2 {
+            return false;
+        }
+        window.tabs.remove(window.active_tab);
+        window.active_tab = window.active_tab.min(window.tabs.len() - 1);
+        self.show_active_tab(window_id);
+        true
+    }
+    /// Run a bridge keyboard shortcut. Returns false if it didn't apply, so
+    /// the key should go to Blitz as usual.
+    fn run_shortcut(&mut self, window_id: WindowId, shortcut: Shortcut) -> bool {
+        tracing::info!("Shortcut {:?}", shortcut);
+        match shortcut {
+            Shortcut::CommandPalette => self.toggle_palette(window_id),
+            Shortcut::Reload | Shortcut::Back | Shortcut::Forward => {
+                if let Some(window) = self.windows.get(&window_id) {
+                    match shortcut {
+                        Shortcut::Back => {
+                            window.active().traverse(-1);
+                        }
+                        Shortcut::Forward => {
+                            window.active().traverse(1);
+                        }
+                        _ => window.active().reload(),
+                    }
+                }
+            }
+            Shortcut::ZoomIn => self.step_zoom(window_id, true),
+            Shortcut::ZoomOut => self.step_zoom(window_id, false),
+            Shortcut::ZoomReset => {
+                self.set_zoom(window_id, zoom::DEFAULT_ZOOM);
+            }
+            Shortcut::Find => self.open_find_bar(window_id),
+            Shortcut::Bookmark => {
+                if let Some(window) = self.windows.get(&window_id) {
+                    bookmarks::bookmark_tab(window.active(), u64::from(window_id));
+                }
+            }
+            Shortcut::NextTab => self.switch_tab(window_id, true),
+            Shortcut::PreviousTab => self.switch_tab(window_id, false),
+            Shortcut::CloseTab => return self.close_tab(window_id),
+            Shortcut::ScrollUp | Shortcut::ScrollDown | Shortcut::ScrollTop | Shortcut::ScrollBottom => {
+                self.scroll_page(window_id, shortcut)
+            }
+        }
+        true
+    }
+
+    /// Everything the bridge does with window input before, or instead of,
+    /// Blitz seeing it. `window_event` and the headless `driver` both go
+    /// through here.
+    fn handle_input(&mut self, window_id: WindowId, event: WindowInput) -> Dispatch {
+        match event {
+            WindowInput::Modifiers(mods) => {
+                self.keyboard_modifiers = mods;
+                tracing::info!("Modifiers changed: ctrl={}, super={}", mods.control_key(), mods.super_key());
+            }
+            WindowInput::Focused => self.focused_window = Some(window_id),
+            WindowInput::CursorMoved(position) => {
+                if let Some(window) = self.windows.get_mut(&window_id) {
+                    window.cursor_position = position;
+                }
+                self.update_hovered_link(window_id, false);
+            }
+            WindowInput::CursorLeft => self.update_hovered_link(window_id, true),
+            WindowInput::Wheel(dy) => {
+                // Ctrl/Cmd+scroll zooms instead of scrolling
+                if input::has_command_modifier(self.keyboard_modifiers) {
+                    if dy != 0.0 {
+                        self.step_zoom(window_id, dy > 0.0);
+                    }
+                    return Dispatch::Handled;
+                }
+            }
+            WindowInput::Mouse { pressed, button } => return self.handle_mouse(window_id, pressed, button),
+            WindowInput::Key { pressed, key, code, text } => {
+                return self.handle_key(window_id, pressed, key, code, text.as_deref())
+            }
+            WindowInput::CloseRequested => {
+                // Closing the last window quits, so that's the last chance to save
+                if self.windows.len() == 1 && config::get().restore_session {
+                    self.save_session();
+                }
+                self.windows.remove(&window_id);
+                if !self.windows.is_empty() {
+                    // Other windows are still open, so don't let Blitz exit the loop
+                    self.shell.remove(window_id);
+                    return Dispatch::Handled;
+                }
+            }
+        }
+        Dispatch::Forward(None)
+    }
+
+    fn handle_mouse(&mut self, window_id: WindowId, pressed: bool, button: MouseButton) -> Dispatch {
+        let menu_open = self
+            .windows
+            .get(&window_id)
+            .is_some_and(|window| window.context_menu.is_some());
+        let palette_click = if menu_open { None } else { self.palette_click_at_cursor(window_id) };
+
+        match (pressed, button) {
+            (true, _) if palette_click.is_some() => {
+                // The palette is modal, so presses never reach the page
+                if let Some(window) = self.windows.get_mut(&window_id) {
+                    window.swallow_release = true;
+                }
+                match palette_click {
+                    Some(palette::Outcome::Run(command)) if button == MouseButton::Left => {
+                        self.close_palette(window_id);
+                        self.run_palette_command(window_id, command);
+                    }
+                    Some(palette::Outcome::Close) => self.close_palette(window_id),
+                    _ => {}
+                }
+                return Dispatch::Handled;
+            }
+            (true, MouseButton::Right) => {
+                self.close_context_menu(window_id, false);
+                self.open_context_menu(window_id);
+                return Dispatch::Handled;
+            }
+            (true, _) if menu_open => {
+                // Any click closes the menu; clicks on an item also run it
+                let picked = self.close_context_menu(window_id, button == MouseButton::Left);
+                if let Some(window) = self.windows.get_mut(&window_id) {
+                    window.swallow_release = true;
+                }
+                if let Some((target, action)) = picked {
+                    self.run_menu_action(window_id, target, action);
+                }
+                return Dispatch::Handled;
+            }
+            (false, _) => {
+                let background_link = self
+                    .windows
+                    .get_mut(&window_id)
+                    .and_then(|window| window.background_link.take());
+                if let Some(href) = background_link {
+                    let policy = config::get().new_window_policy;
+                    self.open_link(window_id, href, false, policy);
+                    return Dispatch::Handled;
+                }
+                if let Some(window) = self.windows.get_mut(&window_id) {
+                    if std::mem::take(&mut window.swallow_release) {
+                        return Dispatch::Handled;
+                    }
+                }
+            }
+            _ => {}
+        }
+        if pressed {
+            // Middle and Mod+clicks open links in the background, like other
+            // browsers. Blitz never sees the press, so it doesn't follow the
+            // link itself; it opens on release.
+            let mod_click = button == MouseButton::Left && self.keyboard_modifiers.contains(keymap::mod_modifier());
+            if button == MouseButton::Middle || mod_click {
+                if let Some(link) = self.link_at_cursor(window_id) {
+                    if let Some(window) = self.windows.get_mut(&window_id) {
+                        window.background_link = Some(link.href);
+                    }
+                    return Dispatch::Handled;
+                }
+            }
+            return Dispatch::Forward(None);
+        }
+
+        // Elements with data-frontier-action or data-frontier-message report
+        // clicks to the host instead of doing their default action
+        let mut pending_action = None;
+        if button == MouseButton::Left {
+            match self.click_action(window_id) {
+                actions::Outcome::Handled => return Dispatch::Handled,
+                actions::Outcome::Pending(pending) => pending_action = Some(pending),
+                actions::Outcome::Unhandled => {}
+            }
+        }
+
+        Dispatch::Forward(pending_action)
+    }
+
+    fn handle_key(
+        &mut self,
+        window_id: WindowId,
+        pressed: bool,
+        key: Key,
+        code: Option<KeyCode>,
+        text: Option<&str>,
+    ) -> Dispatch {
+        tracing::info!("Keyboard input: {:?}, pressed={}", code, pressed);
+        let mods = self.keyboard_modifiers;
+
+        // Escape closes the overlay that opened last, and otherwise keys
+        // go to whichever of the palette and find bar is highest, even with
+        // a context menu above it
+        let (top, top_overlay) = self
+            .windows
+            .get(&window_id)
+            .map_or((None, None), |window| (window.overlays.top(), window.overlays.top_taking_keys()));
+        if let Some(layer) = top {
+            if pressed && code == Some(KeyCode::Escape) {
+                self.dismiss_overlay(window_id, layer);
+                return Dispatch::Handled;
+            }
+        }
+
+        if top_overlay == Some(Layer::Palette) && !input::has_command_modifier(mods) {
+            if pressed {
+                self.handle_palette_key(window_id, &key, text);
+            }
+            return Dispatch::Handled;
+        }
+
+        if top_overlay == Some(Layer::Find) && !input::has_command_modifier(mods) {
+            if pressed {
+                self.handle_find_key(window_id, &key, text, mods.shift_key());
+            }
+            return Dispatch::Handled;
+        }
+
+        if !pressed {
+            return Dispatch::Forward(None);
+        }
+
+        let enter = key == Key::Named(NamedKey::Enter);
+        match self.press_key_sequence(window_id, KeyPress { key, code, mods }) {
+            Step::Run(shortcut) => {
+                if self.run_shortcut(window_id, shortcut) {
+                    return Dispatch::Handled;
+                }
+            }
+            Step::Pending | Step::Handled => return Dispatch::Handled,
+            Step::Unhandled => {}
+        }
+
+        match self.key_action(window_id, enter) {
+            actions::Outcome::Handled => Dispatch::Handled,
+            actions::Outcome::Pending(pending) => Dispatch::Forward(Some(pending)),
+            actions::Outcome::Unhandled => Dispatch::Forward(None),
+        }
+    }
+
+    /// Work queued while the loop was asleep: FFI commands, a session the
+    /// host asked to restore and finished navigations.
+    fn run_queued(&mut self) {
+        commands::drain(self);
+        if let Some(session) = self.pending_restore.take() {
+            self.restore_session(session);
+        }
+        self.apply_pending_navigations();
+    }
+}
+
+impl ApplicationHandler<BlitzShellEvent> for FrontierApplication {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if let Shell::Native { app, .. } = &mut self.shell {
+            app.resumed(event_loop);
+        }
+        self.create_pending_windows(event_loop);
+    }
+
+    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+        if let Shell::Native { app, .. } = &mut self.shell {
+            app.suspended(event_loop);
+        }
+    }
+
+    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: StartCause) {
+        // Check for pending navigation and apply it
+        self.apply_pending_navigations();
+        self.create_pending_windows(event_loop);
+        self.autosave_session();
+        self.expire_key_sequences();
+
+        if let Shell::Native { app, .. } = &mut self.shell {
+            app.new_events(event_loop, cause);
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if let Shell::Native { app, .. } = &mut self.shell {
+            app.about_to_wait(event_loop);
+        }
+
+        // Wake up when a pending key sequence times out so its indicator
+        // goes away, and go back to waiting once it has
+        let deadline = self.windows.values().filter_map(|window| window.keys.deadline()).min();
+        let control_flow = event_loop.control_flow();
+        let stale = matches!(control_flow, ControlFlow::WaitUntil(until) if until <= Instant::now());
+        if stale || control_flow == ControlFlow::Wait {
+            event_loop.set_control_flow(deadline.map_or(ControlFlow::Wait, ControlFlow::WaitUntil));
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let redraw = matches!(event, WindowEvent::RedrawRequested);
+        let dispatch = match WindowInput::from_winit(&event) {
+            Some(input) => self.handle_input(window_id, input),
+            None => Dispatch::Forward(None),
+        };
+
+        if let Dispatch::Forward(pending_action) = dispatch {
+            if let Shell::Native { app, .. } = &mut self.shell {
+                app.window_event(event_loop, window_id, event);
+            }
+            if let Some(pending) = pending_action {
+                self.finish_action(window_id, pending);
+            }
+        }
+        if redraw {
+            self.apply_restore_scroll(window_id);
+        }
+        self.create_pending_windows(event_loop);
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: BlitzShellEvent) {
+        self.run_queued();
+        if let Shell::Native { app, .. } = &mut self.shell {
+            app.user_event(event_loop, event);
+        }
+        self.create_pending_windows(event_loop);
+    }
+}
+
+fn run_event_loop(
+    html: &str,
+    url: &str,
+    state: Arc<Mutex<NavigationState>>,
+) -> Result<(), winit::error::EventLoopError> {
+    let event_loop = create_default_event_loop::<BlitzShellEvent>();
+    let proxy = event_loop.create_proxy();
+
+    let mut application = FrontierApplication::new(Shell::native(proxy.clone()));
+
+    // Update state
+    {
+        let mut state_lock = state.lock().unwrap();
+        state_lock.current_html = html.to_owned();
+        state_lock.current_url = url.to_owned();
+    }
+
+    // Reopen the last session's windows if there are any
+    let mut restored: Vec<BrowserWindow> = if config::get().restore_session {
+        session::load()
+            .map(|session| {
+                session
+                    .windows
+                    .iter()
+                    .filter_map(|snapshot| session::restore_window(snapshot, Some(proxy.clone())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    // Create navigation provider that calls into Zig
+    let tab = Tab::new(state, Some(proxy));
+    let restoring = restored.len();
+    match restored.first_mut() {
+        None => application.open_window(BrowserWindow::new(tab, None)),
+        Some(first) => {
+            tracing::info!("Restoring {} window(s) from the last session", restoring);
+            // The page the host launched with still opens, as the active tab
+            // of the first window, unless it's already showing it
+            if first.active().current_url() != url {
+                first.tabs.push(tab);
+                first.active_tab = first.tabs.len() - 1;
+            }
+            for window in restored {
+                application.open_window(window);
+            }
+        }
+    }
+
+    commands::attach(event_loop.create_proxy());
+    let result = event_loop.run_app(&mut application);
+    commands::detach();
+    result
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_run_static_html(html_ptr: *const u8, len: usize) -> bool {
+    init_tracing();
+
+    if html_ptr.is_null() {
+        tracing::error!("frontier_blitz_run_static_html received null pointer");
+        return false;
+    }
+
+    let html_slice = unsafe { std::slice::from_raw_parts(html_ptr, len) };
+    let html_owned = match std::str::from_utf8(html_slice) {
+        Ok(content) => content.to_owned(),
         Err(err) => {
             tracing::error!("frontier_blitz_run_static_html received invalid UTF-8: {err}");
             return false;
         }
     };
 
-    let state = Arc::new(Mutex::new(NavigationState::new(
-        html_owned.clone(),
-        "http://localhost/".to_string(),
-    )));
+    let state = Arc::new(Mutex::new(NavigationState::new(
+        html_owned.clone(),
+        "http://localhost/".to_string(),
+    )));
+
+    let result = panic::catch_unwind(move || run_event_loop(&html_owned, "http://localhost/", state));
+
+    match result {
+        Ok(Ok(())) => true,
+        Ok(Err(err)) => {
+            tracing::error!("frontier_blitz_run_static_html failed: {err}");
+            false
+        }
+        Err(panic_payload) => {
+            if let Some(msg) = panic_payload.downcast_ref::<&str>() {
+                tracing::error!("frontier_blitz_run_static_html panicked: {msg}");
+            } else if let Some(msg) = panic_payload.downcast_ref::<String>() {
+                tracing::error!("frontier_blitz_run_static_html panicked: {msg}");
+            } else {
+                tracing::error!("frontier_blitz_run_static_html panicked");
+            }
+            false
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_navigate(
+    html_ptr: *const u8,
+    html_len: usize,
+    url_ptr: *const u8,
+    url_len: usize,
+) -> bool {
+    init_tracing();
+
+    if html_ptr.is_null() || url_ptr.is_null() {
+        tracing::error!("frontier_blitz_navigate received null pointer");
+        return false;
+    }
+
+    let html_slice = unsafe { std::slice::from_raw_parts(html_ptr, html_len) };
+    let html_owned = match std::str::from_utf8(html_slice) {
+        Ok(content) => content.to_owned(),
+        Err(err) => {
+            tracing::error!("frontier_blitz_navigate received invalid HTML UTF-8: {err}");
+            return false;
+        }
+    };
+
+    let url_slice = unsafe { std::slice::from_raw_parts(url_ptr, url_len) };
+    let url_owned = match std::str::from_utf8(url_slice) {
+        Ok(content) => content.to_owned(),
+        Err(err) => {
+            tracing::error!("frontier_blitz_navigate received invalid URL UTF-8: {err}");
+            return false;
+        }
+    };
+
+    tracing::info!("Navigating to: {}", url_owned);
+
+    let state = Arc::new(Mutex::new(NavigationState::new(
+        html_owned.clone(),
+        url_owned.clone(),
+    )));
+
+    let result = panic::catch_unwind(move || run_event_loop(&html_owned, &url_owned, state));
+
+    match result {
+        Ok(Ok(())) => true,
+        Ok(Err(err)) => {
+            tracing::error!("frontier_blitz_navigate failed: {err}");
+            false
+        }
+        Err(panic_payload) => {
+            if let Some(msg) = panic_payload.downcast_ref::<&str>() {
+                tracing::error!("frontier_blitz_navigate panicked: {msg}");
+            } else if let Some(msg) = panic_payload.downcast_ref::<String>() {
+                tracing::error!("frontier_blitz_navigate panicked: {msg}");
+            } else {
+                tracing::error!("frontier_blitz_navigate panicked");
+            }
+            false
+        }
+    }
+}
+
+fn ffi_str<'a>(ptr: *const u8, len: usize, caller: &str, what: &str) -> Option<&'a str> {
+    if ptr.is_null() {
+        tracing::error!("{caller} received null {what} pointer");
+        return None;
+    }
+
+    let slice = unsafe { std::slice::from_raw_parts(ptr, len) };
+    match std::str::from_utf8(slice) {
+        Ok(content) => Some(content),
+        Err(err) => {
+            tracing::error!("{caller} received invalid {what} UTF-8: {err}");
+            None
+        }
+    }
+}
+
+/// Optional string argument: a null pointer means "not provided".
+fn ffi_opt_str<'a>(ptr: *const u8, len: usize, caller: &str, what: &str) -> Result<Option<&'a str>, ()> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    ffi_str(ptr, len, caller, what).map(Some).ok_or(())
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_set_download_dir(path_ptr: *const u8, path_len: usize) -> bool {
+    init_tracing();
+
+    let Some(path) = ffi_str(path_ptr, path_len, "frontier_blitz_set_download_dir", "path") else {
+        return false;
+    };
+
+    tracing::info!("Download directory set to: {}", path);
+    config::get().download_dir = PathBuf::from(path);
+    true
+}
+
+/// Start a download for content the host already fetched, e.g. when it knows
+/// the response headers. `content_type` and `disposition` may be null.
+/// Returns the download id, or 0 on failure.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn frontier_blitz_start_download(
+    url_ptr: *const u8,
+    url_len: usize,
+    body_ptr: *const u8,
+    body_len: usize,
+    content_type_ptr: *const u8,
+    content_type_len: usize,
+    disposition_ptr: *const u8,
+    disposition_len: usize,
+) -> u64 {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_start_download";
+    let Some(url) = ffi_str(url_ptr, url_len, CALLER, "URL") else {
+        return 0;
+    };
+    if body_ptr.is_null() && body_len > 0 {
+        tracing::error!("{CALLER} received null body pointer");
+        return 0;
+    }
+    let body = if body_len == 0 {
+        Vec::new()
+    } else {
+        unsafe { std::slice::from_raw_parts(body_ptr, body_len) }.to_vec()
+    };
+    let Ok(content_type) = ffi_opt_str(content_type_ptr, content_type_len, CALLER, "content type") else {
+        return 0;
+    };
+    let Ok(disposition) = ffi_opt_str(disposition_ptr, disposition_len, CALLER, "disposition") else {
+        return 0;
+    };
+
+    let (mime, filename) = match download::classify(url, &body, content_type, disposition) {
+        download::ResponseKind::Download { mime, filename } => (mime, filename),
+        // The host asked for a download explicitly, so honour it even for documents
+        download::ResponseKind::Document => (
+            content_type.unwrap_or("text/html").to_string(),
+            "download.html".to_string(),
+        ),
+    };
+
+    download::manager().start(url.to_string(), body, mime, filename)
+}
+
+/// 0 = new tab, 1 = new window, 2 = replace the current page.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_set_new_window_policy(policy: u8) -> bool {
+    init_tracing();
+
+    match config::NewWindowPolicy::from_u8(policy) {
+        Some(policy) => {
+            tracing::info!("New window policy set to {:?}", policy);
+            config::get().new_window_policy = policy;
+            true
+        }
+        None => {
+            tracing::error!("frontier_blitz_set_new_window_policy received unknown policy {policy}");
+            false
+        }
+    }
+}
+
+/// Show or hide the status bar with the hovered link's URL. Hiding it also
+/// removes one that's showing.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_set_status_bar_enabled(enabled: bool) {
+    init_tracing();
+    config::get().show_status_bar = enabled;
+    if !enabled && commands::running() {
+        commands::post("frontier_blitz_set_status_bar_enabled", |app| app.hide_status_bars());
+    }
+}
+
+/// Set which `<script type>` values are reported in `document-loaded` events,
+/// as a comma-separated list (e.g. `text/typescript,module`). Empty disables it.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_set_script_types(types_ptr: *const u8, types_len: usize) -> bool {
+    init_tracing();
+
+    let Some(types) = ffi_str(types_ptr, types_len, "frontier_blitz_set_script_types", "types") else {
+        return false;
+    };
+    config::get().script_types = types
+        .split(',')
+        .map(|kind| kind.trim().to_ascii_lowercase())
+        .filter(|kind| !kind.is_empty())
+        .collect();
+    true
+}
+
+/// Add a host item to the context menu. `contexts` is a bit set of where it
+/// appears (1 = page, 2 = link, 4 = image). Selecting it emits a
+/// `context-menu-action` event carrying `id`.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_register_context_menu_item(
+    id_ptr: *const u8,
+    id_len: usize,
+    label_ptr: *const u8,
+    label_len: usize,
+    contexts: u8,
+) -> bool {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_register_context_menu_item";
+    let Some(id) = ffi_str(id_ptr, id_len, CALLER, "id") else {
+        return false;
+    };
+    let Some(label) = ffi_str(label_ptr, label_len, CALLER, "label") else {
+        return false;
+    };
+
+    context_menu::register_host_item(id, label, contexts);
+    true
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_unregister_context_menu_item(id_ptr: *const u8, id_len: usize) -> bool {
+    init_tracing();
+
+    match ffi_str(id_ptr, id_len, "frontier_blitz_unregister_context_menu_item", "id") {
+        Some(id) => context_menu::unregister_host_item(id),
+        None => false,
+    }
+}
+
+/// Add (or replace) a host command in the command palette. `keywords` is an
+/// optional comma- or space-separated list matched along with the title, and
+/// `shortcut` an optional hint shown next to it. Picking the command emits a
+/// `palette-command` event carrying `id`.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn frontier_blitz_register_palette_command(
+    id_ptr: *const u8,
+    id_len: usize,
+    title_ptr: *const u8,
+    title_len: usize,
+    keywords_ptr: *const u8,
+    keywords_len: usize,
+    shortcut_ptr: *const u8,
+    shortcut_len: usize,
+) -> bool {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_register_palette_command";
+    let Some(id) = ffi_str(id_ptr, id_len, CALLER, "id") else {
+        return false;
+    };
+    let Some(title) = ffi_str(title_ptr, title_len, CALLER, "title") else {
+        return false;
+    };
+    let Ok(keywords) = ffi_opt_str(keywords_ptr, keywords_len, CALLER, "keywords") else {
+        return false;
+    };
+    let Ok(shortcut) = ffi_opt_str(shortcut_ptr, shortcut_len, CALLER, "shortcut") else {
+        return false;
+    };
+
+    let keywords: Vec<String> = keywords
+        .unwrap_or_default()
+        .split(|ch: char| ch == ',' || ch.is_whitespace())
+        .filter(|keyword| !keyword.is_empty())
+        .map(str::to_string)
+        .collect();
+    palette::register_command(id, title, &keywords, shortcut.filter(|hint| !hint.is_empty()));
+    true
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_unregister_palette_command(id_ptr: *const u8, id_len: usize) -> bool {
+    init_tracing();
+
+    match ffi_str(id_ptr, id_len, "frontier_blitz_unregister_palette_command", "id") {
+        Some(id) => palette::unregister_command(id),
+        None => false,
+    }
+}
+
+/// Search the focused window's page and show the find bar. `flags` is a bit
+/// set (1 = case sensitive, 2 = regex). Returns the match count, or -1 if
+/// the search couldn't run. Must not be called from the event loop thread.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_find(query_ptr: *const u8, query_len: usize, flags: u32) -> i32 {
+    init_tracing();
+
+    let Some(query) = ffi_str(query_ptr, query_len, "frontier_blitz_find", "query") else {
+        return -1;
+    };
+    let query = query.to_string();
+
+    commands::call("frontier_blitz_find", move |app| {
+        let window_id = app.primary_window()?;
+        app.find_in_page(window_id, query, flags)
+    })
+    .flatten()
+    .map(|count| count as i32)
+    .unwrap_or(-1)
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_set_profile_dir(path_ptr: *const u8, path_len: usize) -> bool {
+    init_tracing();
+
+    let Some(path) = ffi_str(path_ptr, path_len, "frontier_blitz_set_profile_dir", "path") else {
+        return false;
+    };
+
+    tracing::info!("Profile directory set to: {}", path);
+    config::get().profile_dir = PathBuf::from(path);
+    true
+}
+
+/// Set the focused window's zoom (1.0 = 100%) and remember it for the page's
+/// origin. Called from the event loop thread (inside a host event callback),
+/// the zoom changes once the callback returns and true is returned.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_set_zoom(level: f32) -> bool {
+    init_tracing();
+
+    if !level.is_finite() || level <= 0.0 {
+        tracing::error!("frontier_blitz_set_zoom received invalid level {level}");
+        return false;
+    }
+
+    commands::call_or_post("frontier_blitz_set_zoom", true, move |app| {
+        app.primary_window()
+            .is_some_and(|window_id| app.set_zoom(window_id, level))
+    })
+    .unwrap_or(false)
+}
+
+/// The focused window's zoom, or 0.0 if there is no running window.
+/// Must not be called from the event loop thread.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_get_zoom() -> f32 {
+    init_tracing();
+
+    commands::call("frontier_blitz_get_zoom", |app| {
+        app.primary_window()
+            .and_then(|window_id| app.zoom_level(window_id))
+    })
+    .flatten()
+    .unwrap_or(0.0)
+}
+
+/// Bytes owned by Rust and handed to the host. Release with `frontier_blitz_free_buffer`.
+#[repr(C)]
+pub struct BufferResult {
+    ptr: *mut u8,
+    len: usize,
+}
+
+impl BufferResult {
+    fn empty() -> Self {
+        Self {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        let ptr = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+        Self { ptr, len }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_free_buffer(ptr: *mut u8, len: usize) {
+    if ptr.is_null() {
+        return;
+    }
+    unsafe {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)));
+    }
+}
+
+/// Render `html` offscreen (no window or GPU needed) and return it as a PNG.
+/// `width` and `height` are the viewport in CSS pixels; the image is `scale`
+/// times that. Returns a null buffer on failure.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_render_to_png(
+    html_ptr: *const u8,
+    html_len: usize,
+    url_ptr: *const u8,
+    url_len: usize,
+    width: u32,
+    height: u32,
+    scale: f64,
+) -> BufferResult {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_render_to_png";
+    let Some(html) = ffi_str(html_ptr, html_len, CALLER, "HTML") else {
+        return BufferResult::empty();
+    };
+    let Some(url) = ffi_str(url_ptr, url_len, CALLER, "URL") else {
+        return BufferResult::empty();
+    };
+
+    let result = panic::catch_unwind(|| headless::render_to_png(html, url, width, height, scale));
+
+    match result {
+        Ok(Ok(png)) => {
+            tracing::info!("Rendered {} headlessly at {}x{} @{}x ({} bytes)", url, width, height, scale, png.len());
+            BufferResult::from_vec(png)
+        }
+        Ok(Err(err)) => {
+            tracing::error!("{CALLER} failed: {err}");
+            BufferResult::empty()
+        }
+        Err(_) => {
+            tracing::error!("{CALLER} panicked");
+            BufferResult::empty()
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_cancel_download(id: u64) -> bool {
+    init_tracing();
+    download::manager().cancel(id)
+}
+
+/// Run a DOM edit for the `frontier_blitz_*` edit functions. Returns the
+/// number of matched elements, or -1 if the selector is invalid, the window
+/// doesn't exist or no event loop is running. Called from the event loop
+/// thread (inside a host event callback), the edit is applied once the
+/// callback returns and 0 is returned.
+fn edit_dom(caller: &'static str, window_id: u64, selector_ptr: *const u8, selector_len: usize, edit: Option<dom_edit::Edit>) -> i32 {
+    let Some(selector) = ffi_str(selector_ptr, selector_len, caller, "selector") else {
+        return -1;
+    };
+    let Some(edit) = edit else {
+        return -1;
+    };
+    let selector = selector.to_string();
+
+    // Errors are logged in the closure since queued edits have no caller to return them to
+    let result = commands::call_or_post(caller, Some(0), move |app| {
+        match app.with_document(window_id, |doc| dom_edit::apply(doc, &selector, &edit))? {
+            Ok(count) => Some(count),
+            Err(err) => {
+                tracing::error!("{caller}: {err}");
+                None
+            }
+        }
+    });
+    result.flatten().map_or(-1, |count| count as i32)
+}
+
+/// Text content of the first element matching the selector, or an empty
+/// buffer (null pointer) if nothing matches. Free with `frontier_blitz_free_buffer`.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_query_text(window_id: u64, selector_ptr: *const u8, selector_len: usize) -> BufferResult {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_query_text";
+    let Some(selector) = ffi_str(selector_ptr, selector_len, CALLER, "selector") else {
+        return BufferResult::empty();
+    };
+    let selector = selector.to_string();
+
+    let result = commands::call(CALLER, move |app| {
+        app.with_document(window_id, |doc| dom_edit::query_text(doc, &selector))
+    });
+    match result.flatten() {
+        Some(Ok(Some(text))) => BufferResult::from_vec(text.into_bytes()),
+        Some(Err(err)) => {
+            tracing::error!("{CALLER}: {err}");
+            BufferResult::empty()
+        }
+        _ => BufferResult::empty(),
+    }
+}
 
-    let result = panic::catch_unwind(move || run_event_loop(&html_owned, "http://localhost/", state));
+#[no_mangle]
+pub extern "C" fn frontier_blitz_set_text(
+    window_id: u64,
+    selector_ptr: *const u8,
+    selector_len: usize,
+    text_ptr: *const u8,
+    text_len: usize,
+) -> i32 {
+    init_tracing();
 
-    match result {
-        Ok(Ok(())) => true,
-        Ok(Err(err)) => {
-            tracing::error!("frontier_blitz_run_static_html failed: {err}");
-            false
+    const CALLER: &str = "frontier_blitz_set_text";
+    let edit = ffi_str(text_ptr, text_len, CALLER, "text").map(|text| dom_edit::Edit::SetText(text.to_string()));
+    edit_dom(CALLER, window_id, selector_ptr, selector_len, edit)
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_set_attribute(
+    window_id: u64,
+    selector_ptr: *const u8,
+    selector_len: usize,
+    name_ptr: *const u8,
+    name_len: usize,
+    value_ptr: *const u8,
+    value_len: usize,
+) -> i32 {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_set_attribute";
+    let name = ffi_str(name_ptr, name_len, CALLER, "name");
+    let value = ffi_str(value_ptr, value_len, CALLER, "value");
+    let edit = name.zip(value).map(|(name, value)| dom_edit::Edit::SetAttribute {
+        name: name.to_string(),
+        value: value.to_string(),
+    });
+    edit_dom(CALLER, window_id, selector_ptr, selector_len, edit)
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_remove_attribute(
+    window_id: u64,
+    selector_ptr: *const u8,
+    selector_len: usize,
+    name_ptr: *const u8,
+    name_len: usize,
+) -> i32 {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_remove_attribute";
+    let edit = ffi_str(name_ptr, name_len, CALLER, "name").map(|name| dom_edit::Edit::RemoveAttribute(name.to_string()));
+    edit_dom(CALLER, window_id, selector_ptr, selector_len, edit)
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_set_inner_html(
+    window_id: u64,
+    selector_ptr: *const u8,
+    selector_len: usize,
+    html_ptr: *const u8,
+    html_len: usize,
+) -> i32 {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_set_inner_html";
+    let edit = ffi_str(html_ptr, html_len, CALLER, "HTML").map(|html| dom_edit::Edit::SetInnerHtml(html.to_string()));
+    edit_dom(CALLER, window_id, selector_ptr, selector_len, edit)
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_add_class(
+    window_id: u64,
+    selector_ptr: *const u8,
+    selector_len: usize,
+    class_ptr: *const u8,
+    class_len: usize,
+) -> i32 {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_add_class";
+    let edit = ffi_str(class_ptr, class_len, CALLER, "class").map(|class| dom_edit::Edit::AddClass(class.to_string()));
+    edit_dom(CALLER, window_id, selector_ptr, selector_len, edit)
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_remove_class(
+    window_id: u64,
+    selector_ptr: *const u8,
+    selector_len: usize,
+    class_ptr: *const u8,
+    class_len: usize,
+) -> i32 {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_remove_class";
+    let edit = ffi_str(class_ptr, class_len, CALLER, "class").map(|class| dom_edit::Edit::RemoveClass(class.to_string()));
+    edit_dom(CALLER, window_id, selector_ptr, selector_len, edit)
+}
+
+/// Patch the window's document to match `html` instead of replacing it, so
+/// focus, scroll position and form state survive. Returns the number of
+/// changes made, or -1 if the window doesn't exist or no event loop is running.
+/// Nodes are never moved: give reordered elements an `id` or `data-key`, or
+/// they and their state are rebuilt. Unkeyed siblings are only lined up
+/// across a few removed nodes.
+/// Called from the event loop thread (inside a host event callback), the patch
+/// is applied once the callback returns and 0 is returned.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_patch_document(window_id: u64, html_ptr: *const u8, html_len: usize) -> i32 {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_patch_document";
+    let Some(html) = ffi_str(html_ptr, html_len, CALLER, "HTML") else {
+        return -1;
+    };
+    let html = html.to_string();
+
+    let result = commands::call_or_post(CALLER, Some(0), move |app| {
+        let window_id = app.window_for_host_id(window_id)?;
+        let stats = app.patch_document(window_id, &html)?;
+        tracing::debug!("{CALLER}: {stats:?}");
+        Some(stats.total())
+    });
+    result.flatten().map_or(-1, |total| total as i32)
+}
+
+/// Current values of the form controls in the window's document, or in the
+/// first element matching the selector (null for the whole document), as a
+/// JSON object keyed by control name or id. Returns an empty buffer if the
+/// selector is invalid or matches nothing. Free with `frontier_blitz_free_buffer`.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_get_form_values(window_id: u64, selector_ptr: *const u8, selector_len: usize) -> BufferResult {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_get_form_values";
+    let Ok(selector) = ffi_opt_str(selector_ptr, selector_len, CALLER, "selector") else {
+        return BufferResult::empty();
+    };
+    let selector = selector.map(str::to_string);
+
+    let result = commands::call(CALLER, move |app| {
+        app.with_document(window_id, |doc| forms::read(doc, selector.as_deref()))
+    });
+    match result.flatten() {
+        Some(Ok(Some(values))) => match serde_json::to_vec(&values) {
+            Ok(json) => BufferResult::from_vec(json),
+            Err(err) => {
+                tracing::error!("{CALLER}: failed to serialize values: {err}");
+                BufferResult::empty()
+            }
+        },
+        Some(Err(err)) => {
+            tracing::error!("{CALLER}: {err}");
+            BufferResult::empty()
         }
-        Err(panic_payload) => {
-            if let Some(msg) = panic_payload.downcast_ref::<&str>() {
-                tracing::error!("frontier_blitz_run_static_html panicked: {msg}");
-            } else if let Some(msg) = panic_payload.downcast_ref::<String>() {
-                tracing::error!("frontier_blitz_run_static_html panicked: {msg}");
-            } else {
-                tracing::error!("frontier_blitz_run_static_html panicked");
+        _ => BufferResult::empty(),
+    }
+}
+
+/// Pre-fill form controls from a JSON object shaped like
+/// `frontier_blitz_get_form_values` output. Returns how many controls were
+/// set, or -1 on invalid JSON, an invalid or unmatched selector, or a missing window.
+/// Called from the event loop thread (inside a host event callback), the
+/// values are set once the callback returns and 0 is returned.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_set_form_values(
+    window_id: u64,
+    selector_ptr: *const u8,
+    selector_len: usize,
+    json_ptr: *const u8,
+    json_len: usize,
+) -> i32 {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_set_form_values";
+    let Ok(selector) = ffi_opt_str(selector_ptr, selector_len, CALLER, "selector") else {
+        return -1;
+    };
+    let Some(json) = ffi_str(json_ptr, json_len, CALLER, "JSON") else {
+        return -1;
+    };
+    let values: serde_json::Map<String, serde_json::Value> = match serde_json::from_str(json) {
+        Ok(values) => values,
+        Err(err) => {
+            tracing::error!("{CALLER}: expected a JSON object: {err}");
+            return -1;
+        }
+    };
+    let selector = selector.map(str::to_string);
+
+    let result = commands::call_or_post(CALLER, Some(0), move |app| {
+        match app.with_document(window_id, |doc| forms::write(doc, selector.as_deref(), &values))? {
+            Ok(count) => count,
+            Err(err) => {
+                tracing::error!("{CALLER}: {err}");
+                None
             }
-            false
         }
+    });
+    result.flatten().map_or(-1, |count| count as i32)
+}
+
+/// HTML of the window's live document, or the outer HTML of the first element
+/// matching the selector (null for the whole document). Includes host edits
+/// and current form values. Returns an empty buffer if the selector is invalid
+/// or matches nothing. Free with `frontier_blitz_free_buffer`.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_serialize_dom(window_id: u64, selector_ptr: *const u8, selector_len: usize) -> BufferResult {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_serialize_dom";
+    let Ok(selector) = ffi_opt_str(selector_ptr, selector_len, CALLER, "selector") else {
+        return BufferResult::empty();
+    };
+    let selector = selector.map(str::to_string);
+
+    let result = commands::call(CALLER, move |app| {
+        app.with_document(window_id, |doc| match &selector {
+            Some(selector) => serialize::outer_html(doc, selector),
+            None => Ok(Some(serialize::document(doc))),
+        })
+    });
+    match result.flatten() {
+        Some(Ok(Some(html))) => BufferResult::from_vec(html.into_bytes()),
+        Some(Err(err)) => {
+            tracing::error!("{CALLER}: {err}");
+            BufferResult::empty()
+        }
+        _ => BufferResult::empty(),
     }
 }
 
+/// Render `template` into the elements matching `selector` whenever the host
+/// posts a message called `name`. `{{key}}` placeholders are replaced with
+/// HTML-escaped payload values. `mode` is 0 to replace the element's
+/// contents, 1 to append and 2 to prepend.
 #[no_mangle]
-pub extern "C" fn frontier_blitz_navigate(
-    html_ptr: *const u8,
-    html_len: usize,
-    url_ptr: *const u8,
-    url_len: usize,
+pub extern "C" fn frontier_blitz_register_message_template(
+    name_ptr: *const u8,
+    name_len: usize,
+    selector_ptr: *const u8,
+    selector_len: usize,
+    template_ptr: *const u8,
+    template_len: usize,
+    mode: u32,
 ) -> bool {
     init_tracing();
 
-    if html_ptr.is_null() || url_ptr.is_null() {
-        tracing::error!("frontier_blitz_navigate received null pointer");
+    const CALLER: &str = "frontier_blitz_register_message_template";
+    let Some(name) = ffi_str(name_ptr, name_len, CALLER, "name") else {
+        return false;
+    };
+    let Some(selector) = ffi_str(selector_ptr, selector_len, CALLER, "selector") else {
+        return false;
+    };
+    let Some(template) = ffi_str(template_ptr, template_len, CALLER, "template") else {
+        return false;
+    };
+    let Some(mode) = messages::Mode::from_ffi(mode) else {
+        tracing::error!("{CALLER}: unknown mode {mode}");
         return false;
+    };
+
+    messages::register_template(name, selector, template, mode);
+    true
+}
+
+#[no_mangle]
+pub extern "C" fn frontier_blitz_unregister_message_templates(name_ptr: *const u8, name_len: usize) -> bool {
+    init_tracing();
+
+    match ffi_str(name_ptr, name_len, "frontier_blitz_unregister_message_templates", "name") {
+        Some(name) => messages::unregister_templates(name),
+        None => false,
     }
+}
 
-    let html_slice = unsafe { std::slice::from_raw_parts(html_ptr, html_len) };
-    let html_owned = match std::str::from_utf8(html_slice) {
-        Ok(content) => content.to_owned(),
+/// Send a message to the window's page: the templates registered for `name`
+/// are rendered with the JSON `payload` (null for none). Returns how many
+/// elements were updated, or -1 on invalid JSON, a template with an invalid
+/// selector, or a missing window. Called from the event loop thread (inside a
+/// host event callback), the message is delivered once the callback returns
+/// and 0 is returned.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_post_message(
+    window_id: u64,
+    name_ptr: *const u8,
+    name_len: usize,
+    payload_ptr: *const u8,
+    payload_len: usize,
+) -> i32 {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_post_message";
+    let Some(name) = ffi_str(name_ptr, name_len, CALLER, "name") else {
+        return -1;
+    };
+    let payload = match ffi_opt_str(payload_ptr, payload_len, CALLER, "payload") {
+        Ok(Some(json)) => match serde_json::from_str(json) {
+            Ok(payload) => payload,
+            Err(err) => {
+                tracing::error!("{CALLER}: invalid payload JSON: {err}");
+                return -1;
+            }
+        },
+        Ok(None) => serde_json::Value::Null,
+        Err(()) => return -1,
+    };
+    let name = name.to_string();
+
+    let result = commands::call_or_post(CALLER, Some(0), move |app| {
+        match app.with_document(window_id, |doc| messages::deliver(doc, &name, &payload))? {
+            Ok(updated) => Some(updated),
+            Err(err) => {
+                tracing::error!("{CALLER}: {err}");
+                None
+            }
+        }
+    });
+    result.flatten().map_or(-1, |updated| updated as i32)
+}
+
+/// Turn session snapshots (and restoring them on launch) on or off. On by default.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_set_session_restore_enabled(enabled: bool) {
+    init_tracing();
+    config::get().restore_session = enabled;
+}
+
+/// Write the open windows, tabs, history, scroll offsets and zoom to
+/// `session.json` in the profile directory now. Returns false if the write
+/// failed or no event loop is running.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_save_session() -> bool {
+    init_tracing();
+    commands::call("frontier_blitz_save_session", |app| app.save_session()).unwrap_or(false)
+}
+
+/// Reopen the windows from the saved session alongside the current ones.
+/// Returns how many windows will be opened, or -1 if there is no readable
+/// session or no event loop is running.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_restore_session() -> i32 {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_restore_session";
+    let Some(session) = session::load() else {
+        tracing::error!("{CALLER}: no saved session");
+        return -1;
+    };
+    let count = session.windows.iter().filter(|window| window.is_restorable()).count() as i32;
+    let queued = commands::call(CALLER, move |app| {
+        app.pending_restore = Some(session);
+    });
+    match queued {
+        Some(()) => count,
+        None => -1,
+    }
+}
+
+/// Search browsing history. `mode` 0 matches every word of the query against
+/// titles and URLs, 1 matches URLs starting with the query. Returns a JSON
+/// array of entries, most recent first, or an empty buffer on bad arguments.
+/// Free with `frontier_blitz_free_buffer`.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_history_search(query_ptr: *const u8, query_len: usize, mode: u8, limit: u32) -> BufferResult {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_history_search";
+    let Some(query) = ffi_str(query_ptr, query_len, CALLER, "query") else {
+        return BufferResult::empty();
+    };
+    let Some(mode) = history::SearchMode::from_u8(mode) else {
+        tracing::error!("{CALLER} received unknown mode {mode}");
+        return BufferResult::empty();
+    };
+
+    let entries = history::search(query, mode, limit as usize);
+    match serde_json::to_vec(&entries) {
+        Ok(json) => BufferResult::from_vec(json),
         Err(err) => {
-            tracing::error!("frontier_blitz_navigate received invalid HTML UTF-8: {err}");
-            return false;
+            tracing::error!("{CALLER}: failed to serialize entries: {err}");
+            BufferResult::empty()
+        }
+    }
+}
+
+/// Delete history visits made between `from_ms` (inclusive) and `to_ms`
+/// (exclusive), in milliseconds since the Unix epoch. Returns how many
+/// visits were deleted.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_history_delete_range(from_ms: i64, to_ms: i64) -> u64 {
+    init_tracing();
+    history::delete_range(from_ms, to_ms) as u64
+}
+
+fn bookmarks_json<T: Serialize>(caller: &str, value: &T) -> BufferResult {
+    match serde_json::to_vec(value) {
+        Ok(json) => BufferResult::from_vec(json),
+        Err(err) => {
+            tracing::error!("{caller}: failed to serialize bookmarks: {err}");
+            BufferResult::empty()
         }
+    }
+}
+
+/// Bookmark `url` in folder `parent_id` (0 for the top level). `tags` is an
+/// optional comma-separated list. Returns the new bookmark's id, or -1 if
+/// the folder doesn't exist or the arguments are invalid.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_bookmark_add(
+    parent_id: u64,
+    title_ptr: *const u8,
+    title_len: usize,
+    url_ptr: *const u8,
+    url_len: usize,
+    tags_ptr: *const u8,
+    tags_len: usize,
+) -> i64 {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_bookmark_add";
+    let Some(title) = ffi_str(title_ptr, title_len, CALLER, "title") else {
+        return -1;
+    };
+    let Some(url) = ffi_str(url_ptr, url_len, CALLER, "url") else {
+        return -1;
+    };
+    let Ok(tags) = ffi_opt_str(tags_ptr, tags_len, CALLER, "tags") else {
+        return -1;
     };
 
-    let url_slice = unsafe { std::slice::from_raw_parts(url_ptr, url_len) };
-    let url_owned = match std::str::from_utf8(url_slice) {
-        Ok(content) => content.to_owned(),
+    match bookmarks::add(parent_id, title, url, &bookmarks::parse_tags(tags.unwrap_or_default())) {
+        Ok(id) => id as i64,
         Err(err) => {
-            tracing::error!("frontier_blitz_navigate received invalid URL UTF-8: {err}");
-            return false;
+            tracing::error!("{CALLER}: {err}");
+            -1
         }
+    }
+}
+
+/// Create a bookmark folder in `parent_id` (0 for the top level). Returns the
+/// folder's id, or -1 on failure.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_bookmark_add_folder(parent_id: u64, title_ptr: *const u8, title_len: usize) -> i64 {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_bookmark_add_folder";
+    let Some(title) = ffi_str(title_ptr, title_len, CALLER, "title") else {
+        return -1;
     };
 
-    tracing::info!("Navigating to: {}", url_owned);
+    match bookmarks::add_folder(parent_id, title) {
+        Ok(id) => id as i64,
+        Err(err) => {
+            tracing::error!("{CALLER}: {err}");
+            -1
+        }
+    }
+}
 
-    let state = Arc::new(Mutex::new(NavigationState::new(
-        html_owned.clone(),
-        url_owned.clone(),
-    )));
+/// Remove a bookmark, or a folder with everything in it. Returns how many
+/// items were removed, or -1 if `id` doesn't exist.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_bookmark_remove(id: u64) -> i32 {
+    init_tracing();
 
-    let result = panic::catch_unwind(move || run_event_loop(&html_owned, &url_owned, state));
+    match bookmarks::remove(id) {
+        Ok(removed) => removed as i32,
+        Err(err) => {
+            tracing::error!("frontier_blitz_bookmark_remove: {err}");
+            -1
+        }
+    }
+}
 
-    match result {
-        Ok(Ok(())) => true,
-        Ok(Err(err)) => {
-            tracing::error!("frontier_blitz_navigate failed: {err}");
+/// Move a bookmark or folder into folder `parent_id` (0 for the top level)
+/// at position `index` among its children. A negative index moves it to the
+/// end. Returns false if either id doesn't exist or a folder would end up
+/// inside itself.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_bookmark_move(id: u64, parent_id: u64, index: i64) -> bool {
+    init_tracing();
+
+    let index = usize::try_from(index).ok();
+    match bookmarks::move_to(id, parent_id, index) {
+        Ok(()) => true,
+        Err(err) => {
+            tracing::error!("frontier_blitz_bookmark_move: {err}");
             false
         }
-        Err(panic_payload) => {
-            if let Some(msg) = panic_payload.downcast_ref::<&str>() {
-                tracing::error!("frontier_blitz_navigate panicked: {msg}");
-            } else if let Some(msg) = panic_payload.downcast_ref::<String>() {
-                tracing::error!("frontier_blitz_navigate panicked: {msg}");
-            } else {
-                tracing::error!("frontier_blitz_navigate panicked");
-            }
+    }
+}
+
+/// Replace a bookmark's tags with the comma-separated list `tags`; an empty
+/// list clears them. Returns false if `id` doesn't exist or `tags` isn't
+/// valid UTF-8.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_bookmark_set_tags(id: u64, tags_ptr: *const u8, tags_len: usize) -> bool {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_bookmark_set_tags";
+    let Ok(tags) = ffi_opt_str(tags_ptr, tags_len, CALLER, "tags") else {
+        return false;
+    };
+    match bookmarks::set_tags(id, &bookmarks::parse_tags(tags.unwrap_or_default())) {
+        Ok(()) => true,
+        Err(err) => {
+            tracing::error!("{CALLER}: {err}");
             false
         }
     }
 }
 
-// No global state needed - everything happens in window_event()
+/// List bookmarks as a JSON array. With `folder_id` 0 or more, the direct
+/// children of that folder in order; with -1, every bookmark and folder,
+/// each folder followed by its contents. Returns an empty buffer if the
+/// folder doesn't exist. Free with `frontier_blitz_free_buffer`.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_bookmarks_list(folder_id: i64) -> BufferResult {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_bookmarks_list";
+    let Ok(folder_id) = u64::try_from(folder_id) else {
+        return bookmarks_json(CALLER, &bookmarks::all());
+    };
+    match bookmarks::children(folder_id) {
+        Ok(children) => bookmarks_json(CALLER, &children),
+        Err(err) => {
+            tracing::error!("{CALLER}: {err}");
+            BufferResult::empty()
+        }
+    }
+}
+
+/// Search bookmarks by title, URL and tag; `#tag` words only match tags.
+/// Returns a JSON array of at most `limit` bookmarks. Free with
+/// `frontier_blitz_free_buffer`.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_bookmarks_search(query_ptr: *const u8, query_len: usize, limit: u32) -> BufferResult {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_bookmarks_search";
+    let Some(query) = ffi_str(query_ptr, query_len, CALLER, "query") else {
+        return BufferResult::empty();
+    };
+    bookmarks_json(CALLER, &bookmarks::search(query, limit as usize))
+}
+
+/// Import a Netscape bookmark file into folder `parent_id` (0 for the top
+/// level). Returns how many bookmarks were added, or -1 on failure.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_bookmarks_import(parent_id: u64, html_ptr: *const u8, html_len: usize) -> i32 {
+    init_tracing();
+
+    const CALLER: &str = "frontier_blitz_bookmarks_import";
+    let Some(html) = ffi_str(html_ptr, html_len, CALLER, "html") else {
+        return -1;
+    };
+    match bookmarks::import_html(html, parent_id) {
+        Ok(imported) => imported as i32,
+        Err(err) => {
+            tracing::error!("{CALLER}: {err}");
+            -1
+        }
+    }
+}
+
+/// Every bookmark as a Netscape bookmark file. Free with
+/// `frontier_blitz_free_buffer`.
+#[no_mangle]
+pub extern "C" fn frontier_blitz_bookmarks_export() -> BufferResult {
+    init_tracing();
+    BufferResult::from_vec(bookmarks::export_html().into_bytes())
+}
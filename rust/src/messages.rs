@@ -0,0 +1,258 @@
+// A message channel between pages and the host.
+//
+// Pages send messages by following a `frontier-message:name?key=value` link,
+// submitting a form whose action is `frontier-message:name` (its fields
+// become the payload), or clicking an element with
+// `data-frontier-message="name"` and an optional JSON
+// `data-frontier-payload`. Each is reported as a `frontier-message` event
+// with the sending window and the page's origin.
+//
+// The host sends messages with `frontier_blitz_post_message`. Templates
+// registered for the message's name render the payload into the elements
+// matching their selector, with `{{key}}` (or `{{key.nested}}`) replaced by
+// the HTML-escaped payload value.
+
+use std::sync::{Mutex, OnceLock};
+
+use blitz_dom::BaseDocument;
+use blitz_html::HtmlDocument;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::dom_edit::{self, InvalidSelector};
+use crate::{dom, host, overlay, patch};
+
+pub const SCHEME: &str = "frontier-message:";
+
+pub const MESSAGE_ATTR: &str = "data-frontier-message";
+pub const PAYLOAD_ATTR: &str = "data-frontier-payload";
+
+const EVENT_NAME: &str = "frontier-message";
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Message {
+    pub name: String,
+    pub payload: Value,
+}
+
+#[derive(Serialize)]
+struct MessageEvent<'a> {
+    window_id: u64,
+    origin: String,
+    name: &'a str,
+    payload: &'a Value,
+}
+
+pub fn is_message_url(url: &str) -> bool {
+    url.starts_with(SCHEME)
+}
+
+/// The message a `frontier-message:` URL sends. Query parameters and `fields`
+/// (from a submitted form) make up the payload; later values for the same
+/// key win.
+pub fn from_url(url: &str, fields: &[(String, String)]) -> Option<Message> {
+    let rest = url.strip_prefix(SCHEME)?;
+    let (name, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let name = name.trim_start_matches('/');
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut payload = Map::new();
+    let query = url::form_urlencoded::parse(query.as_bytes()).into_owned();
+    for (key, value) in query.chain(fields.iter().cloned()) {
+        payload.insert(key, Value::String(value));
+    }
+    Some(Message {
+        name: name.to_string(),
+        payload: Value::Object(payload),
+    })
+}
+
+/// The message sent by clicking `node_id`, if it or an ancestor has
+/// `data-frontier-message`.
+pub fn from_click(doc: &BaseDocument, node_id: usize) -> Option<Message> {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        if dom::attr(doc, id, overlay::OVERLAY_ATTR).is_some() {
+            return None;
+        }
+        if let Some(name) = dom::attr(doc, id, MESSAGE_ATTR) {
+            let payload = match dom::attr(doc, id, PAYLOAD_ATTR) {
+                Some(json) => serde_json::from_str(json).unwrap_or_else(|err| {
+                    tracing::warn!("Ignoring invalid {PAYLOAD_ATTR} on message {name:?}: {err}");
+                    Value::Null
+                }),
+                None => Value::Null,
+            };
+            return Some(Message {
+                name: name.to_string(),
+                payload,
+            });
+        }
+        current = doc.get_node(id)?.parent;
+    }
+    None
+}
+
+/// Report `message`, sent by the page at `page_url` in `window_id`, to the host.
+pub fn emit(window_id: u64, page_url: &str, message: &Message) {
+    let origin = url::Url::parse(page_url)
+        .map(|url| url.origin().ascii_serialization())
+        .unwrap_or_else(|_| "null".to_string());
+    tracing::info!("Message {:?} from {}", message.name, origin);
+    host::emit(
+        EVENT_NAME,
+        &MessageEvent {
+            window_id,
+            origin,
+            name: &message.name,
+            payload: &message.payload,
+        },
+    );
+}
+
+/// How a rendered template is placed into its target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Replace,
+    Append,
+    Prepend,
+}
+
+impl Mode {
+    pub fn from_ffi(mode: u32) -> Option<Self> {
+        match mode {
+            0 => Some(Mode::Replace),
+            1 => Some(Mode::Append),
+            2 => Some(Mode::Prepend),
+            _ => None,
+        }
+    }
+}
+
+struct Template {
+    name: String,
+    selector: String,
+    html: String,
+    mode: Mode,
+}
+
+fn templates() -> &'static Mutex<Vec<Template>> {
+    static TEMPLATES: OnceLock<Mutex<Vec<Template>>> = OnceLock::new();
+    TEMPLATES.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Render `html` into the elements matching `selector` whenever the host
+/// posts `name`. Replaces any template for the same name and selector.
+pub fn register_template(name: &str, selector: &str, html: &str, mode: Mode) {
+    let mut templates = templates().lock().unwrap();
+    templates.retain(|template| !(template.name == name && template.selector == selector));
+    templates.push(Template {
+        name: name.to_string(),
+        selector: selector.to_string(),
+        html: html.to_string(),
+        mode,
+    });
+}
+
+/// Drop every template for `name`. Returns false if there were none.
+pub fn unregister_templates(name: &str) -> bool {
+    let mut templates = templates().lock().unwrap();
+    let before = templates.len();
+    templates.retain(|template| template.name != name);
+    templates.len() != before
+}
+
+/// Apply the templates registered for a host message to `doc`. Returns how
+/// many elements were updated.
+pub fn deliver(doc: &mut BaseDocument, name: &str, payload: &Value) -> Result<usize, InvalidSelector> {
+    let matching: Vec<(String, String, Mode)> = templates()
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|template| template.name == name)
+        .map(|template| (template.selector.clone(), render(&template.html, payload), template.mode))
+        .collect();
+
+    let mut updated = 0;
+    for (selector, html, mode) in matching {
+        for target in dom_edit::select(doc, &selector)? {
+            insert_fragment(doc, target, &html, mode);
+            updated += 1;
+        }
+    }
+    Ok(updated)
+}
+
+/// Substitute `{{path}}` placeholders with values from `payload`.
+pub fn render(template: &str, payload: &Value) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let path = rest[start + 2..start + end].trim();
+        escape(&lookup(payload, path), &mut out);
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn lookup(payload: &Value, path: &str) -> String {
+    let value = path
+        .split('.')
+        .try_fold(payload, |value, key| value.get(key));
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn escape(text: &str, out: &mut String) {
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+}
+
+fn insert_fragment(doc: &mut BaseDocument, target: usize, html: &str, mode: Mode) {
+    let fragment = HtmlDocument::from_html(html, Default::default());
+    let fragment: &BaseDocument = &fragment;
+    let Some(body) = fragment.query_selector("body").ok().flatten() else {
+        return;
+    };
+    let nodes = fragment
+        .get_node(body)
+        .map(|node| node.children.clone())
+        .unwrap_or_default();
+    let existing = doc
+        .get_node(target)
+        .map(|node| node.children.clone())
+        .unwrap_or_default();
+
+    let mut mutator = doc.mutate();
+    if mode == Mode::Replace {
+        for child in &existing {
+            mutator.remove_node(*child);
+        }
+    }
+    let created: Vec<usize> = nodes
+        .into_iter()
+        .filter_map(|node| patch::clone_into(&mut mutator, fragment, node))
+        .collect();
+    match (mode, existing.first()) {
+        (Mode::Prepend, Some(first)) => mutator.insert_nodes_before(*first, &created),
+        _ => mutator.append_children(target, &created),
+    }
+}
@@ -0,0 +1,215 @@
+// Bridge-owned UI drawn on top of page content.
+//
+// Overlays are ordinary elements appended to the page's `<body>` and tagged
+// with `data-frontier-overlay` so bridge code can find them again and ignore
+// them when hit-testing page content.
+//
+// The overlays that take input (context menu, find bar, command palette) are
+// tracked per window in an `OverlayStack`, so Escape closes whichever opened
+// last, keys go to the highest one that takes them, and focus goes back to
+// where it was before.
+
+use blitz_dom::{Attribute, BaseDocument, DocumentMutator};
+use markup5ever::{ns, LocalName, QualName};
+
+use crate::dom;
+
+pub const OVERLAY_ATTR: &str = "data-frontier-overlay";
+
+pub const STATUS_BAR: &str = "status";
+
+const STATUS_BAR_STYLE: &str = "position: fixed; left: 0; bottom: 0; max-width: 60%; \
+    padding: 3px 8px; background: #f1f5f9; color: #334155; border: 1px solid #cbd5e1; \
+    border-left: none; border-bottom: none; border-top-right-radius: 4px; \
+    font: 12px -apple-system, sans-serif; white-space: nowrap; overflow: hidden; \
+    text-overflow: ellipsis; z-index: 2147483647;";
+
+pub const KEY_INDICATOR: &str = "keys";
+
+const KEY_INDICATOR_STYLE: &str = "position: fixed; right: 8px; bottom: 8px; \
+    padding: 3px 8px; background: #1e293b; color: #f8fafc; border-radius: 4px; \
+    font: 12px ui-monospace, monospace; white-space: nowrap; z-index: 2147483647;";
+
+/// An overlay that takes keyboard or mouse input while it's open.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layer {
+    ContextMenu,
+    Find,
+    Palette,
+}
+
+impl Layer {
+    /// Whether typing goes to it. The context menu is only clicked (or
+    /// closed with Escape).
+    pub fn takes_keys(self) -> bool {
+        match self {
+            Layer::ContextMenu => false,
+            Layer::Find | Layer::Palette => true,
+        }
+    }
+}
+
+/// The open input overlays, oldest first, each with the element that had
+/// focus when it opened.
+#[derive(Debug, Default)]
+pub struct OverlayStack {
+    layers: Vec<(Layer, Option<usize>)>,
+}
+
+impl OverlayStack {
+    /// Record that `layer` is showing. A layer that's already open keeps its
+    /// place and the focus it saved.
+    pub fn open(&mut self, doc: &BaseDocument, layer: Layer) {
+        if !self.contains(layer) {
+            self.layers.push((layer, doc.get_focussed_node_id()));
+        }
+    }
+
+    /// Forget `layer`. If it was on top, give focus back to the element that
+    /// had it when the layer opened, if that element is still in the
+    /// document; a layer closing underneath another leaves focus alone.
+    pub fn close(&mut self, doc: &mut BaseDocument, layer: Layer) {
+        let Some(index) = self.layers.iter().position(|(open, _)| *open == layer) else {
+            return;
+        };
+        let on_top = index + 1 == self.layers.len();
+        let (_, focus) = self.layers.remove(index);
+        if !on_top {
+            return;
+        }
+        if let Some(node_id) = focus {
+            if doc.get_node(node_id).is_some() && doc.get_focussed_node_id() != Some(node_id) {
+                doc.set_focus_to(node_id);
+            }
+        }
+    }
+
+    /// Forget every layer without touching focus, e.g. when the document
+    /// they were drawn in is replaced.
+    pub fn clear(&mut self) {
+        self.layers.clear();
+    }
+
+    /// The layer opened most recently, which Escape closes.
+    pub fn top(&self) -> Option<Layer> {
+        self.layers.last().map(|(layer, _)| *layer)
+    }
+
+    /// The most recent layer that keys are typed into, skipping ones above
+    /// it that only take clicks.
+    pub fn top_taking_keys(&self) -> Option<Layer> {
+        self.layers
+            .iter()
+            .rev()
+            .map(|(layer, _)| *layer)
+            .find(|layer| layer.takes_keys())
+    }
+
+    pub fn contains(&self, layer: Layer) -> bool {
+        self.layers.iter().any(|(open, _)| *open == layer)
+    }
+}
+
+pub fn html_name(local: &str) -> QualName {
+    QualName::new(None, ns!(html), LocalName::from(local))
+}
+
+pub fn attr_name(local: &str) -> QualName {
+    QualName::new(None, ns!(), LocalName::from(local))
+}
+
+fn attribute(name: &str, value: &str) -> Attribute {
+    Attribute {
+        name: attr_name(name),
+        value: value.to_string(),
+    }
+}
+
+/// The overlay element named `name`, if it's in the document.
+pub fn find(doc: &BaseDocument, name: &str) -> Option<usize> {
+    doc.query_selector(&format!("[{OVERLAY_ATTR}=\"{name}\"]"))
+        .ok()
+        .flatten()
+}
+
+/// Whether `node_id` is part of any bridge overlay.
+pub fn contains(doc: &BaseDocument, node_id: usize) -> bool {
+    let mut current = Some(node_id);
+    while let Some(id) = current {
+        if dom::attr(doc, id, OVERLAY_ATTR).is_some() {
+            return true;
+        }
+        current = doc.get_node(id).and_then(|node| node.parent);
+    }
+    false
+}
+
+pub fn remove(doc: &mut BaseDocument, name: &str) {
+    if let Some(node_id) = find(doc, name) {
+        doc.mutate().remove_node(node_id);
+    }
+}
+
+/// Replace the overlay named `name` with a fresh container and let `build`
+/// fill it in. Returns the container's node id.
+pub fn mount(
+    doc: &mut BaseDocument,
+    name: &str,
+    style: &str,
+    build: impl FnOnce(&mut DocumentMutator, usize),
+) -> Option<usize> {
+    remove(doc, name);
+
+    let body = doc.query_selector("body").ok().flatten()?;
+    let mut mutator = doc.mutate();
+    let container = mutator.create_element(
+        html_name("div"),
+        vec![attribute(OVERLAY_ATTR, name), attribute("style", style)],
+    );
+    build(&mut mutator, container);
+    mutator.append_children(body, &[container]);
+    Some(container)
+}
+
+/// Append a child element with optional text content.
+pub fn append_element(
+    mutator: &mut DocumentMutator,
+    parent: usize,
+    tag: &str,
+    attrs: &[(&str, &str)],
+    text: Option<&str>,
+) -> usize {
+    let attrs = attrs
+        .iter()
+        .map(|(name, value)| attribute(name, value))
+        .collect();
+    let element = mutator.create_element(html_name(tag), attrs);
+    if let Some(text) = text {
+        let text_node = mutator.create_text_node(text);
+        mutator.append_children(element, &[text_node]);
+    }
+    mutator.append_children(parent, &[element]);
+    element
+}
+
+/// Show `text` in a single-element overlay. `None` removes the overlay.
+fn set_text_overlay(doc: &mut BaseDocument, name: &str, style: &str, text: Option<&str>) {
+    let Some(text) = text else {
+        remove(doc, name);
+        return;
+    };
+
+    mount(doc, name, style, |mutator, container| {
+        let text_node = mutator.create_text_node(text);
+        mutator.append_children(container, &[text_node]);
+    });
+}
+
+pub fn set_status_text(doc: &mut BaseDocument, text: Option<&str>) {
+    set_text_overlay(doc, STATUS_BAR, STATUS_BAR_STYLE, text);
+}
+
+/// Show the pending key sequence and mode, see `sequence`.
+pub fn set_key_indicator(doc: &mut BaseDocument, text: Option<&str>) {
+    set_text_overlay(doc, KEY_INDICATOR, KEY_INDICATOR_STYLE, text);
+}
@@ -0,0 +1,415 @@
+// Command palette (Cmd/Ctrl+K) drawn as a bridge overlay.
+//
+// What's typed is fuzzy-matched against built-in commands, commands the host
+// registered, bookmarks and history, and the best matches are listed below
+// the query. Like the find bar, keys go to the palette instead of the page
+// while it's open, so the page keeps its focus and form state. The panel sits
+// on a backdrop covering the page, and a click on the backdrop closes it.
+
+use std::sync::{Mutex, OnceLock};
+
+use blitz_dom::BaseDocument;
+use serde::Serialize;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::input::Shortcut;
+use crate::{bookmarks, dom, history, host, keymap, overlay};
+
+pub const OVERLAY_NAME: &str = "command-palette";
+
+/// Page the "New Tab" command opens.
+pub const NEW_TAB_URL: &str = "about:blank";
+
+const ITEM_ATTR: &str = "data-frontier-palette-item";
+
+const MAX_RESULTS: usize = 12;
+// Candidates taken from history before fuzzy matching, most recent first
+const HISTORY_CANDIDATES: usize = 500;
+const BOOKMARK_BONUS: i64 = 6;
+
+const BACKDROP_STYLE: &str = "position: fixed; top: 0; right: 0; bottom: 0; left: 0; \
+    background: rgba(15, 23, 42, 0.2); z-index: 2147483647;";
+const PANEL_STYLE: &str = "width: 560px; margin: 72px auto 0; padding: 8px; background: white; \
+    border: 1px solid #cbd5e1; border-radius: 10px; box-shadow: 0 16px 48px rgba(15, 23, 42, 0.28); \
+    font: 14px -apple-system, sans-serif; color: #0f172a;";
+const QUERY_STYLE: &str = "padding: 8px 10px; margin-bottom: 6px; border: 1px solid #667eea; \
+    border-radius: 6px; white-space: pre;";
+const PLACEHOLDER_STYLE: &str = "padding: 8px 10px; margin-bottom: 6px; border: 1px solid #667eea; \
+    border-radius: 6px; color: #94a3b8;";
+const ITEM_STYLE: &str = "display: flex; justify-content: space-between; gap: 16px; padding: 6px 10px; \
+    border-radius: 6px; cursor: default;";
+const SELECTED_ITEM_STYLE: &str = "display: flex; justify-content: space-between; gap: 16px; \
+    padding: 6px 10px; border-radius: 6px; cursor: default; background: #e0e7ff;";
+const TITLE_STYLE: &str = "white-space: nowrap; overflow: hidden; text-overflow: ellipsis;";
+const DETAIL_STYLE: &str = "color: #64748b; font-size: 12px; white-space: nowrap; overflow: hidden; \
+    text-overflow: ellipsis; max-width: 50%;";
+const EMPTY_STYLE: &str = "padding: 6px 10px; color: #64748b;";
+
+/// What running a palette item does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Shortcut(Shortcut),
+    NewTab,
+    /// Navigate the current tab, as if the URL had been typed.
+    Open(String),
+    /// Reported to the host with a `palette-command` event.
+    Host(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaletteItem {
+    pub command: Command,
+    pub title: String,
+    /// The URL for pages, the shortcut hint for commands.
+    pub detail: String,
+}
+
+struct CommandEntry {
+    command: Command,
+    title: String,
+    keywords: Vec<String>,
+    shortcut: Option<String>,
+}
+
+/// The built-in commands, in the order they're listed for an empty query.
+fn builtins() -> Vec<CommandEntry> {
+    let entry = |command: Command, title: &str, keywords: &str| CommandEntry {
+        shortcut: match command {
+            Command::Shortcut(shortcut) => keymap::hint(shortcut),
+            _ => None,
+        },
+        command,
+        title: title.to_string(),
+        keywords: keywords.split_whitespace().map(str::to_string).collect(),
+    };
+    vec![
+        entry(Command::Shortcut(Shortcut::Back), "Back", "previous history"),
+        entry(Command::Shortcut(Shortcut::Forward), "Forward", "next history"),
+        entry(Command::Shortcut(Shortcut::Reload), "Reload", "refresh"),
+        entry(Command::Shortcut(Shortcut::Find), "Find in Page", "search text"),
+        entry(Command::NewTab, "New Tab", "open blank"),
+        entry(Command::Shortcut(Shortcut::NextTab), "Next Tab", "switch"),
+        entry(Command::Shortcut(Shortcut::PreviousTab), "Previous Tab", "switch"),
+        entry(Command::Shortcut(Shortcut::CloseTab), "Close Tab", ""),
+        entry(Command::Shortcut(Shortcut::ZoomIn), "Zoom In", "bigger larger"),
+        entry(Command::Shortcut(Shortcut::ZoomOut), "Zoom Out", "smaller"),
+        entry(Command::Shortcut(Shortcut::ZoomReset), "Reset Zoom", "actual size"),
+        entry(Command::Shortcut(Shortcut::Bookmark), "Bookmark This Page", "favorite star"),
+        entry(Command::Shortcut(Shortcut::ScrollTop), "Scroll to Top", "start beginning"),
+        entry(Command::Shortcut(Shortcut::ScrollBottom), "Scroll to Bottom", "end"),
+        entry(Command::Open(history::PAGE_URL.to_string()), "Show History", "visited"),
+        entry(Command::Open("about:downloads".to_string()), "Show Downloads", "files"),
+    ]
+}
+
+fn host_commands() -> &'static Mutex<Vec<CommandEntry>> {
+    static COMMANDS: OnceLock<Mutex<Vec<CommandEntry>>> = OnceLock::new();
+    COMMANDS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Add (or replace) a host command. `shortcut` is only shown as a hint; the
+/// host handles the key itself.
+pub fn register_command(id: &str, title: &str, keywords: &[String], shortcut: Option<&str>) {
+    let mut commands = host_commands().lock().unwrap();
+    let command = Command::Host(id.to_string());
+    commands.retain(|entry| entry.command != command);
+    commands.push(CommandEntry {
+        command,
+        title: title.to_string(),
+        keywords: keywords.to_vec(),
+        shortcut: shortcut.map(str::to_string),
+    });
+}
+
+pub fn unregister_command(id: &str) -> bool {
+    let mut commands = host_commands().lock().unwrap();
+    let before = commands.len();
+    commands.retain(|entry| entry.command != Command::Host(id.to_string()));
+    commands.len() != before
+}
+
+/// How well `query` matches `text`, or `None` if it doesn't. Every
+/// non-space character of `query` has to appear in `text` in order,
+/// ignoring case; matches at the start of words and runs of consecutive
+/// characters score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for ch in &query {
+        let found = (position..text.len()).find(|&index| text[index] == *ch)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 5;
+        } else if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 8;
+        } else {
+            // Small penalty for skipping ahead inside a word
+            score -= (found - position).min(3) as i64;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    if text.starts_with(&query) {
+        score += 10;
+    }
+    Some(score)
+}
+
+/// The URL to open for typed text that looks like one.
+fn typed_url(query: &str) -> Option<String> {
+    let query = query.trim();
+    if query.is_empty() || query.contains(char::is_whitespace) {
+        return None;
+    }
+    if query.contains("://") || query.starts_with("about:") || query.starts_with("file:") {
+        return Some(query.to_string());
+    }
+    if query == "localhost" || query.starts_with("localhost:") || query.starts_with("localhost/") {
+        return Some(format!("http://{query}"));
+    }
+    let host = query.split(['/', '?', '#']).next().unwrap_or_default();
+    if host.contains('.') && !host.starts_with('.') && !host.ends_with('.') {
+        return Some(format!("https://{query}"));
+    }
+    None
+}
+
+fn without_scheme(url: &str) -> &str {
+    url.split_once("://").map_or(url, |(_, rest)| rest)
+}
+
+/// Items for `query`, best first.
+pub fn matches(query: &str) -> Vec<PaletteItem> {
+    let query = query.trim();
+    let mut scored: Vec<(i64, PaletteItem)> = Vec::new();
+
+    let host = host_commands().lock().unwrap();
+    for entry in builtins().iter().chain(host.iter()) {
+        let keyword_score = entry
+            .keywords
+            .iter()
+            .filter_map(|keyword| fuzzy_score(query, keyword))
+            .max()
+            .map(|score| score - 2);
+        let Some(score) = fuzzy_score(query, &entry.title).max(keyword_score) else {
+            continue;
+        };
+        scored.push((
+            score,
+            PaletteItem {
+                command: entry.command.clone(),
+                title: entry.title.clone(),
+                detail: entry.shortcut.clone().unwrap_or_default(),
+            },
+        ));
+    }
+    drop(host);
+
+    // Pages only once something's typed, so an empty palette lists commands
+    if !query.is_empty() {
+        let mut seen: Vec<String> = Vec::new();
+        let mut page = |url: &str, title: &str, bonus: i64| {
+            if seen.iter().any(|seen| seen == url) {
+                return;
+            }
+            let Some(score) = fuzzy_score(query, title).max(fuzzy_score(query, without_scheme(url))) else {
+                return;
+            };
+            seen.push(url.to_string());
+            scored.push((
+                score + bonus,
+                PaletteItem {
+                    command: Command::Open(url.to_string()),
+                    title: if title.is_empty() { url.to_string() } else { title.to_string() },
+                    detail: url.to_string(),
+                },
+            ));
+        };
+        for bookmark in bookmarks::search("", usize::MAX) {
+            if let Some(url) = &bookmark.url {
+                page(url, &bookmark.title, BOOKMARK_BONUS);
+            }
+        }
+        for entry in history::search("", history::SearchMode::Text, HISTORY_CANDIDATES) {
+            page(&entry.url, &entry.title, entry.visit_count.min(5) as i64);
+        }
+    }
+
+    // Stable, so equal scores keep commands first and in their listed order
+    scored.sort_by(|a, b| b.0.cmp(&a.0));
+    let mut items: Vec<PaletteItem> = scored.into_iter().map(|(_, item)| item).collect();
+
+    if let Some(url) = typed_url(query) {
+        items.retain(|item| item.command != Command::Open(url.clone()));
+        items.insert(
+            0,
+            PaletteItem {
+                command: Command::Open(url.clone()),
+                title: format!("Open {url}"),
+                detail: String::new(),
+            },
+        );
+    }
+    items.truncate(MAX_RESULTS);
+    items
+}
+
+/// What a key or click in the palette did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Keep,
+    Close,
+    Run(Command),
+}
+
+pub struct PaletteState {
+    pub query: String,
+    items: Vec<PaletteItem>,
+    selected: usize,
+}
+
+impl PaletteState {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            items: matches(""),
+            selected: 0,
+        }
+    }
+
+    pub fn items(&self) -> &[PaletteItem] {
+        &self.items
+    }
+
+    pub fn selected(&self) -> Option<&PaletteItem> {
+        self.items.get(self.selected)
+    }
+
+    /// Re-run the match for `self.query`, selecting the best item.
+    pub fn refresh(&mut self) {
+        self.items = matches(&self.query);
+        self.selected = 0;
+    }
+
+    /// Move the selection down (or up), wrapping around.
+    pub fn step(&mut self, forward: bool) {
+        let count = self.items.len();
+        if count == 0 {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % count
+        } else {
+            (self.selected + count - 1) % count
+        };
+    }
+
+    pub fn handle_key(&mut self, key: &Key, text: Option<&str>) -> Outcome {
+        match key {
+            Key::Named(NamedKey::Escape) => return Outcome::Close,
+            Key::Named(NamedKey::Enter) => {
+                if let Some(item) = self.selected() {
+                    return Outcome::Run(item.command.clone());
+                }
+            }
+            Key::Named(NamedKey::ArrowDown) => self.step(true),
+            Key::Named(NamedKey::ArrowUp) => self.step(false),
+            Key::Named(NamedKey::Backspace) => {
+                self.query.pop();
+                self.refresh();
+            }
+            _ => {
+                if let Some(text) = text.filter(|text| !text.chars().any(char::is_control)) {
+                    self.query.push_str(text);
+                    self.refresh();
+                }
+            }
+        }
+        Outcome::Keep
+    }
+
+    /// What a click on `node_id` does: runs the item under it, closes the
+    /// palette if it's on the backdrop, and otherwise nothing.
+    pub fn click(&self, doc: &BaseDocument, node_id: usize) -> Outcome {
+        if overlay::find(doc, OVERLAY_NAME) == Some(node_id) {
+            return Outcome::Close;
+        }
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            if let Some(index) = dom::attr(doc, id, ITEM_ATTR) {
+                let item = index.parse::<usize>().ok().and_then(|index| self.items.get(index));
+                return item.map_or(Outcome::Keep, |item| Outcome::Run(item.command.clone()));
+            }
+            if dom::attr(doc, id, overlay::OVERLAY_ATTR).is_some() {
+                break;
+            }
+            current = doc.get_node(id).and_then(|node| node.parent);
+        }
+        Outcome::Keep
+    }
+
+    /// Draw (or redraw) the palette for the current query and selection.
+    pub fn render(&self, doc: &mut BaseDocument) {
+        let (query, query_style) = if self.query.is_empty() {
+            ("Type a command or URL", PLACEHOLDER_STYLE)
+        } else {
+            (self.query.as_str(), QUERY_STYLE)
+        };
+
+        overlay::mount(doc, OVERLAY_NAME, BACKDROP_STYLE, |mutator, backdrop| {
+            let panel = overlay::append_element(mutator, backdrop, "div", &[("style", PANEL_STYLE)], None);
+            overlay::append_element(mutator, panel, "div", &[("style", query_style)], Some(query));
+            if self.items.is_empty() {
+                overlay::append_element(mutator, panel, "div", &[("style", EMPTY_STYLE)], Some("No matches"));
+            }
+            for (index, item) in self.items.iter().enumerate() {
+                let style = if index == self.selected { SELECTED_ITEM_STYLE } else { ITEM_STYLE };
+                let row = overlay::append_element(
+                    mutator,
+                    panel,
+                    "div",
+                    &[(ITEM_ATTR, &index.to_string()), ("style", style)],
+                    None,
+                );
+                overlay::append_element(mutator, row, "span", &[("style", TITLE_STYLE)], Some(&item.title));
+                if !item.detail.is_empty() {
+                    overlay::append_element(mutator, row, "span", &[("style", DETAIL_STYLE)], Some(&item.detail));
+                }
+            }
+        });
+    }
+
+    pub fn close(&self, doc: &mut BaseDocument) {
+        overlay::remove(doc, OVERLAY_NAME);
+    }
+}
+
+impl Default for PaletteState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Serialize)]
+struct PaletteCommandEvent<'a> {
+    window_id: u64,
+    id: &'a str,
+    page_url: &'a str,
+}
+
+/// Tell the host one of its commands was picked.
+pub(crate) fn run_host_command(window_id: u64, page_url: &str, id: &str) {
+    tracing::info!("Palette ran host command {}", id);
+    host::emit("palette-command", &PaletteCommandEvent { window_id, id, page_url });
+}
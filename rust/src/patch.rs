@@ -0,0 +1,287 @@
+// Update a live document to match new HTML by diffing trees instead of
+// replacing the document, so focus, scroll position, caret and typed input
+// survive host-driven re-renders.
+//
+// Children are matched in order, using `id` (or `data-key`) to line up keyed
+// elements when some were inserted or removed. An unkeyed child that doesn't
+// match in place looks up to `LOOKAHEAD` live siblings ahead, so removing
+// one doesn't rebuild everything after it. Nodes are never moved: a child
+// that can't be matched this way, such as a reordered unkeyed one, is
+// rebuilt. Bridge overlays in the live tree are left alone.
+
+use blitz_dom::{BaseDocument, DocumentMutator, NodeData};
+use blitz_html::HtmlDocument;
+use serde::Serialize;
+
+use crate::{dom, overlay};
+
+/// How much a patch changed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PatchStats {
+    pub inserted: usize,
+    pub removed: usize,
+    pub attributes: usize,
+    pub text: usize,
+}
+
+impl PatchStats {
+    pub fn total(&self) -> usize {
+        self.inserted + self.removed + self.attributes + self.text
+    }
+}
+
+// Elements whose contents Blitz only reads when they're inserted, so changes
+// inside them rebuild the whole element
+const REBUILT_ON_CHANGE: &[&str] = &["style", "link", "script", "title"];
+
+// How many siblings an unkeyed child that doesn't match in place is compared
+// with, in each tree, to tell a removal from an insertion
+const LOOKAHEAD: usize = 8;
+
+/// Patch `live` so its DOM matches `html`.
+pub fn apply(live: &mut BaseDocument, html: &str) -> PatchStats {
+    let target = HtmlDocument::from_html(html, Default::default());
+    let target: &BaseDocument = &target;
+
+    let mut stats = PatchStats::default();
+    patch_children(live, target, 0, 0, &mut stats);
+    stats
+}
+
+enum Kind<'a> {
+    Text(&'a str),
+    Element(&'a str),
+}
+
+/// Nodes that take part in diffing: elements and text, minus overlays.
+fn kind(doc: &BaseDocument, node_id: usize) -> Option<Kind<'_>> {
+    let node = doc.get_node(node_id)?;
+    match &node.data {
+        NodeData::Text(text) => Some(Kind::Text(&text.content)),
+        NodeData::Element(_) => {
+            if dom::attr(doc, node_id, overlay::OVERLAY_ATTR).is_some() {
+                return None;
+            }
+            dom::tag_name(doc, node_id).map(Kind::Element)
+        }
+        _ => None,
+    }
+}
+
+fn key(doc: &BaseDocument, node_id: usize) -> Option<&str> {
+    dom::attr(doc, node_id, "data-key").or_else(|| dom::attr(doc, node_id, "id"))
+}
+
+/// Whether `live_id` can be patched into `target_id` in place.
+fn compatible(live: &BaseDocument, live_id: usize, target: &BaseDocument, target_id: usize) -> bool {
+    match (kind(live, live_id), kind(target, target_id)) {
+        (Some(Kind::Text(_)), Some(Kind::Text(_))) => true,
+        (Some(Kind::Element(a)), Some(Kind::Element(b))) => {
+            a == b
+                && key(live, live_id) == key(target, target_id)
+                // Changing an input's type changes what it is
+                && dom::attr(live, live_id, "type") == dom::attr(target, target_id, "type")
+        }
+        _ => false,
+    }
+}
+
+fn diffable_children(doc: &BaseDocument, node_id: usize) -> Vec<usize> {
+    doc.get_node(node_id)
+        .map(|node| {
+            node.children
+                .iter()
+                .copied()
+                .filter(|child| kind(doc, *child).is_some())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn patch_children(live: &mut BaseDocument, target: &BaseDocument, live_parent: usize, target_parent: usize, stats: &mut PatchStats) {
+    let live_children = diffable_children(live, live_parent);
+    let target_children = diffable_children(target, target_parent);
+    // New nodes go before any overlays so those stay on top
+    let first_overlay = live
+        .get_node(live_parent)
+        .and_then(|node| {
+            node.children
+                .iter()
+                .copied()
+                .find(|child| dom::attr(live, *child, overlay::OVERLAY_ATTR).is_some())
+        });
+
+    let mut next = 0;
+    for (position, &target_child) in target_children.iter().enumerate() {
+        let in_place = live_children
+            .get(next)
+            .is_some_and(|&live_child| compatible(live, live_child, target, target_child));
+
+        let matched = if in_place {
+            Some(next)
+        } else if let Some(wanted) = key(target, target_child) {
+            // A keyed element further on means the ones before it were removed
+            live_children[next..]
+                .iter()
+                .position(|&live_child| {
+                    key(live, live_child) == Some(wanted) && compatible(live, live_child, target, target_child)
+                })
+                .map(|offset| next + offset)
+        } else {
+            skip_removed(live, &live_children, next, target, target_child, &target_children[position + 1..])
+        };
+
+        match matched {
+            Some(index) => {
+                remove_all(live, &live_children[next..index], stats);
+                patch_node(live, target, live_children[index], target_child, stats);
+                next = index + 1;
+            }
+            None => {
+                let anchor = live_children.get(next).copied().or(first_overlay);
+                let mut mutator = live.mutate();
+                let Some(created) = clone_into(&mut mutator, target, target_child) else {
+                    continue;
+                };
+                match anchor {
+                    Some(anchor) => mutator.insert_nodes_before(anchor, &[created]),
+                    None => mutator.append_children(live_parent, &[created]),
+                }
+                stats.inserted += 1;
+            }
+        }
+    }
+
+    remove_all(live, &live_children[next.min(live_children.len())..], stats);
+}
+
+/// For an unkeyed `target_child` that doesn't match `live_children[next]`:
+/// the index of a live sibling shortly after that it does match, when the
+/// live children in between look removed. If `live_children[next]` matches
+/// one of the `upcoming` target children instead, `target_child` was
+/// inserted before it and `None` is returned.
+fn skip_removed(
+    live: &BaseDocument,
+    live_children: &[usize],
+    next: usize,
+    target: &BaseDocument,
+    target_child: usize,
+    upcoming: &[usize],
+) -> Option<usize> {
+    let &current = live_children.get(next)?;
+    let offset = live_children[next + 1..]
+        .iter()
+        .take(LOOKAHEAD)
+        .position(|&live_child| compatible(live, live_child, target, target_child))?;
+    let inserted = upcoming
+        .iter()
+        .take(LOOKAHEAD)
+        .any(|&upcoming| compatible(live, current, target, upcoming));
+    (!inserted).then_some(next + 1 + offset)
+}
+
+fn remove_all(live: &mut BaseDocument, nodes: &[usize], stats: &mut PatchStats) {
+    if nodes.is_empty() {
+        return;
+    }
+    let mut mutator = live.mutate();
+    for &node_id in nodes {
+        mutator.remove_node(node_id);
+        stats.removed += 1;
+    }
+}
+
+fn patch_node(live: &mut BaseDocument, target: &BaseDocument, live_id: usize, target_id: usize, stats: &mut PatchStats) {
+    match (kind(live, live_id), kind(target, target_id)) {
+        (Some(Kind::Text(old)), Some(Kind::Text(new))) => {
+            if old != new {
+                let new = new.to_string();
+                let mut mutator = live.mutate();
+                let replacement = mutator.create_text_node(&new);
+                mutator.insert_nodes_before(live_id, &[replacement]);
+                mutator.remove_node(live_id);
+                stats.text += 1;
+            }
+        }
+        (Some(Kind::Element(tag)), Some(Kind::Element(_))) => {
+            if REBUILT_ON_CHANGE.contains(&tag) {
+                if outer_text(live, live_id) != outer_text(target, target_id) || attrs(live, live_id) != attrs(target, target_id) {
+                    let mut mutator = live.mutate();
+                    if let Some(replacement) = clone_into(&mut mutator, target, target_id) {
+                        mutator.insert_nodes_before(live_id, &[replacement]);
+                    }
+                    mutator.remove_node(live_id);
+                    stats.removed += 1;
+                    stats.inserted += 1;
+                }
+                return;
+            }
+            patch_attributes(live, target, live_id, target_id, stats);
+            patch_children(live, target, live_id, target_id, stats);
+        }
+        _ => {}
+    }
+}
+
+fn attrs(doc: &BaseDocument, node_id: usize) -> Vec<(String, String)> {
+    let mut attrs: Vec<(String, String)> = doc
+        .get_node(node_id)
+        .and_then(|node| node.element_data())
+        .map(|element| {
+            element
+                .attrs()
+                .iter()
+                .map(|attr| (attr.name.local.to_string(), attr.value.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+    attrs.sort();
+    attrs
+}
+
+fn outer_text(doc: &BaseDocument, node_id: usize) -> String {
+    doc.get_node(node_id).map(|node| node.text_content()).unwrap_or_default()
+}
+
+fn patch_attributes(live: &mut BaseDocument, target: &BaseDocument, live_id: usize, target_id: usize, stats: &mut PatchStats) {
+    let old = attrs(live, live_id);
+    let new = attrs(target, target_id);
+    if old == new {
+        return;
+    }
+
+    let mut mutator = live.mutate();
+    for (name, value) in &new {
+        let unchanged = old.iter().any(|(old_name, old_value)| old_name == name && old_value == value);
+        if !unchanged {
+            mutator.set_attribute(live_id, overlay::attr_name(name), value);
+            stats.attributes += 1;
+        }
+    }
+    for (name, _) in &old {
+        if !new.iter().any(|(new_name, _)| new_name == name) {
+            mutator.clear_attribute(live_id, overlay::attr_name(name));
+            stats.attributes += 1;
+        }
+    }
+}
+
+/// Recreate `node_id` from `source` (and its subtree) in the mutator's
+/// document. Only elements and text are copied; returns `None` for anything else.
+pub(crate) fn clone_into(mutator: &mut DocumentMutator<'_>, source: &BaseDocument, node_id: usize) -> Option<usize> {
+    let node = source.get_node(node_id)?;
+    match &node.data {
+        NodeData::Text(text) => Some(mutator.create_text_node(&text.content)),
+        NodeData::Element(element) => {
+            let created = mutator.create_element(element.name.clone(), element.attrs().to_vec());
+            let children: Vec<usize> = node
+                .children
+                .iter()
+                .filter_map(|child| clone_into(mutator, source, *child))
+                .collect();
+            mutator.append_children(created, &children);
+            Some(created)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,30 @@
+// Files the bridge keeps in the profile directory (zoom levels, etc).
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config;
+
+pub fn path(file_name: &str) -> PathBuf {
+    config::get().profile_dir.join(file_name)
+}
+
+/// Write `contents` to `path` so readers only ever see the old or the new
+/// file: write a sibling temp file, fsync it, then rename over the target.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut file = fs::File::create(&tmp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&tmp_path, path)
+}
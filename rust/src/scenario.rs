@@ -0,0 +1,305 @@
+// Scripted browsing flows run headlessly against the test host.
+//
+// A scenario is a TOML file listing the pages the test host serves and the
+// steps to run:
+//
+//     name = "Mod+K navigation"
+//     start = "https://start.test/"
+//
+//     [pages]
+//     "https://start.test/" = { file = "pages/start.html" }
+//     "https://example.test/" = { html = "<title>Example</title>" }
+//
+//     [[step]]
+//     action = "press"
+//     key = "Mod+K"
+//
+//     [[step]]
+//     action = "assert_title"
+//     equals = "Example"
+//
+// Relative paths (page files, screenshots) are resolved against the
+// scenario file's directory.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::driver::TestDriver;
+use crate::{input, keymap, test_host};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    /// Loaded before the first step, if set.
+    #[serde(default)]
+    pub start: Option<String>,
+    #[serde(default)]
+    pub viewport: Option<[u32; 2]>,
+    #[serde(default)]
+    pub pages: BTreeMap<String, Page>,
+    #[serde(rename = "step", default)]
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Page {
+    Inline { html: String },
+    File { file: PathBuf },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Step {
+    Load { url: String },
+    /// A key combination such as `Mod+K`, `Ctrl+Shift+P` or `Enter`. `Mod`
+    /// is the keymap's command modifier.
+    Press { key: String },
+    Type { text: String },
+    Click { selector: String },
+    /// Passes once a navigation has completed since the previous wait (or
+    /// the start), optionally to `url`.
+    WaitForNavigation { url: Option<String> },
+    AssertTitle { equals: String },
+    AssertText {
+        selector: String,
+        #[serde(default)]
+        equals: Option<String>,
+        #[serde(default)]
+        contains: Option<String>,
+    },
+    AssertUrl { equals: String },
+    Screenshot { path: PathBuf },
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Load { url } => write!(f, "load {url}"),
+            Step::Press { key } => write!(f, "press {key}"),
+            Step::Type { text } => write!(f, "type {text:?}"),
+            Step::Click { selector } => write!(f, "click {selector}"),
+            Step::WaitForNavigation { url: Some(url) } => write!(f, "wait for navigation to {url}"),
+            Step::WaitForNavigation { url: None } => write!(f, "wait for navigation"),
+            Step::AssertTitle { equals } => write!(f, "assert title is {equals:?}"),
+            Step::AssertText { selector, .. } => write!(f, "assert text of {selector}"),
+            Step::AssertUrl { equals } => write!(f, "assert url is {equals}"),
+            Step::Screenshot { path } => write!(f, "screenshot {}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StepOutcome {
+    Passed,
+    Failed(String),
+    Skipped,
+}
+
+#[derive(Debug)]
+pub struct StepReport {
+    pub description: String,
+    pub outcome: StepOutcome,
+    pub duration: Duration,
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub name: String,
+    pub steps: Vec<StepReport>,
+}
+
+impl Report {
+    pub fn passed(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|step| matches!(step.outcome, StepOutcome::Passed))
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.steps.len();
+        writeln!(f, "{}", self.name)?;
+        for (index, step) in self.steps.iter().enumerate() {
+            let status = match &step.outcome {
+                StepOutcome::Passed => "ok".to_string(),
+                StepOutcome::Failed(reason) => format!("FAILED: {reason}"),
+                StepOutcome::Skipped => "skipped".to_string(),
+            };
+            writeln!(
+                f,
+                "  [{}/{}] {} ... {} ({} ms)",
+                index + 1,
+                total,
+                step.description,
+                status,
+                step.duration.as_millis()
+            )?;
+        }
+        write!(f, "{}", if self.passed() { "PASS" } else { "FAIL" })
+    }
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Driver(String),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Io(path, err) => write!(f, "{}: {err}", path.display()),
+            ScenarioError::Parse(path, err) => write!(f, "{}: {err}", path.display()),
+            ScenarioError::Driver(err) => write!(f, "failed to start headless window: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+fn read(path: &Path) -> Result<String, ScenarioError> {
+    std::fs::read_to_string(path).map_err(|err| ScenarioError::Io(path.to_path_buf(), err))
+}
+
+pub fn load(path: &Path) -> Result<Scenario, ScenarioError> {
+    toml::from_str(&read(path)?).map_err(|err| ScenarioError::Parse(path.to_path_buf(), err))
+}
+
+/// Run the scenario at `path`. Steps after the first failure are skipped.
+pub fn run_file(path: &Path) -> Result<Report, ScenarioError> {
+    let scenario = load(path)?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    run(&scenario, base_dir)
+}
+
+pub fn run(scenario: &Scenario, base_dir: &Path) -> Result<Report, ScenarioError> {
+    test_host::reset();
+    test_host::use_temp_profile();
+    for (url, page) in &scenario.pages {
+        let html = match page {
+            Page::Inline { html } => html.clone(),
+            Page::File { file } => read(&base_dir.join(file))?,
+        };
+        test_host::serve(url, &html);
+    }
+
+    let [width, height] = scenario.viewport.unwrap_or([800, 600]);
+    let mut driver = TestDriver::with_viewport("", "about:blank", width, height, 1.0)
+        .map_err(|err| ScenarioError::Driver(err.to_string()))?;
+    if let Some(start) = &scenario.start {
+        driver.load(start);
+    }
+
+    let mut runner = Runner {
+        driver,
+        base_dir,
+        navigations_seen: test_host::navigations().len(),
+    };
+
+    let mut steps = Vec::new();
+    let mut failed = false;
+    for step in &scenario.steps {
+        let started = Instant::now();
+        let outcome = if failed {
+            StepOutcome::Skipped
+        } else {
+            match runner.run_step(step) {
+                Ok(()) => StepOutcome::Passed,
+                Err(reason) => {
+                    failed = true;
+                    StepOutcome::Failed(reason)
+                }
+            }
+        };
+        steps.push(StepReport {
+            description: step.to_string(),
+            outcome,
+            duration: started.elapsed(),
+        });
+    }
+
+    Ok(Report {
+        name: scenario.name.clone(),
+        steps,
+    })
+}
+
+struct Runner<'a> {
+    driver: TestDriver,
+    base_dir: &'a Path,
+    navigations_seen: usize,
+}
+
+impl Runner<'_> {
+    fn run_step(&mut self, step: &Step) -> Result<(), String> {
+        let driver = &mut self.driver;
+        match step {
+            Step::Load { url } => driver.load(url),
+            Step::Press { key } => {
+                let (mods, code) = input::parse_combo(key, keymap::mod_modifier()).ok_or_else(|| format!("unknown key {key:?}"))?;
+                driver.press_with(mods, code);
+            }
+            Step::Type { text } => driver.type_text(text),
+            Step::Click { selector } => {
+                if !driver.click_selector(selector) {
+                    return Err(format!("no element matches {selector}"));
+                }
+            }
+            Step::WaitForNavigation { url } => {
+                // The driver applies navigations synchronously, so anything
+                // that was going to load already has
+                let count = test_host::navigations().len();
+                if count == self.navigations_seen {
+                    return Err("no navigation happened".to_string());
+                }
+                self.navigations_seen = count;
+                if let Some(url) = url {
+                    expect("url", url, &driver.url())?;
+                }
+            }
+            Step::AssertTitle { equals } => {
+                expect("title", equals, &driver.title().unwrap_or_default())?;
+            }
+            Step::AssertText { selector, equals, contains } => {
+                let text = driver
+                    .text(selector)
+                    .ok_or_else(|| format!("no element matches {selector}"))?;
+                let text = text.trim();
+                if let Some(equals) = equals {
+                    expect("text", equals, text)?;
+                }
+                if let Some(contains) = contains {
+                    if !text.contains(contains.as_str()) {
+                        return Err(format!("text {text:?} doesn't contain {contains:?}"));
+                    }
+                }
+            }
+            Step::AssertUrl { equals } => expect("url", equals, &driver.url())?,
+            Step::Screenshot { path } => {
+                let path = self.base_dir.join(path);
+                let png = driver.screenshot_png().map_err(|err| err.to_string())?;
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+                }
+                std::fs::write(&path, png).map_err(|err| format!("{}: {err}", path.display()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn expect(what: &str, expected: &str, actual: &str) -> Result<(), String> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!("expected {what} {expected:?}, got {actual:?}"))
+    }
+}
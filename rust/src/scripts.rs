@@ -0,0 +1,113 @@
+// Collect `<script>` elements of host-handled types when a document loads.
+//
+// Blitz has no script engine; scripts of the configured types (TypeScript by
+// default) are handed to the host, which runs them elsewhere (Bun). Every
+// loaded document gets a new generation id so the host can tell which page
+// a late result belongs to.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use blitz_dom::BaseDocument;
+use serde::Serialize;
+
+use crate::{config, dom, host};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Script {
+    pub index: usize,
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// `src` resolved against the document URL, for external scripts.
+    pub src: Option<String>,
+    /// Inline source text; empty for external scripts.
+    pub source: String,
+    pub attributes: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+struct DocumentLoadedEvent<'a> {
+    window_id: u64,
+    generation: u64,
+    url: &'a str,
+    scripts: Vec<Script>,
+}
+
+pub fn next_generation() -> u64 {
+    static GENERATION: AtomicU64 = AtomicU64::new(1);
+    GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// The script's type, normalised. Scripts without one are JavaScript.
+fn script_type(doc: &BaseDocument, node_id: usize) -> String {
+    match dom::attr(doc, node_id, "type").map(str::trim) {
+        Some(kind) if !kind.is_empty() => kind.to_ascii_lowercase(),
+        _ => "text/javascript".to_string(),
+    }
+}
+
+/// Scripts whose type is in `types`, in document order.
+pub fn collect(doc: &BaseDocument, base_url: &str, types: &[String]) -> Vec<Script> {
+    let mut scripts = Vec::new();
+    let mut stack = vec![0usize];
+
+    while let Some(node_id) = stack.pop() {
+        let Some(node) = doc.get_node(node_id) else {
+            continue;
+        };
+        stack.extend(node.children.iter().rev().copied());
+
+        if dom::tag_name(doc, node_id) != Some("script") {
+            continue;
+        }
+        let kind = script_type(doc, node_id);
+        if !types.iter().any(|wanted| wanted.eq_ignore_ascii_case(&kind)) {
+            continue;
+        }
+
+        let attributes = node
+            .element_data()
+            .map(|element| {
+                element
+                    .attrs()
+                    .iter()
+                    .map(|attr| (attr.name.local.to_string(), attr.value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let src = dom::attr(doc, node_id, "src").map(|src| dom::resolve_url(base_url, src));
+        let source = if src.is_some() { String::new() } else { node.text_content() };
+
+        scripts.push(Script {
+            index: scripts.len(),
+            kind,
+            src,
+            source,
+            attributes,
+        });
+    }
+
+    scripts
+}
+
+/// Tell the host a new document is live in `window_id`, with its scripts.
+/// Returns the document's generation.
+pub fn document_loaded(doc: &BaseDocument, window_id: u64, url: &str) -> u64 {
+    let generation = next_generation();
+    let types = config::get().script_types.clone();
+    let scripts = collect(doc, url, &types);
+    if !scripts.is_empty() {
+        tracing::info!("Found {} script(s) for the host in {}", scripts.len(), url);
+    }
+
+    host::emit(
+        "document-loaded",
+        &DocumentLoadedEvent {
+            window_id,
+            generation,
+            url,
+            scripts,
+        },
+    );
+    generation
+}
@@ -0,0 +1,138 @@
+// Multi-key sequences (`g g`, `Ctrl+X Ctrl+F`) and key modes.
+//
+// Every window feeds key presses through a `KeySequence`, which matches them
+// against the `keymap` bindings for its current mode. Keys that start a
+// longer binding are held until the next key completes it or the keymap's
+// timeout passes; while that's happening, and while the window is in a mode
+// other than the one it started in, an indicator shows in the bottom-right
+// corner.
+//
+// Plain keys typed into a focused text field never get here, so page forms
+// work whatever the keymap binds.
+
+use std::time::Instant;
+
+use blitz_dom::BaseDocument;
+use winit::keyboard::ModifiersState;
+
+use crate::forms;
+use crate::input::{self, Chord, KeyPress, Shortcut};
+use crate::keymap::{self, Action, Match};
+
+/// What a key press amounted to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// Not bound: the key goes on to the page.
+    Unhandled,
+    /// Held as the start of a longer binding.
+    Pending,
+    /// Completed a binding that switched mode.
+    Handled,
+    /// Completed a binding for `Shortcut`.
+    Run(Shortcut),
+}
+
+#[derive(Debug)]
+pub struct KeySequence {
+    mode: String,
+    pending: Vec<KeyPress>,
+    // The chords `pending` matched, for the indicator
+    typed: Vec<Chord>,
+    deadline: Option<Instant>,
+}
+
+impl Default for KeySequence {
+    fn default() -> Self {
+        Self {
+            mode: keymap::start_mode(),
+            pending: Vec::new(),
+            typed: Vec::new(),
+            deadline: None,
+        }
+    }
+}
+
+impl KeySequence {
+    pub fn mode(&self) -> &str {
+        &self.mode
+    }
+
+    /// Add a key press to the sequence. A key that doesn't continue the
+    /// pending sequence drops it and is matched on its own.
+    pub fn press(&mut self, press: KeyPress, now: Instant) -> Step {
+        self.expire(now);
+        let continuing = !self.pending.is_empty();
+        self.pending.push(press);
+
+        match keymap::resolve(&self.mode, &self.pending) {
+            Match::Exact(action) => {
+                self.reset();
+                match action {
+                    Action::Shortcut(shortcut) => Step::Run(shortcut),
+                    Action::Mode(mode) => {
+                        tracing::info!("Key mode {mode}");
+                        self.mode = mode;
+                        Step::Handled
+                    }
+                }
+            }
+            Match::Prefix(typed) => {
+                self.typed = typed;
+                self.deadline = Some(now + keymap::timeout());
+                Step::Pending
+            }
+            Match::Unbound => {
+                let press = self.pending.pop().unwrap();
+                self.reset();
+                if continuing {
+                    self.press(press, now)
+                } else {
+                    Step::Unhandled
+                }
+            }
+        }
+    }
+
+    /// Drop the pending sequence if its timeout has passed. Returns true if
+    /// there was one to drop.
+    pub fn expire(&mut self, now: Instant) -> bool {
+        if self.deadline.is_some_and(|deadline| now >= deadline) {
+            self.reset();
+            return true;
+        }
+        false
+    }
+
+    /// When the pending sequence times out, if there is one.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Forget the pending sequence. The mode stays as it is.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.typed.clear();
+        self.deadline = None;
+    }
+
+    /// What the indicator shows, e.g. `INSERT` or `Ctrl+X`, if anything.
+    pub fn indicator(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if self.mode != keymap::start_mode() {
+            parts.push(self.mode.to_uppercase());
+        }
+        if !self.typed.is_empty() {
+            parts.push(keymap::join(&self.typed));
+        }
+        (!parts.is_empty()).then(|| parts.join("  "))
+    }
+}
+
+/// Whether a key press is typing into a focused text field, and so goes
+/// straight to Blitz. Ctrl/Cmd chords still reach the keymap.
+pub fn passes_through(doc: &BaseDocument, mods: ModifiersState) -> bool {
+    !input::has_command_modifier(mods)
+        && doc
+            .get_focussed_node_id()
+            .is_some_and(|node_id| forms::is_text_field(doc, node_id))
+}
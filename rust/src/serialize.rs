@@ -0,0 +1,136 @@
+// Serialize the live DOM back to HTML: what the page is now, after host edits
+// and user input, rather than the source it was loaded from.
+//
+// Form controls are written with their current state (typed text as `value`,
+// `checked` as toggled), so the output reloads the way the page looks.
+// Bridge overlays are left out, and find-in-page highlights are unwrapped
+// so their text reads as the page had it.
+
+use blitz_dom::{BaseDocument, NodeData};
+
+use crate::dom_edit::{self, InvalidSelector};
+use crate::{dom, find, forms, overlay};
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr",
+];
+
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// The whole document, with a doctype.
+pub fn document(doc: &BaseDocument) -> String {
+    let mut out = String::from("<!DOCTYPE html>");
+    write_children(doc, 0, &mut out);
+    out
+}
+
+/// Outer HTML of the first element matching `selector`, or `None` if nothing matches.
+pub fn outer_html(doc: &BaseDocument, selector: &str) -> Result<Option<String>, InvalidSelector> {
+    let Some(node_id) = dom_edit::select(doc, selector)?.into_iter().next() else {
+        return Ok(None);
+    };
+    let mut out = String::new();
+    write_node(doc, node_id, &mut out);
+    Ok(Some(out))
+}
+
+fn write_children(doc: &BaseDocument, node_id: usize, out: &mut String) {
+    let Some(node) = doc.get_node(node_id) else {
+        return;
+    };
+    for &child in &node.children {
+        write_node(doc, child, out);
+    }
+}
+
+fn write_node(doc: &BaseDocument, node_id: usize, out: &mut String) {
+    let Some(node) = doc.get_node(node_id) else {
+        return;
+    };
+    match &node.data {
+        NodeData::Text(text) => {
+            let raw = node
+                .parent
+                .and_then(|parent| dom::tag_name(doc, parent))
+                .is_some_and(|tag| RAW_TEXT_ELEMENTS.contains(&tag));
+            if raw {
+                out.push_str(&text.content);
+            } else {
+                escape(&text.content, false, out);
+            }
+        }
+        NodeData::Element(_) => write_element(doc, node_id, out),
+        _ => {}
+    }
+}
+
+fn write_element(doc: &BaseDocument, node_id: usize, out: &mut String) {
+    if dom::attr(doc, node_id, overlay::OVERLAY_ATTR).is_some() {
+        return;
+    }
+    if dom::attr(doc, node_id, find::MATCH_ATTR).is_some() {
+        write_children(doc, node_id, out);
+        return;
+    }
+    let Some(element) = doc.get_node(node_id).and_then(|node| node.element_data()) else {
+        return;
+    };
+    let tag = element.name.local.as_ref();
+    let kind = forms::input_type(doc, node_id);
+    let toggle = matches!(kind.as_deref(), Some("checkbox" | "radio"));
+    let text_value = forms::is_text_field(doc, node_id)
+        .then(|| element.text_input_data().map(|input| input.editor.text().to_string()))
+        .flatten();
+
+    out.push('<');
+    out.push_str(tag);
+    for attr in element.attrs() {
+        let name = attr.name.local.as_ref();
+        // Written below from the live state instead
+        if (toggle && name == "checked") || (text_value.is_some() && tag == "input" && name == "value") {
+            continue;
+        }
+        write_attribute(name, &attr.value, out);
+    }
+    if toggle && forms::checked(doc, node_id) == Some(true) {
+        write_attribute("checked", "", out);
+    }
+    if let (Some(value), "input") = (&text_value, tag) {
+        write_attribute("value", value, out);
+    }
+    out.push('>');
+
+    if VOID_ELEMENTS.contains(&tag) {
+        return;
+    }
+    match (&text_value, tag) {
+        (Some(value), "textarea") => escape(value, false, out),
+        _ => write_children(doc, node_id, out),
+    }
+    out.push_str("</");
+    out.push_str(tag);
+    out.push('>');
+}
+
+fn write_attribute(name: &str, value: &str, out: &mut String) {
+    out.push(' ');
+    out.push_str(name);
+    if !value.is_empty() {
+        out.push_str("=\"");
+        escape(value, true, out);
+        out.push('"');
+    }
+}
+
+fn escape(text: &str, attribute: bool, out: &mut String) {
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '\u{a0}' => out.push_str("&nbsp;"),
+            '"' if attribute => out.push_str("&quot;"),
+            '<' if !attribute => out.push_str("&lt;"),
+            '>' if !attribute => out.push_str("&gt;"),
+            _ => out.push(ch),
+        }
+    }
+}
@@ -0,0 +1,170 @@
+// Session snapshots: the open windows with their tabs' history, scroll
+// offsets and zoom, saved to `session.json` in the profile directory so the
+// next launch can reopen them.
+//
+// Pages aren't stored; a restored tab fetches its current history entry from
+// the host again the first time it's shown, so background tabs cost nothing
+// until they're switched to. Scroll offsets are only known for the tab a
+// window is showing, so background tabs restore at the top.
+
+use std::fs;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use blitz_dom::BaseDocument;
+use blitz_shell::BlitzShellEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{profile, zoom, BrowserWindow, NavigationState, Tab};
+
+pub const FILE_NAME: &str = "session.json";
+
+/// How often the event loop snapshots the session while it's running.
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+const VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub version: u32,
+    pub windows: Vec<WindowSnapshot>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowSnapshot {
+    pub tabs: Vec<TabSnapshot>,
+    pub active_tab: usize,
+}
+
+impl WindowSnapshot {
+    /// Whether any of its tabs has a history entry to reopen. Windows
+    /// without one are skipped on restore.
+    pub fn is_restorable(&self) -> bool {
+        self.tabs.iter().any(|tab| !tab.history.is_empty())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TabSnapshot {
+    pub history: Vec<String>,
+    pub history_index: usize,
+    /// Viewport scroll offset in CSS pixels.
+    #[serde(default)]
+    pub scroll: [f64; 2],
+    #[serde(default = "default_zoom")]
+    pub zoom: f32,
+}
+
+fn default_zoom() -> f32 {
+    1.0
+}
+
+impl Session {
+    pub fn new(windows: Vec<WindowSnapshot>) -> Self {
+        Self {
+            version: VERSION,
+            windows,
+        }
+    }
+}
+
+/// Snapshot `window`. `doc` is the document it's showing, if any, which
+/// supplies the active tab's scroll offset and zoom.
+pub(crate) fn snapshot_window(window: &BrowserWindow, doc: Option<&BaseDocument>) -> WindowSnapshot {
+    let tabs = window
+        .tabs
+        .iter()
+        .enumerate()
+        .map(|(index, tab)| {
+            let state = tab.state.lock().unwrap();
+            // The history entry rather than `current_url`, which may be the
+            // command palette
+            let url = state.history.get(state.history_index).cloned().unwrap_or_default();
+            let live = doc.filter(|_| index == window.active_tab);
+            TabSnapshot {
+                history: state.history.clone(),
+                history_index: state.history_index,
+                scroll: live
+                    .map(|doc| {
+                        let scroll = doc.viewport_scroll();
+                        [scroll.x, scroll.y]
+                    })
+                    .unwrap_or_default(),
+                zoom: live.map_or_else(|| zoom::level_for(&url), zoom::current),
+            }
+        })
+        .collect();
+
+    WindowSnapshot {
+        tabs,
+        active_tab: window.active_tab,
+    }
+}
+
+/// Recreate a tab. Its current history entry is fetched once it's shown.
+pub(crate) fn restore_tab(
+    snapshot: &TabSnapshot,
+    proxy: Option<winit::event_loop::EventLoopProxy<BlitzShellEvent>>,
+) -> Option<Tab> {
+    let index = snapshot.history_index.min(snapshot.history.len().checked_sub(1)?);
+    let url = snapshot.history[index].clone();
+
+    let mut state = NavigationState::new(String::new(), url.clone());
+    state.history = snapshot.history.clone();
+    state.history_index = index;
+    state.restore_scroll = Some(snapshot.scroll).filter(|scroll| *scroll != [0.0, 0.0]);
+    state.unloaded = true;
+    let tab = Tab::new(Arc::new(Mutex::new(state)), proxy);
+
+    if (zoom::level_for(&url) - snapshot.zoom).abs() > f32::EPSILON {
+        zoom::remember(&url, snapshot.zoom);
+    }
+    Some(tab)
+}
+
+/// Recreate a window and its tabs. `None` if none of its tabs could be restored.
+pub(crate) fn restore_window(
+    snapshot: &WindowSnapshot,
+    proxy: Option<winit::event_loop::EventLoopProxy<BlitzShellEvent>>,
+) -> Option<BrowserWindow> {
+    let mut tabs = snapshot
+        .tabs
+        .iter()
+        .filter_map(|tab| restore_tab(tab, proxy.clone()))
+        .collect::<Vec<_>>()
+        .into_iter();
+    let mut window = BrowserWindow::new(tabs.next()?, None);
+    window.tabs.extend(tabs);
+    window.active_tab = snapshot.active_tab.min(window.tabs.len() - 1);
+    Some(window)
+}
+
+pub fn save(session: &Session) -> io::Result<()> {
+    let json = serde_json::to_vec_pretty(session).map_err(io::Error::other)?;
+    profile::write_atomic(&profile::path(FILE_NAME), &json)
+}
+
+/// The saved session, if there is a readable one.
+pub fn load() -> Option<Session> {
+    let path = profile::path(FILE_NAME);
+    let contents = match fs::read(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+        Err(err) => {
+            tracing::warn!("Failed to read {}: {err}", path.display());
+            return None;
+        }
+    };
+    match serde_json::from_slice::<Session>(&contents) {
+        Ok(session) if session.version == VERSION => Some(session),
+        Ok(session) => {
+            tracing::warn!("Ignoring session from unsupported version {}", session.version);
+            None
+        }
+        Err(err) => {
+            tracing::warn!("Ignoring unreadable session {}: {err}", path.display());
+            None
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// Where each window's document lives.
+//
+// Normally that's a Blitz `View` with a native winit window. Under `driver`
+// there is no native window, so each window is a headless document that the
+// driver lays out after every event. `FrontierApplication` only reaches
+// documents through `Shell`, so the same event handling runs in both.
+
+#[cfg(feature = "test-host")]
+use std::collections::HashMap;
+
+use anyrender_vello::VelloWindowRenderer;
+use blitz_dom::BaseDocument;
+use blitz_html::HtmlDocument;
+use blitz_shell::{BlitzApplication, BlitzShellEvent};
+use winit::event_loop::EventLoopProxy;
+use winit::window::WindowId;
+
+pub(crate) enum Shell {
+    Native {
+        app: BlitzApplication<VelloWindowRenderer>,
+        proxy: EventLoopProxy<BlitzShellEvent>,
+    },
+    #[cfg(feature = "test-host")]
+    Headless(HashMap<WindowId, HeadlessView>),
+}
+
+#[cfg(feature = "test-host")]
+pub(crate) struct HeadlessView {
+    pub(crate) document: HtmlDocument,
+    pub(crate) scale: f64,
+}
+
+impl Shell {
+    pub(crate) fn native(proxy: EventLoopProxy<BlitzShellEvent>) -> Self {
+        Shell::Native {
+            app: BlitzApplication::new(proxy.clone()),
+            proxy,
+        }
+    }
+
+    /// The proxy tabs use to wake the event loop; `None` when headless.
+    pub(crate) fn proxy(&self) -> Option<EventLoopProxy<BlitzShellEvent>> {
+        match self {
+            Shell::Native { proxy, .. } => Some(proxy.clone()),
+            #[cfg(feature = "test-host")]
+            Shell::Headless(_) => None,
+        }
+    }
+
+    pub(crate) fn document(&self, id: WindowId) -> Option<&BaseDocument> {
+        match self {
+            Shell::Native { app, .. } => app.windows.get(&id).map(|view| {
+                let doc: &BaseDocument = &view.doc;
+                doc
+            }),
+            #[cfg(feature = "test-host")]
+            Shell::Headless(views) => views.get(&id).map(|view| {
+                let doc: &BaseDocument = &view.document;
+                doc
+            }),
+        }
+    }
+
+    pub(crate) fn document_mut(&mut self, id: WindowId) -> Option<&mut BaseDocument> {
+        match self {
+            Shell::Native { app, .. } => app.windows.get_mut(&id).map(|view| {
+                let doc: &mut BaseDocument = &mut view.doc;
+                doc
+            }),
+            #[cfg(feature = "test-host")]
+            Shell::Headless(views) => views.get_mut(&id).map(|view| {
+                let doc: &mut BaseDocument = &mut view.document;
+                doc
+            }),
+        }
+    }
+
+    pub(crate) fn scale_factor(&self, id: WindowId) -> f64 {
+        match self {
+            Shell::Native { app, .. } => app.windows.get(&id).map_or(1.0, |view| view.window.scale_factor()),
+            #[cfg(feature = "test-host")]
+            Shell::Headless(views) => views.get(&id).map_or(1.0, |view| view.scale),
+        }
+    }
+
+    /// Ask for the window to be repainted. Headless documents are laid out
+    /// by the driver after every event instead.
+    pub(crate) fn request_redraw(&self, id: WindowId) {
+        match self {
+            Shell::Native { app, .. } => {
+                if let Some(view) = app.windows.get(&id) {
+                    view.window.request_redraw();
+                }
+            }
+            #[cfg(feature = "test-host")]
+            Shell::Headless(_) => {}
+        }
+    }
+
+    pub(crate) fn replace_document(&mut self, id: WindowId, doc: HtmlDocument) {
+        match self {
+            Shell::Native { app, .. } => {
+                if let Some(view) = app.windows.get_mut(&id) {
+                    view.replace_document(Box::new(doc) as _, false);
+                }
+            }
+            #[cfg(feature = "test-host")]
+            Shell::Headless(views) => {
+                if let Some(view) = views.get_mut(&id) {
+                    view.document = doc;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, id: WindowId) {
+        match self {
+            Shell::Native { app, .. } => {
+                app.windows.remove(&id);
+            }
+            #[cfg(feature = "test-host")]
+            Shell::Headless(views) => {
+                views.remove(&id);
+            }
+        }
+    }
+}
@@ -0,0 +1,236 @@
+// Rust implementations of the symbols the Zig host normally provides, so the
+// bridge can be linked and exercised by `cargo test` alone.
+//
+// Pages are served from an in-memory map of URL to HTML. Every call into the
+// "host" is logged per thread (tests run on separate threads), and buffers
+// handed to the bridge are tracked until it frees them. Each thread also gets
+// its own profile and download directories.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread::ThreadId;
+
+use crate::{config, HtmlResult};
+
+/// A call the bridge made into the host.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HostCall {
+    NavigateToUrl(String),
+    FreeHtml { len: usize },
+    /// `frontier_free_html` with a pointer the host never handed out, or
+    /// one already freed.
+    BadFree { len: usize },
+    HostEvent { name: String, payload: String },
+}
+
+type EventHandler = Box<dyn FnMut(&str)>;
+
+#[derive(Default)]
+struct ThreadState {
+    calls: Vec<HostCall>,
+    handlers: Vec<(String, EventHandler)>,
+}
+
+thread_local! {
+    static STATE: RefCell<ThreadState> = RefCell::new(ThreadState::default());
+}
+
+fn pages() -> &'static Mutex<HashMap<String, String>> {
+    static PAGES: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    PAGES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// Buffers returned to the bridge, by address, until `frontier_free_html`.
+// Tagged with the thread they were handed to so counts stay per test.
+type Allocations = HashMap<usize, (ThreadId, Box<[u8]>)>;
+
+fn allocations() -> &'static Mutex<Allocations> {
+    static ALLOCATIONS: OnceLock<Mutex<Allocations>> = OnceLock::new();
+    ALLOCATIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn record(call: HostCall) {
+    STATE.with(|state| state.borrow_mut().calls.push(call));
+}
+
+/// Serve `html` for `url` (shared by all threads).
+pub fn serve(url: &str, html: &str) {
+    pages().lock().unwrap().insert(url.to_string(), html.to_string());
+}
+
+pub fn calls() -> Vec<HostCall> {
+    STATE.with(|state| state.borrow().calls.clone())
+}
+
+pub fn navigations() -> Vec<String> {
+    calls()
+        .into_iter()
+        .filter_map(|call| match call {
+            HostCall::NavigateToUrl(url) => Some(url),
+            _ => None,
+        })
+        .collect()
+}
+
+/// (name, JSON payload) of every host event emitted on this thread.
+pub fn events() -> Vec<(String, String)> {
+    calls()
+        .into_iter()
+        .filter_map(|call| match call {
+            HostCall::HostEvent { name, payload } => Some((name, payload)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Run `handler` with the payload whenever this thread emits a `name` host
+/// event, like a host callback would. FFI functions called from it run on
+/// the (stand-in) event loop thread.
+pub fn on_event(name: &str, handler: impl FnMut(&str) + 'static) {
+    STATE.with(|state| state.borrow_mut().handlers.push((name.to_string(), Box::new(handler))));
+}
+
+/// Forget this thread's calls and event handlers.
+pub fn reset() {
+    STATE.with(|state| *state.borrow_mut() = ThreadState::default());
+}
+
+/// Number of buffers handed to the bridge on this thread that it hasn't freed yet.
+pub fn outstanding_allocations() -> usize {
+    let thread = std::thread::current().id();
+    allocations()
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|(owner, _)| *owner == thread)
+        .count()
+}
+
+/// Number of `frontier_free_html` calls on this thread that didn't match a
+/// buffer the host handed out.
+pub fn bad_frees() -> usize {
+    calls()
+        .iter()
+        .filter(|call| matches!(call, HostCall::BadFree { .. }))
+        .count()
+}
+
+/// This process's temporary directory, which holds every thread's
+/// directory.
+pub fn temp_dir() -> PathBuf {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(|| std::env::temp_dir().join(format!("frontier-test-host-{}", std::process::id())))
+        .clone()
+}
+
+/// This thread's directory under `temp_dir`, which holds its default
+/// profile and download directories.
+pub fn thread_dir() -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    thread_local! {
+        static DIR: PathBuf = temp_dir().join(format!("thread-{}", NEXT.fetch_add(1, Ordering::Relaxed)));
+    }
+    DIR.with(PathBuf::clone)
+}
+
+/// Point this thread's profile and download directories back at its own
+/// directory, in case the test changed them. Returns that directory.
+pub fn use_temp_profile() -> PathBuf {
+    let dir = thread_dir();
+    let mut config = config::get();
+    config.profile_dir = dir.join("profile");
+    config.download_dir = dir.join("downloads");
+    dir
+}
+
+fn hand_out(html: String) -> HtmlResult {
+    let bytes = html.into_bytes().into_boxed_slice();
+    let result = HtmlResult {
+        ptr: bytes.as_ptr(),
+        len: bytes.len(),
+    };
+    allocations()
+        .lock()
+        .unwrap()
+        .insert(result.ptr as usize, (std::thread::current().id(), bytes));
+    result
+}
+
+/// Same `?url=` extraction the Zig host does for its navigator form.
+fn extract_url(url: &str) -> String {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return url.to_string();
+    };
+    parsed
+        .query_pairs()
+        .find(|(name, _)| name == "url")
+        .map(|(_, value)| value.into_owned())
+        .unwrap_or_else(|| url.to_string())
+}
+
+fn not_found(url: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html><head><title>Error</title></head><body><h1>Navigation Error</h1><code>{}</code></body></html>",
+        crate::about::escape_html(url)
+    )
+}
+
+// Panicking here would unwind into an `extern "C"` caller, so a bad free is
+// recorded for tests to assert on instead
+#[no_mangle]
+extern "C" fn frontier_free_html(ptr: *const u8, len: usize) {
+    if allocations().lock().unwrap().remove(&(ptr as usize)).is_some() {
+        record(HostCall::FreeHtml { len });
+    } else {
+        tracing::error!("frontier_free_html called with a pointer the test host didn't hand out ({ptr:?}, {len})");
+        record(HostCall::BadFree { len });
+    }
+}
+
+#[no_mangle]
+extern "C" fn frontier_navigate_to_url(url_ptr: *const u8, url_len: usize) -> HtmlResult {
+    let bytes = unsafe { std::slice::from_raw_parts(url_ptr, url_len) };
+    let url = String::from_utf8_lossy(bytes).into_owned();
+    record(HostCall::NavigateToUrl(url.clone()));
+
+    let target = extract_url(&url);
+    let html = pages()
+        .lock()
+        .unwrap()
+        .get(&target)
+        .cloned()
+        .unwrap_or_else(|| not_found(&target));
+    hand_out(html)
+}
+
+#[no_mangle]
+extern "C" fn frontier_host_event(name_ptr: *const u8, name_len: usize, payload_ptr: *const u8, payload_len: usize) {
+    let (name, payload) = unsafe {
+        (
+            std::slice::from_raw_parts(name_ptr, name_len),
+            std::slice::from_raw_parts(payload_ptr, payload_len),
+        )
+    };
+    let name = String::from_utf8_lossy(name).into_owned();
+    let payload = String::from_utf8_lossy(payload).into_owned();
+    record(HostCall::HostEvent {
+        name: name.clone(),
+        payload: payload.clone(),
+    });
+
+    // Handlers may emit events themselves, so they run without the state borrowed
+    let mut handlers = STATE.with(|state| std::mem::take(&mut state.borrow_mut().handlers));
+    for (event, handler) in &mut handlers {
+        if *event == name {
+            handler(&payload);
+        }
+    }
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        handlers.append(&mut state.handlers);
+        state.handlers = handlers;
+    });
+}
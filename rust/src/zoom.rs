@@ -0,0 +1,123 @@
+// Page zoom levels, remembered per origin in the profile directory, which is
+// read again if the profile directory changes.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use blitz_dom::BaseDocument;
+
+use crate::profile;
+
+const FILE_NAME: &str = "zoom.json";
+
+pub const DEFAULT_ZOOM: f32 = 1.0;
+pub const MIN_ZOOM: f32 = 0.25;
+pub const MAX_ZOOM: f32 = 5.0;
+
+// Same steps mainstream browsers use for keyboard zoom
+const STEPS: &[f32] = &[
+    0.25, 0.33, 0.5, 0.67, 0.75, 0.8, 0.9, 1.0, 1.1, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0, 4.0, 5.0,
+];
+
+/// The next zoom step above (or below) `current`.
+pub fn step(current: f32, zoom_in: bool) -> f32 {
+    const EPSILON: f32 = 0.001;
+    let next = if zoom_in {
+        STEPS.iter().copied().find(|step| *step > current + EPSILON)
+    } else {
+        STEPS.iter().rev().copied().find(|step| *step < current - EPSILON)
+    };
+    next.unwrap_or(current)
+}
+
+pub fn clamp(level: f32) -> f32 {
+    level.clamp(MIN_ZOOM, MAX_ZOOM)
+}
+
+/// The key zoom levels are stored under. Opaque origins (`file:`, `about:`)
+/// share one entry per scheme.
+pub fn origin_key(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(parsed) => {
+            let origin = parsed.origin();
+            if origin.is_tuple() {
+                origin.ascii_serialization()
+            } else {
+                format!("{}:", parsed.scheme())
+            }
+        }
+        Err(_) => url.to_string(),
+    }
+}
+
+struct Store {
+    path: PathBuf,
+    levels: HashMap<String, f32>,
+}
+
+/// The levels for the current profile directory, reloaded if it changed.
+fn store() -> MutexGuard<'static, Store> {
+    static STORE: OnceLock<Mutex<Store>> = OnceLock::new();
+    let mut store = STORE
+        .get_or_init(|| {
+            Mutex::new(Store {
+                path: PathBuf::new(),
+                levels: HashMap::new(),
+            })
+        })
+        .lock()
+        .unwrap();
+
+    let path = profile::path(FILE_NAME);
+    if store.path != path {
+        store.levels = load(&path);
+        store.path = path;
+    }
+    store
+}
+
+fn load(path: &Path) -> HashMap<String, f32> {
+    match std::fs::read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|err| {
+            tracing::warn!("Ignoring unreadable {}: {err}", path.display());
+            HashMap::new()
+        }),
+        Err(_) => HashMap::new(),
+    }
+}
+
+fn save(store: &Store) {
+    let result = serde_json::to_vec_pretty(&store.levels)
+        .map_err(std::io::Error::other)
+        .and_then(|json| profile::write_atomic(&store.path, &json));
+    if let Err(err) = result {
+        tracing::error!("Failed to save zoom levels to {}: {err}", store.path.display());
+    }
+}
+
+pub fn level_for(url: &str) -> f32 {
+    store().levels.get(&origin_key(url)).copied().unwrap_or(DEFAULT_ZOOM)
+}
+
+/// Remember `level` for the origin of `url`. The default level is stored as
+/// "no entry" so the file only lists origins that were actually changed.
+pub fn remember(url: &str, level: f32) {
+    let mut store = store();
+    let key = origin_key(url);
+    if (level - DEFAULT_ZOOM).abs() < f32::EPSILON {
+        store.levels.remove(&key);
+    } else {
+        store.levels.insert(key, level);
+    }
+    save(&store);
+}
+
+/// Apply `level` to the document; Blitz restyles and relays out on the next frame.
+pub fn apply(doc: &mut BaseDocument, level: f32) {
+    doc.viewport_mut().set_zoom(level);
+}
+
+pub fn current(doc: &BaseDocument) -> f32 {
+    doc.viewport().zoom()
+}
@@ -0,0 +1,108 @@
+/// `data-frontier-action` elements report to the host instead of navigating.
+use frontier_blitz_bridge::driver::{HostEvent, TestDriver};
+use frontier_blitz_bridge::test_host;
+use winit::keyboard::KeyCode;
+
+const PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+        <body style="margin: 0;">
+            <button id="increment" data-frontier-action="increment" data-step-size="2"
+                style="display: block; width: 120px; height: 30px;">+</button>
+            <a id="link-action" href="/elsewhere" data-frontier-action="open"
+                style="display: block; width: 120px; height: 30px;">Open</a>
+            <form id="todo" data-frontier-action="add-todo" action="/submit">
+                <input id="title" name="title" value="" style="display: block; width: 200px; height: 24px;" />
+                <input id="done" type="checkbox" name="done" value="yes" checked />
+                <button id="save" style="display: block; width: 120px; height: 30px;">Save</button>
+            </form>
+            <input id="search" data-frontier-action="search" style="display: block; width: 200px; height: 24px;" />
+        </body>
+    </html>
+"#;
+
+fn driver() -> TestDriver {
+    test_host::reset();
+    TestDriver::new(PAGE, "https://actions.example/").unwrap()
+}
+
+fn actions(driver: &TestDriver) -> Vec<HostEvent> {
+    driver
+        .host_events()
+        .into_iter()
+        .filter(|event| event.name == "frontier-action")
+        .collect()
+}
+
+#[test]
+fn click_reports_action_id_and_dataset() {
+    let mut driver = driver();
+    driver.click_selector("#increment");
+
+    let events = actions(&driver);
+    assert_eq!(events.len(), 1);
+    let payload = &events[0].payload;
+    assert_eq!(payload["action"], "increment");
+    assert_eq!(payload["event"], "click");
+    assert_eq!(payload["id"], "increment");
+    assert_eq!(payload["dataset"]["stepSize"], "2");
+}
+
+#[test]
+fn action_links_do_not_navigate() {
+    let mut driver = driver();
+    driver.click_selector("#link-action");
+
+    assert!(test_host::navigations().is_empty());
+    assert_eq!(driver.url(), "https://actions.example/");
+    assert_eq!(actions(&driver)[0].payload["action"], "open");
+}
+
+#[test]
+fn submit_button_sends_form_values() {
+    let mut driver = driver();
+    driver.click_selector("#title");
+    driver.type_text("milk");
+    driver.click_selector("#save");
+
+    assert!(test_host::navigations().is_empty());
+    let events = actions(&driver);
+    let submit = events.iter().find(|event| event.payload["event"] == "submit").unwrap();
+    assert_eq!(submit.payload["action"], "add-todo");
+    assert_eq!(submit.payload["form"]["title"], "milk");
+    assert_eq!(submit.payload["form"]["done"], "yes");
+}
+
+#[test]
+fn enter_in_action_form_submits_to_host() {
+    let mut driver = driver();
+    driver.click_selector("#title");
+    driver.type_text("eggs");
+    driver.press(KeyCode::Enter);
+
+    assert!(test_host::navigations().is_empty());
+    let events = actions(&driver);
+    assert_eq!(events.last().unwrap().payload["event"], "submit");
+    assert_eq!(events.last().unwrap().payload["form"]["title"], "eggs");
+}
+
+#[test]
+fn typing_reports_input_and_enter_reports_change() {
+    let mut driver = driver();
+    driver.click_selector("#search");
+    driver.type_text("ab");
+    driver.press(KeyCode::Enter);
+
+    let events: Vec<_> = actions(&driver)
+        .into_iter()
+        .map(|event| (event.payload["event"].clone(), event.payload["value"].clone()))
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            ("input".into(), "a".into()),
+            ("input".into(), "ab".into()),
+            ("change".into(), "ab".into()),
+        ]
+    );
+}
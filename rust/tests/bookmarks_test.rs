@@ -0,0 +1,180 @@
+/// The bookmarks store, Ctrl+D and Netscape bookmark file import/export.
+use frontier_blitz_bridge::bookmarks::{self, BookmarkError, ROOT};
+use frontier_blitz_bridge::driver::TestDriver;
+use frontier_blitz_bridge::test_host;
+use winit::keyboard::KeyCode;
+
+fn titles(folder: u64) -> Vec<String> {
+    bookmarks::children(folder)
+        .unwrap()
+        .into_iter()
+        .map(|bookmark| bookmark.title)
+        .collect()
+}
+
+#[test]
+fn ctrl_d_bookmarks_the_current_page_once() {
+    test_host::reset();
+    let page = "<!DOCTYPE html><html><head><title>Team docs</title></head><body>Docs</body></html>";
+    let mut driver = TestDriver::new(page, "https://docs.ctrl-d.example/").unwrap();
+
+    driver.press_mod(KeyCode::KeyD);
+    driver.press_mod(KeyCode::KeyD);
+
+    let found = bookmarks::search("ctrl-d.example", 10);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].title, "Team docs");
+    assert_eq!(found[0].parent, ROOT);
+
+    let added: Vec<_> = driver
+        .host_events()
+        .into_iter()
+        .filter(|event| event.name == "bookmark-added")
+        .collect();
+    assert_eq!(added.len(), 2);
+    assert_eq!(added[0].payload["url"], "https://docs.ctrl-d.example/");
+    assert_eq!(added[0].payload["existing"], false);
+    assert_eq!(added[1].payload["existing"], true);
+}
+
+#[test]
+fn items_move_between_folders_in_order() {
+    let work = bookmarks::add_folder(ROOT, "Work").unwrap();
+    let archive = bookmarks::add_folder(work, "Archive").unwrap();
+    let a = bookmarks::add(work, "A", "https://a.move.example/", &[]).unwrap();
+    bookmarks::add(work, "B", "https://b.move.example/", &[]).unwrap();
+
+    bookmarks::move_to(a, work, Some(0)).unwrap();
+    assert_eq!(titles(work), ["A", "Archive", "B"]);
+
+    bookmarks::move_to(a, archive, None).unwrap();
+    assert_eq!(titles(work), ["Archive", "B"]);
+    assert_eq!(titles(archive), ["A"]);
+
+    assert_eq!(bookmarks::move_to(work, archive, None), Err(BookmarkError::Cycle(work)));
+    assert_eq!(bookmarks::move_to(work, a, None), Err(BookmarkError::NotAFolder(a)));
+}
+
+#[test]
+fn removing_a_folder_removes_its_contents() {
+    let folder = bookmarks::add_folder(ROOT, "Old").unwrap();
+    let inner = bookmarks::add_folder(folder, "Older").unwrap();
+    let link = bookmarks::add(inner, "Gone", "https://gone.remove.example/", &[]).unwrap();
+
+    assert_eq!(bookmarks::remove(folder), Ok(3));
+
+    assert!(bookmarks::get(link).is_none());
+    assert_eq!(bookmarks::remove(folder), Err(BookmarkError::NotFound(folder)));
+}
+
+#[test]
+fn search_matches_words_and_exact_tags() {
+    let tags = bookmarks::parse_tags("Infra, oncall ,infra");
+    assert_eq!(tags, ["infra", "oncall"]);
+    bookmarks::add(ROOT, "Runbook", "https://wiki.search.example/runbook", &tags).unwrap();
+    bookmarks::add(ROOT, "Infrastructure map", "https://wiki.search.example/map", &[]).unwrap();
+
+    let titles = |query: &str| -> Vec<String> {
+        bookmarks::search(query, 10).into_iter().map(|bookmark| bookmark.title).collect()
+    };
+    assert_eq!(titles("search.example infra"), ["Runbook", "Infrastructure map"]);
+    assert_eq!(titles("search.example #infra"), ["Runbook"]);
+    assert!(titles("search.example #infr").is_empty());
+}
+
+#[test]
+fn store_is_written_to_the_profile() {
+    let path = test_host::use_temp_profile().join("profile").join(bookmarks::FILE_NAME);
+
+    bookmarks::add(ROOT, "On disk", "https://disk.example/", &[]).unwrap();
+
+    let on_disk = std::fs::read_to_string(path).unwrap();
+    assert!(on_disk.contains("https://disk.example/"));
+}
+
+#[test]
+fn netscape_files_import_with_their_folders() {
+    let target = bookmarks::add_folder(ROOT, "Imported").unwrap();
+    let file = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks Menu</H1>
+<DL><p>
+    <DT><H3 ADD_DATE="1700000000">Docs &amp; guides</H3>
+    <DL><p>
+        <DT><A HREF="https://docs.import.example/?a=1&amp;b=2" ADD_DATE="1700000001" TAGS="docs,internal">Internal docs</A>
+    </DL><p>
+    <DT><A HREF="https://status.import.example/">Status</A>
+    <DT><H3>Empty</H3>
+    <DL><p>
+    </DL><p>
+</DL><p>
+"#;
+
+    assert_eq!(bookmarks::import_html(file, target), Ok(2));
+
+    assert_eq!(titles(target), ["Docs & guides", "Status", "Empty"]);
+    let folder = bookmarks::children(target).unwrap()[0].id;
+    let docs = &bookmarks::children(folder).unwrap()[0];
+    assert_eq!(docs.url.as_deref(), Some("https://docs.import.example/?a=1&b=2"));
+    assert_eq!(docs.tags, ["docs", "internal"]);
+    assert_eq!(docs.added, 1_700_000_001_000);
+}
+
+#[test]
+fn export_round_trips_through_import() {
+    let source = bookmarks::add_folder(ROOT, "Round trip <source>").unwrap();
+    let nested = bookmarks::add_folder(source, "Nested").unwrap();
+    bookmarks::add(nested, "Quote \"this\"", "https://round.example/?q=a&b", &["x".to_string()]).unwrap();
+
+    let exported = bookmarks::export_html();
+    assert!(exported.starts_with("<!DOCTYPE NETSCAPE-Bookmark-file-1>"));
+    let copy = bookmarks::add_folder(ROOT, "Copy").unwrap();
+    bookmarks::import_html(&exported, copy).unwrap();
+
+    let copied = bookmarks::children(copy)
+        .unwrap()
+        .into_iter()
+        .find(|folder| folder.title == "Round trip <source>")
+        .unwrap();
+    let nested = &bookmarks::children(copied.id).unwrap()[0];
+    let link = &bookmarks::children(nested.id).unwrap()[0];
+    assert_eq!(nested.title, "Nested");
+    assert_eq!(link.title, "Quote \"this\"");
+    assert_eq!(link.url.as_deref(), Some("https://round.example/?q=a&b"));
+    assert_eq!(link.tags, ["x"]);
+}
+
+#[test]
+fn numeric_entities_are_decoded_on_import() {
+    let target = bookmarks::add_folder(ROOT, "Entities").unwrap();
+    let file = r#"<DL><p>
+    <DT><H3>Caf&#233; &#x2013; notes</H3>
+    <DL><p>
+        <DT><A HREF="https://entities.example/?a=1&#38;b=2">It&#39;s &#x1F600; &bogus; &#xZZ;</A>
+    </DL><p>
+</DL><p>
+"#;
+
+    assert_eq!(bookmarks::import_html(file, target), Ok(1));
+
+    assert_eq!(titles(target), ["Café – notes"]);
+    let folder = bookmarks::children(target).unwrap()[0].id;
+    let link = &bookmarks::children(folder).unwrap()[0];
+    assert_eq!(link.title, "It's 😀 &bogus; &#xZZ;");
+    assert_eq!(link.url.as_deref(), Some("https://entities.example/?a=1&b=2"));
+}
+
+#[test]
+fn tags_can_be_replaced_through_the_ffi() {
+    let id = bookmarks::add(ROOT, "Tagged", "https://tagged.example/", &["old".to_string()]).unwrap();
+
+    let tags = "Work, reading";
+    assert!(frontier_blitz_bridge::frontier_blitz_bookmark_set_tags(id, tags.as_ptr(), tags.len()));
+    assert_eq!(bookmarks::get(id).unwrap().tags, ["work", "reading"]);
+
+    assert!(frontier_blitz_bridge::frontier_blitz_bookmark_set_tags(id, std::ptr::null(), 0));
+    assert!(bookmarks::get(id).unwrap().tags.is_empty());
+
+    assert!(!frontier_blitz_bridge::frontier_blitz_bookmark_set_tags(u64::MAX, tags.as_ptr(), tags.len()));
+}
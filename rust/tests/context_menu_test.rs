@@ -0,0 +1,141 @@
+/// The right-click context menu, driven through the same mouse handling as
+/// a native window: placement, built-in items and host-registered items.
+use frontier_blitz_bridge::driver::{Input, TestDriver};
+use frontier_blitz_bridge::test_host;
+use winit::event::MouseButton;
+
+const PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+        <head><title>Menu</title></head>
+        <body style="margin: 0; font: 16px sans-serif;">
+            <a id="docs" href="/docs" style="display: block; width: 200px; height: 40px;">Docs</a>
+        </body>
+    </html>
+"#;
+
+const URL: &str = "https://menu.example/";
+
+const MENU: &str = r#"[data-frontier-overlay="context-menu"]"#;
+
+fn driver() -> TestDriver {
+    test_host::reset();
+    test_host::serve(URL, PAGE);
+    TestDriver::new(PAGE, URL).unwrap()
+}
+
+fn right_click(driver: &mut TestDriver, x: f64, y: f64) {
+    driver.send(Input::MouseMove { x, y });
+    driver.send(Input::MouseDown { x, y, button: MouseButton::Right });
+    driver.send(Input::MouseUp { x, y, button: MouseButton::Right });
+}
+
+fn item(action: &str) -> String {
+    format!(r#"[data-frontier-menu-action="{action}"]"#)
+}
+
+/// The menu's left, top, right and bottom edges in CSS pixels.
+fn menu_bounds(driver: &TestDriver) -> (f64, f64, f64, f64) {
+    let node_id = driver.query_selector(MENU).unwrap();
+    let node = driver.document().get_node(node_id).unwrap();
+    let position = node.absolute_position(0.0, 0.0);
+    let size = node.final_layout.size;
+    (
+        position.x as f64,
+        position.y as f64,
+        (position.x + size.width) as f64,
+        (position.y + size.height) as f64,
+    )
+}
+
+#[test]
+fn menu_opens_at_the_cursor() {
+    let mut driver = driver();
+    right_click(&mut driver, 300.0, 100.0);
+
+    let (left, top, ..) = menu_bounds(&driver);
+    assert_eq!((left, top), (300.0, 100.0));
+}
+
+#[test]
+fn menu_near_the_corner_stays_inside_the_viewport() {
+    let mut driver = driver();
+    right_click(&mut driver, 790.0, 590.0);
+
+    let (left, top, right, bottom) = menu_bounds(&driver);
+    assert!(left >= 0.0 && top >= 0.0, "menu starts at ({left}, {top})");
+    assert!(right <= 800.0 && bottom <= 600.0, "menu ends at ({right}, {bottom})");
+    // It opens up and to the left of the cursor rather than sliding under it
+    assert!(right <= 790.0 && bottom <= 590.0);
+}
+
+#[test]
+fn picking_reload_reloads_the_page() {
+    let mut driver = driver();
+    right_click(&mut driver, 300.0, 100.0);
+
+    assert!(driver.click_selector(&item("reload")));
+
+    assert!(driver.query_selector(MENU).is_none());
+    assert_eq!(test_host::navigations(), [URL]);
+    assert_eq!(driver.url(), URL);
+}
+
+#[test]
+fn copying_a_link_address_goes_to_the_host() {
+    let mut driver = driver();
+    right_click(&mut driver, 20.0, 20.0);
+
+    assert!(driver.click_selector(&item("copy-link")));
+
+    let copied: Vec<_> = driver
+        .host_events()
+        .into_iter()
+        .filter(|event| event.name == "copy-to-clipboard")
+        .map(|event| event.payload["text"].clone())
+        .collect();
+    assert_eq!(copied, [serde_json::json!("https://menu.example/docs")]);
+    // Picking the item doesn't also follow the link underneath
+    assert_eq!(driver.url(), URL);
+}
+
+#[test]
+fn host_items_report_their_id() {
+    let (id, label) = ("menu-test-share", "Share");
+    assert!(frontier_blitz_bridge::frontier_blitz_register_context_menu_item(
+        id.as_ptr(),
+        id.len(),
+        label.as_ptr(),
+        label.len(),
+        2,
+    ));
+    let mut driver = driver();
+
+    // Only links get the item
+    right_click(&mut driver, 300.0, 100.0);
+    assert!(driver.query_selector(&item("host:menu-test-share")).is_none());
+    right_click(&mut driver, 20.0, 20.0);
+    assert!(driver.click_selector(&item("host:menu-test-share")));
+
+    let actions: Vec<_> = driver
+        .host_events()
+        .into_iter()
+        .filter(|event| event.name == "context-menu-action")
+        .collect();
+    assert!(frontier_blitz_bridge::frontier_blitz_unregister_context_menu_item(id.as_ptr(), id.len()));
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].payload["id"], "menu-test-share");
+    assert_eq!(actions[0].payload["target"]["link_url"], "https://menu.example/docs");
+}
+
+#[test]
+fn clicking_outside_closes_the_menu_without_clicking_the_page() {
+    let mut driver = driver();
+    right_click(&mut driver, 300.0, 100.0);
+
+    driver.click(20.0, 20.0);
+
+    assert!(driver.query_selector(MENU).is_none());
+    assert_eq!(driver.url(), URL);
+    assert!(test_host::navigations().is_empty());
+}
@@ -0,0 +1,73 @@
+/// Host-driven DOM edits through the same code the `frontier_blitz_set_*` FFI uses.
+use frontier_blitz_bridge::dom_edit::{self, Edit};
+use frontier_blitz_bridge::driver::TestDriver;
+
+const PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+        <body>
+            <h1 id="title" class="big">Counter</h1>
+            <span class="count">0</span>
+            <span class="count">0</span>
+            <div id="list"></div>
+        </body>
+    </html>
+"#;
+
+fn driver() -> TestDriver {
+    TestDriver::new(PAGE, "https://edit.example/").unwrap()
+}
+
+#[test]
+fn set_text_replaces_content_of_every_match() {
+    let mut driver = driver();
+    assert_eq!(driver.edit(".count", &Edit::SetText("5".into())), Ok(2));
+    assert_eq!(dom_edit::query_text(driver.document(), ".count").unwrap().as_deref(), Some("5"));
+}
+
+#[test]
+fn query_text_returns_none_without_match() {
+    let driver = driver();
+    assert_eq!(dom_edit::query_text(driver.document(), "#missing"), Ok(None));
+    assert_eq!(driver.text("#title").as_deref(), Some("Counter"));
+}
+
+#[test]
+fn attributes_can_be_set_and_removed() {
+    let mut driver = driver();
+    let edit = Edit::SetAttribute {
+        name: "data-state".into(),
+        value: "ready".into(),
+    };
+    assert_eq!(driver.edit("#title", &edit), Ok(1));
+    assert!(driver.query_selector("[data-state=ready]").is_some());
+
+    assert_eq!(driver.edit("#title", &Edit::RemoveAttribute("data-state".into())), Ok(1));
+    assert!(driver.query_selector("[data-state]").is_none());
+}
+
+#[test]
+fn classes_are_added_once_and_removed() {
+    let mut driver = driver();
+    driver.edit("#title", &Edit::AddClass("active".into())).unwrap();
+    driver.edit("#title", &Edit::AddClass("active".into())).unwrap();
+    assert!(driver.query_selector("h1.big.active").is_some());
+
+    driver.edit("#title", &Edit::RemoveClass("big".into())).unwrap();
+    assert!(driver.query_selector("h1.big").is_none());
+    assert!(driver.query_selector("h1.active").is_some());
+}
+
+#[test]
+fn inner_html_is_parsed_into_elements() {
+    let mut driver = driver();
+    let edit = Edit::SetInnerHtml("<ul><li>one</li><li id=\"two\">two</li></ul>".into());
+    assert_eq!(driver.edit("#list", &edit), Ok(1));
+    assert_eq!(driver.text("#two").as_deref(), Some("two"));
+}
+
+#[test]
+fn invalid_selector_is_an_error() {
+    let mut driver = driver();
+    assert!(driver.edit("[[", &Edit::SetText("x".into())).is_err());
+}
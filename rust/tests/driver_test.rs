@@ -0,0 +1,240 @@
+/// End-to-end tests driven by synthetic input through `TestDriver`.
+use frontier_blitz_bridge::driver::{Input, TestDriver};
+use frontier_blitz_bridge::test_host;
+use winit::event::MouseButton;
+use winit::keyboard::{KeyCode, ModifiersState};
+
+const PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+        <head><title>Driver Test</title></head>
+        <body style="margin: 0; font: 16px sans-serif;">
+            <a id="docs" href="/docs" style="display: block; width: 200px; height: 40px;">Docs</a>
+            <p id="text">apple banana apple cherry apple</p>
+        </body>
+    </html>
+"#;
+
+const URL: &str = "https://example.com/";
+
+#[test]
+fn cmd_k_shows_command_palette() {
+    let mut driver = TestDriver::new(PAGE, URL).unwrap();
+    driver.press_mod(KeyCode::KeyK);
+
+    assert!(driver.palette().is_some());
+    // Drawn over the page rather than replacing it
+    assert_eq!(driver.url(), URL);
+    assert!(driver.query_selector("#docs").is_some());
+}
+
+#[test]
+fn k_without_modifier_is_not_a_shortcut() {
+    let mut driver = TestDriver::new(PAGE, URL).unwrap();
+    driver.type_text("k");
+
+    assert_eq!(driver.url(), URL);
+}
+
+#[test]
+fn find_bar_counts_typed_query_and_closes_on_escape() {
+    let mut driver = TestDriver::new(PAGE, URL).unwrap();
+    driver.press_mod(KeyCode::KeyF);
+    driver.type_text("apple");
+    assert_eq!(driver.find_match_count(), Some(3));
+
+    driver.press(KeyCode::Backspace);
+    driver.type_text("e");
+    assert_eq!(driver.find_match_count(), Some(3));
+
+    driver.press(KeyCode::Escape);
+    assert_eq!(driver.find_match_count(), None);
+    assert_eq!(driver.text("#text").unwrap(), "apple banana apple cherry apple");
+}
+
+#[test]
+fn keyboard_zoom_steps_and_resets() {
+    test_host::use_temp_profile();
+    let mut driver = TestDriver::new(PAGE, "https://zoom.example/").unwrap();
+    driver.press_mod(KeyCode::Equal);
+    assert_eq!(driver.zoom(), 1.1);
+
+    driver.press_mod(KeyCode::Digit0);
+    assert_eq!(driver.zoom(), 1.0);
+}
+
+#[test]
+fn zoom_levels_follow_the_profile_directory() {
+    let profile = test_host::use_temp_profile();
+    let mut driver = TestDriver::new(PAGE, "https://profile.zoom.example/").unwrap();
+    driver.press_mod(KeyCode::Equal);
+    assert_eq!(driver.zoom(), 1.1);
+
+    let other = profile.join("other-profile").to_string_lossy().into_owned();
+    assert!(frontier_blitz_bridge::frontier_blitz_set_profile_dir(other.as_ptr(), other.len()));
+    let driver = TestDriver::new(PAGE, "https://profile.zoom.example/").unwrap();
+    assert_eq!(driver.zoom(), 1.0);
+
+    test_host::use_temp_profile();
+    let driver = TestDriver::new(PAGE, "https://profile.zoom.example/").unwrap();
+    assert_eq!(driver.zoom(), 1.1);
+}
+
+#[test]
+fn disabling_the_status_bar_removes_it() {
+    let status = r#"[data-frontier-overlay="status"]"#;
+    let mut driver = TestDriver::new(PAGE, URL).unwrap();
+    driver.send(Input::MouseMove { x: 20.0, y: 20.0 });
+    assert!(driver.query_selector(status).is_some());
+
+    frontier_blitz_bridge::frontier_blitz_set_status_bar_enabled(false);
+    driver.send(Input::MouseMove { x: 21.0, y: 20.0 });
+
+    assert!(driver.query_selector(status).is_none());
+    assert_eq!(driver.hovered_link(), Some("https://example.com/docs"));
+    frontier_blitz_bridge::frontier_blitz_set_status_bar_enabled(true);
+}
+
+#[test]
+fn hovering_a_link_reports_it_to_the_host() {
+    let mut driver = TestDriver::new(PAGE, URL).unwrap();
+    driver.send(Input::MouseMove { x: 20.0, y: 20.0 });
+    assert_eq!(driver.hovered_link(), Some("https://example.com/docs"));
+
+    driver.send(Input::MouseMove { x: 700.0, y: 500.0 });
+    assert_eq!(driver.hovered_link(), None);
+
+    let hovered: Vec<_> = driver
+        .host_events()
+        .into_iter()
+        .filter(|event| event.name == "link-hovered")
+        .map(|event| event.payload["url"].clone())
+        .collect();
+    assert_eq!(hovered, vec![serde_json::json!("https://example.com/docs"), serde_json::Value::Null]);
+}
+
+#[test]
+fn right_click_does_not_follow_links() {
+    let mut driver = TestDriver::new(PAGE, URL).unwrap();
+    driver.send(Input::MouseDown { x: 20.0, y: 20.0, button: MouseButton::Right });
+    driver.send(Input::MouseUp { x: 20.0, y: 20.0, button: MouseButton::Right });
+
+    assert_eq!(driver.url(), URL);
+    assert_eq!(driver.history(), vec![URL.to_string()]);
+}
+
+#[test]
+fn right_click_opens_context_menu_and_escape_closes_it() {
+    let mut driver = TestDriver::new(PAGE, URL).unwrap();
+    driver.send(Input::MouseDown { x: 20.0, y: 20.0, button: MouseButton::Right });
+    driver.send(Input::MouseUp { x: 20.0, y: 20.0, button: MouseButton::Right });
+    assert!(driver.query_selector(r#"[data-frontier-overlay="context-menu"]"#).is_some());
+
+    // Keys go to the menu rather than the page
+    driver.press(KeyCode::Escape);
+    assert!(driver.query_selector(r#"[data-frontier-overlay="context-menu"]"#).is_none());
+    assert_eq!(driver.url(), URL);
+}
+
+#[test]
+fn blank_target_link_opens_a_tab_instead_of_navigating() {
+    test_host::use_temp_profile();
+    test_host::serve("https://example.com/new", "<title>New</title>");
+    let page = r#"<body style="margin: 0"><a id="new" href="/new" target="_blank" style="display: block; height: 40px">New</a></body>"#;
+    let mut driver = TestDriver::new(page, URL).unwrap();
+    driver.click_selector("#new");
+
+    let opened: Vec<_> = driver
+        .host_events()
+        .into_iter()
+        .filter(|event| event.name == "window-opened")
+        .collect();
+    assert_eq!(opened.len(), 1);
+    assert_eq!(opened[0].payload["url"], "https://example.com/new");
+    assert_eq!(driver.url(), "https://example.com/new");
+    assert_eq!(driver.session().windows[0].tabs.len(), 2);
+}
+
+#[test]
+fn dom_edits_from_a_host_event_callback_apply_after_it_returns() {
+    test_host::reset();
+    let mut driver = TestDriver::new(PAGE, URL).unwrap();
+
+    let returned = std::rc::Rc::new(std::cell::Cell::new(None));
+    let seen = returned.clone();
+    test_host::on_event("link-hovered", move |_| {
+        let (selector, text) = ("#text", "hovered");
+        seen.set(Some(frontier_blitz_bridge::frontier_blitz_set_text(
+            0,
+            selector.as_ptr(),
+            selector.len(),
+            text.as_ptr(),
+            text.len(),
+        )));
+    });
+    driver.send(Input::MouseMove { x: 20.0, y: 20.0 });
+
+    // Queued rather than refused, since the callback runs on the loop thread
+    assert_eq!(returned.get(), Some(0));
+    assert_eq!(driver.text("#text").unwrap(), "hovered");
+}
+
+fn opened(driver: &TestDriver) -> Vec<serde_json::Value> {
+    driver
+        .host_events()
+        .into_iter()
+        .filter(|event| event.name == "window-opened")
+        .map(|event| event.payload)
+        .collect()
+}
+
+#[test]
+fn middle_click_opens_link_in_a_background_tab() {
+    test_host::serve("https://example.com/docs", "<title>Docs</title>");
+    let mut driver = TestDriver::new(PAGE, URL).unwrap();
+    driver.send(Input::MouseDown { x: 20.0, y: 20.0, button: MouseButton::Middle });
+    driver.send(Input::MouseUp { x: 20.0, y: 20.0, button: MouseButton::Middle });
+
+    let opened = opened(&driver);
+    assert_eq!(opened.len(), 1);
+    assert_eq!(opened[0]["url"], "https://example.com/docs");
+    assert_eq!(opened[0]["opener_window_id"], 0);
+    assert_eq!(opened[0]["opener_tab_index"], 0);
+    assert_eq!(driver.url(), URL);
+    assert_eq!(driver.session().windows[0].tabs.len(), 2);
+}
+
+#[test]
+fn only_the_keymaps_mod_key_opens_links_in_the_background() {
+    test_host::serve("https://example.com/docs", "<title>Docs</title>");
+    let mut driver = TestDriver::new(PAGE, URL).unwrap();
+    let other = if frontier_blitz_bridge::keymap::mod_modifier() == ModifiersState::CONTROL {
+        ModifiersState::SUPER
+    } else {
+        ModifiersState::CONTROL
+    };
+
+    driver.send(Input::Modifiers(frontier_blitz_bridge::keymap::mod_modifier()));
+    driver.click(20.0, 20.0);
+    assert_eq!(opened(&driver).len(), 1);
+    assert_eq!(driver.url(), URL);
+
+    // Any other modifier is an ordinary click
+    driver.send(Input::Modifiers(other));
+    driver.click(20.0, 20.0);
+    assert_eq!(opened(&driver).len(), 1);
+    assert_eq!(driver.url(), "https://example.com/docs");
+}
+
+#[test]
+fn forms_targeting_blank_open_a_tab() {
+    test_host::serve("https://example.com/search?q=apple", "<title>Results</title>");
+    let page = r#"<body style="margin: 0"><form action="/search" target="_blank"><input name="q" value="apple"><button id="go">Go</button></form></body>"#;
+    let mut driver = TestDriver::new(page, URL).unwrap();
+    driver.click_selector("#go");
+
+    let opened = opened(&driver);
+    assert_eq!(opened.len(), 1);
+    assert_eq!(opened[0]["url"], "https://example.com/search?q=apple");
+    assert_eq!(driver.session().windows[0].tabs[0].history, vec![URL.to_string()]);
+}
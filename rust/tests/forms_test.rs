@@ -0,0 +1,108 @@
+/// Form state read-back and pre-fill, as used by `frontier_blitz_get_form_values`
+/// and `frontier_blitz_set_form_values`.
+use frontier_blitz_bridge::driver::TestDriver;
+use serde_json::{json, Map, Value};
+
+const PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+        <body>
+            <input id="search" type="text" value="top">
+            <form id="bookmark">
+                <input name="title" type="text" value="Frontier">
+                <textarea name="notes">Some notes</textarea>
+                <input name="pinned" type="checkbox" checked>
+                <input name="tags" type="checkbox" value="work" checked>
+                <input name="tags" type="checkbox" value="home">
+                <input name="folder" type="radio" value="bar">
+                <input name="folder" type="radio" value="menu" checked>
+                <select name="color">
+                    <option value="red">Red</option>
+                    <option value="blue" selected>Blue</option>
+                </select>
+                <button type="submit">Save</button>
+            </form>
+        </body>
+    </html>
+"#;
+
+fn driver() -> TestDriver {
+    TestDriver::new(PAGE, "https://forms.example/").unwrap()
+}
+
+fn object(value: Value) -> Map<String, Value> {
+    value.as_object().unwrap().clone()
+}
+
+#[test]
+fn reads_every_kind_of_control_in_a_form() {
+    let driver = driver();
+
+    let values = driver.form_values(Some("#bookmark")).unwrap().unwrap();
+
+    assert_eq!(
+        Value::Object(values),
+        json!({
+            "title": "Frontier",
+            "notes": "Some notes",
+            "pinned": true,
+            "tags": ["work"],
+            "folder": "menu",
+            "color": "blue",
+        })
+    );
+}
+
+#[test]
+fn whole_document_includes_controls_outside_forms() {
+    let driver = driver();
+
+    let values = driver.form_values(None).unwrap().unwrap();
+
+    assert_eq!(values.get("search"), Some(&json!("top")));
+    assert!(values.contains_key("title"));
+}
+
+#[test]
+fn typed_text_is_read_back() {
+    let mut driver = driver();
+    driver.click_selector("#search");
+    driver.type_text("!");
+
+    let values = driver.form_values(None).unwrap().unwrap();
+
+    assert!(values["search"].as_str().unwrap().contains('!'));
+}
+
+#[test]
+fn unmatched_or_invalid_scope() {
+    let driver = driver();
+    assert_eq!(driver.form_values(Some("#missing")), Ok(None));
+    assert!(driver.form_values(Some("[")).is_err());
+}
+
+#[test]
+fn set_values_round_trips() {
+    let mut driver = driver();
+    let wanted = object(json!({
+        "title": "Edited",
+        "notes": "New notes",
+        "pinned": false,
+        "tags": ["home"],
+        "folder": "bar",
+        "color": "red",
+    }));
+
+    assert_eq!(driver.set_form_values(Some("#bookmark"), &wanted), Ok(Some(8)));
+
+    assert_eq!(driver.form_values(Some("#bookmark")).unwrap().unwrap(), wanted);
+}
+
+#[test]
+fn unknown_keys_are_ignored() {
+    let mut driver = driver();
+    let count = driver.set_form_values(None, &object(json!({ "nope": "x", "search": "bottom" })));
+
+    assert_eq!(count, Ok(Some(1)));
+    assert_eq!(driver.form_values(None).unwrap().unwrap()["search"], json!("bottom"));
+}
@@ -0,0 +1,148 @@
+/// The persistent history store and `about:history`.
+use frontier_blitz_bridge::driver::TestDriver;
+use frontier_blitz_bridge::history::{self, SearchMode, Transition};
+use frontier_blitz_bridge::test_host;
+use winit::keyboard::KeyCode;
+
+fn page(title: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html><html><head><title>{title}</title></head><body style="margin: 0;"><a id="next" href="/next" style="display: block; height: 40px;">Next</a></body></html>"#
+    )
+}
+
+/// A driver on `{origin}/` that has followed its link to `{origin}/next`.
+fn browsed(origin: &str) -> TestDriver {
+    test_host::reset();
+    test_host::use_temp_profile();
+    test_host::serve(&format!("{origin}/"), &page("Start page"));
+    test_host::serve(&format!("{origin}/next"), &page("Next &amp; last"));
+    let mut driver = TestDriver::new(&page("Start page"), &format!("{origin}/")).unwrap();
+    driver.click_selector("#next");
+    driver
+}
+
+fn entry(url: &str) -> Option<history::Entry> {
+    history::search(url, SearchMode::Prefix, 10)
+        .into_iter()
+        .find(|entry| entry.url == url)
+}
+
+#[test]
+fn link_navigation_is_recorded_with_its_title() {
+    browsed("https://record.example");
+
+    let entry = entry("https://record.example/next").unwrap();
+    assert_eq!(entry.title, "Next & last");
+    assert_eq!(entry.visit_count, 1);
+    assert_eq!(entry.last_transition, Transition::Link);
+}
+
+#[test]
+fn reload_and_back_are_counted_as_visits() {
+    let mut driver = browsed("https://revisit.example");
+    driver.press_mod(KeyCode::KeyR);
+    let next = entry("https://revisit.example/next").unwrap();
+    assert_eq!(next.visit_count, 2);
+    assert_eq!(next.last_transition, Transition::Reload);
+
+    driver.press_mod(KeyCode::BracketLeft);
+    let start = entry("https://revisit.example/").unwrap();
+    assert_eq!(start.last_transition, Transition::BackForward);
+}
+
+#[test]
+fn prefix_search_ignores_scheme_and_www() {
+    browsed("https://www.prefix.example");
+
+    let found = history::search("prefix.example/ne", SearchMode::Prefix, 10);
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].url, "https://www.prefix.example/next");
+}
+
+#[test]
+fn text_search_matches_every_word_in_title_or_url() {
+    browsed("https://fulltext.example");
+
+    let found = history::search("LAST fulltext", SearchMode::Text, 10);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].url, "https://fulltext.example/next");
+
+    assert!(history::search("last nowhere", SearchMode::Text, 10).is_empty());
+}
+
+#[test]
+fn time_range_deletion_removes_visits_from_disk() {
+    let before = history::now();
+    browsed("https://delete.example");
+    let path = test_host::use_temp_profile().join("profile").join(history::FILE_NAME);
+
+    let deleted = history::delete_range(before, i64::MAX);
+
+    assert!(deleted >= 1);
+    assert!(entry("https://delete.example/next").is_none());
+    let on_disk = std::fs::read_to_string(path).unwrap_or_default();
+    assert!(!on_disk.contains("https://delete.example/next"));
+}
+
+#[test]
+fn history_page_lists_matching_entries() {
+    let mut driver = browsed("https://page.example");
+
+    driver.load("about:history?q=page.example");
+
+    assert_eq!(driver.title().as_deref(), Some("History"));
+    assert!(driver.text("ul").unwrap().contains("Next & last"));
+    // Built-in pages aren't recorded
+    assert!(entry("about:history").is_none());
+}
+
+#[test]
+fn title_is_read_from_raw_html() {
+    assert_eq!(history::title_of("<HTML><Title id=x>\n  A &lt;b&gt;\n</TITLE>"), "A <b>");
+    assert_eq!(history::title_of("<p>No title</p>"), "");
+}
+
+#[test]
+fn only_the_history_page_can_clear_history() {
+    let mut driver = browsed("https://untrusted.example");
+
+    driver.load("frontier://history/clear");
+    assert!(entry("https://untrusted.example/next").is_some());
+    assert_eq!(driver.url(), "https://untrusted.example/next");
+
+    driver.load("about:history");
+    driver.click_selector("a.clear");
+    assert!(entry("https://untrusted.example/next").is_none());
+    assert_eq!(driver.url(), "about:history");
+}
+
+#[test]
+fn loading_keeps_only_the_newest_visits() {
+    let profile = test_host::use_temp_profile().join("long-profile");
+    std::fs::create_dir_all(&profile).unwrap();
+    let lines: String = (0..history::MAX_VISITS + 5)
+        .map(|index| {
+            let visit = history::Visit {
+                url: format!("https://long.example/{index}"),
+                title: String::new(),
+                time: index as i64,
+                transition: Transition::Link,
+            };
+            serde_json::to_string(&visit).unwrap() + "\n"
+        })
+        .collect();
+    std::fs::write(profile.join(history::FILE_NAME), lines).unwrap();
+
+    let dir = profile.to_string_lossy().into_owned();
+    assert!(frontier_blitz_bridge::frontier_blitz_set_profile_dir(dir.as_ptr(), dir.len()));
+    let kept = history::search("long.example", SearchMode::Prefix, usize::MAX);
+    test_host::use_temp_profile();
+
+    assert_eq!(kept.len(), history::MAX_VISITS);
+    assert_eq!(kept[0].url, format!("https://long.example/{}", history::MAX_VISITS + 4));
+    assert!(kept.iter().all(|entry| entry.url != "https://long.example/4"));
+    let on_disk = std::fs::read_to_string(profile.join(history::FILE_NAME)).unwrap();
+    assert_eq!(on_disk.lines().count(), history::MAX_VISITS);
+    assert!(on_disk.contains("\"https://long.example/5\""));
+}
@@ -0,0 +1,217 @@
+/// Multi-key sequences and key modes: pending sequences and their indicator,
+/// timeouts, per-mode bindings, and text fields getting plain keys.
+use std::path::PathBuf;
+use std::time::Duration;
+
+use frontier_blitz_bridge::driver::{Input, TestDriver};
+use frontier_blitz_bridge::{keymap, test_host};
+use winit::keyboard::{Key, KeyCode, ModifiersState};
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Sequences</title></head>
+<body style="margin: 0">
+  <input id="name" name="name">
+  <p id="text">needle</p>
+  <div style="height: 3000px">Tall</div>
+</body></html>"#;
+
+const CTRL: ModifiersState = ModifiersState::CONTROL;
+
+fn write_keymap(contents: &str) {
+    let path: PathBuf = test_host::use_temp_profile().join("profile").join(keymap::FILE_NAME);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
+    keymap::reload();
+}
+
+fn driver(url: &str) -> TestDriver {
+    TestDriver::new(PAGE, url).unwrap()
+}
+
+fn press_typed(driver: &mut TestDriver, mods: ModifiersState, code: KeyCode, text: &str) {
+    driver.send(Input::Modifiers(mods));
+    driver.send(Input::Key {
+        code,
+        key: Key::Character(text.into()),
+        text: None,
+    });
+    driver.send(Input::Modifiers(ModifiersState::empty()));
+}
+
+fn scroll_y(driver: &TestDriver) -> f64 {
+    driver.scroll().1
+}
+
+#[test]
+fn chord_sequences_wait_for_their_last_key() {
+    test_host::reset();
+    write_keymap(
+        r#"
+platform = "linux"
+
+[bindings]
+"Mod+F" = "none"
+"Ctrl+X Ctrl+F" = "find"
+"#,
+    );
+    let mut driver = driver("https://chords.sequences.example/");
+
+    driver.press_with(CTRL, KeyCode::KeyX);
+    assert_eq!(driver.find_match_count(), None);
+    assert_eq!(driver.key_indicator().as_deref(), Some("Ctrl+X"));
+
+    driver.press_with(CTRL, KeyCode::KeyF);
+    assert_eq!(driver.find_match_count(), Some(0));
+    assert_eq!(driver.key_indicator(), None);
+
+    driver.press(KeyCode::Escape);
+    driver.press_with(CTRL, KeyCode::KeyK);
+    let palette = driver.palette().unwrap();
+    let find = palette.items().iter().find(|item| item.title == "Find in Page").unwrap();
+    assert_eq!(find.detail, "Ctrl+X Ctrl+F");
+}
+
+#[test]
+fn plain_key_sequences_run_in_normal_mode() {
+    test_host::reset();
+    write_keymap(
+        r#"
+platform = "linux"
+
+[modes.normal]
+"g g" = "scroll-top"
+"Shift+G" = "scroll-bottom"
+"#,
+    );
+    let mut driver = driver("https://plain.sequences.example/");
+
+    press_typed(&mut driver, ModifiersState::SHIFT, KeyCode::KeyG, "G");
+    let bottom = scroll_y(&driver);
+    assert!(bottom > 0.0);
+
+    driver.type_text("g");
+    assert_eq!(scroll_y(&driver), bottom);
+    assert_eq!(driver.key_indicator().as_deref(), Some("G"));
+
+    driver.type_text("g");
+    assert_eq!(scroll_y(&driver), 0.0);
+    assert_eq!(driver.key_indicator(), None);
+}
+
+#[test]
+fn a_key_that_breaks_the_sequence_is_matched_on_its_own() {
+    test_host::reset();
+    write_keymap("platform = \"linux\"\n[modes.normal]\n\"g g\" = \"find\"\n");
+    let mut driver = driver("https://broken.sequences.example/");
+
+    driver.type_text("gx");
+    assert_eq!(driver.key_indicator(), None);
+    assert_eq!(driver.find_match_count(), None);
+
+    driver.type_text("g");
+    driver.press_with(CTRL, KeyCode::KeyK);
+    assert!(driver.palette().is_some());
+}
+
+#[test]
+fn pending_sequences_time_out() {
+    test_host::reset();
+    write_keymap(
+        r#"
+platform = "linux"
+timeout_ms = 20
+
+[modes.normal]
+"g g" = "find"
+"#,
+    );
+    let mut driver = driver("https://timeout.sequences.example/");
+
+    driver.type_text("g");
+    std::thread::sleep(Duration::from_millis(50));
+    driver.type_text("g");
+
+    // The second "g" started a new sequence
+    assert_eq!(driver.find_match_count(), None);
+    assert_eq!(driver.key_indicator().as_deref(), Some("G"));
+
+    driver.type_text("g");
+    assert_eq!(driver.find_match_count(), Some(0));
+}
+
+#[test]
+fn modes_switch_which_bindings_apply() {
+    test_host::reset();
+    write_keymap(
+        r#"
+platform = "linux"
+
+[modes.normal]
+"j" = "scroll-down"
+"i" = "mode:insert"
+
+[modes.insert]
+"Escape" = "mode:normal"
+"Mod+K" = "none"
+"#,
+    );
+    let mut driver = driver("https://modes.sequences.example/");
+    assert_eq!(driver.key_mode(), keymap::DEFAULT_MODE);
+
+    driver.type_text("j");
+    let scrolled = scroll_y(&driver);
+    assert!(scrolled > 0.0);
+
+    driver.type_text("i");
+    assert_eq!(driver.key_mode(), "insert");
+    assert_eq!(driver.key_indicator().as_deref(), Some("INSERT"));
+    driver.type_text("j");
+    driver.press_with(CTRL, KeyCode::KeyK);
+    assert_eq!(scroll_y(&driver), scrolled);
+    assert!(driver.palette().is_none());
+
+    driver.press(KeyCode::Escape);
+    assert_eq!(driver.key_mode(), "normal");
+    assert_eq!(driver.key_indicator(), None);
+}
+
+#[test]
+fn windows_start_in_the_mode_the_file_names() {
+    test_host::reset();
+    write_keymap("platform = \"linux\"\nmode = \"insert\"\n[modes.insert]\n\"Escape\" = \"mode:normal\"\n");
+    let mut driver = driver("https://start.sequences.example/");
+    assert_eq!(driver.key_mode(), "insert");
+    assert_eq!(driver.key_indicator(), None);
+
+    driver.press(KeyCode::Escape);
+    assert_eq!(driver.key_mode(), "normal");
+    assert_eq!(driver.key_indicator().as_deref(), Some("NORMAL"));
+}
+
+#[test]
+fn text_fields_get_plain_keys() {
+    test_host::reset();
+    write_keymap(
+        r#"
+platform = "linux"
+
+[modes.normal]
+"j" = "scroll-down"
+"g g" = "find"
+"#,
+    );
+    let mut driver = driver("https://fields.sequences.example/");
+    driver.click_selector("#name");
+
+    driver.type_text("jgg");
+
+    let values = driver.form_values(None).unwrap().unwrap();
+    assert_eq!(values["name"], "jgg");
+    assert_eq!(scroll_y(&driver), 0.0);
+    assert_eq!(driver.find_match_count(), None);
+    assert_eq!(driver.key_indicator(), None);
+
+    // Ctrl/Cmd chords still reach the keymap
+    driver.press_mod(KeyCode::KeyK);
+    assert!(driver.palette().is_some());
+}
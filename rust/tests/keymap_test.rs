@@ -0,0 +1,179 @@
+/// `keymap.toml`: remapping, what `Mod` means, matching by layout with a
+/// physical-key fallback, and hot reload.
+use std::path::PathBuf;
+
+use frontier_blitz_bridge::driver::{Input, TestDriver};
+use frontier_blitz_bridge::{keymap, test_host};
+use winit::keyboard::{Key, KeyCode, ModifiersState};
+
+const PAGE: &str = r#"<!DOCTYPE html><html><head><title>Keymap</title></head><body><p>Keys</p></body></html>"#;
+
+const CTRL: ModifiersState = ModifiersState::CONTROL;
+
+fn keymap_path() -> PathBuf {
+    test_host::use_temp_profile().join("profile").join(keymap::FILE_NAME)
+}
+
+/// Write the keymap and have the next lookup read it straight away.
+fn write_keymap(contents: &str) {
+    let path = keymap_path();
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
+    keymap::reload();
+}
+
+fn driver(url: &str) -> TestDriver {
+    TestDriver::new(PAGE, url).unwrap()
+}
+
+/// Press `code`, which types `text` on the layout being simulated.
+fn press_typed(driver: &mut TestDriver, mods: ModifiersState, code: KeyCode, text: &str) {
+    driver.send(Input::Modifiers(mods));
+    driver.send(Input::Key {
+        code,
+        key: Key::Character(text.into()),
+        text: None,
+    });
+    driver.send(Input::Modifiers(ModifiersState::empty()));
+}
+
+#[test]
+fn defaults_match_mod_exactly() {
+    test_host::reset();
+    let _ = std::fs::remove_file(keymap_path());
+    let mut driver = driver("https://defaults.keymap.example/");
+    let other = if keymap::mod_modifier() == CTRL {
+        ModifiersState::SUPER
+    } else {
+        CTRL
+    };
+
+    driver.press_with(other, KeyCode::KeyK);
+    assert!(driver.palette().is_none());
+
+    driver.press_mod(KeyCode::KeyK);
+    assert!(driver.palette().is_some());
+}
+
+#[test]
+fn file_bindings_replace_and_remove_defaults() {
+    test_host::reset();
+    write_keymap(
+        r#"
+platform = "linux"
+
+[bindings]
+"Mod+Shift+P" = "command-palette"
+"Mod+K" = "none"
+"Ctrl+Alt+R" = "reload"
+"#,
+    );
+    let mut driver = driver("https://remap.keymap.example/");
+
+    driver.press_with(CTRL, KeyCode::KeyK);
+    assert!(driver.palette().is_none());
+
+    driver.press_with(CTRL | ModifiersState::SHIFT, KeyCode::KeyP);
+    let palette = driver.palette().unwrap();
+    let reload = palette.items().iter().find(|item| item.title == "Reload").unwrap();
+    assert_eq!(reload.detail, "Ctrl+R");
+    let find = palette.items().iter().find(|item| item.title == "Find in Page").unwrap();
+    assert_eq!(find.detail, "Ctrl+F");
+}
+
+#[test]
+fn platform_decides_what_mod_means() {
+    test_host::reset();
+    write_keymap("platform = \"macos\"\n");
+    let mut driver = driver("https://platform.keymap.example/");
+    assert_eq!(keymap::mod_modifier(), ModifiersState::SUPER);
+
+    driver.press_with(CTRL, KeyCode::KeyK);
+    assert!(driver.palette().is_none());
+    driver.press_with(ModifiersState::SUPER, KeyCode::KeyK);
+    assert!(driver.palette().is_some());
+
+    write_keymap("platform = \"windows\"\n");
+    assert_eq!(keymap::mod_modifier(), CTRL);
+}
+
+#[test]
+fn bindings_follow_the_layout() {
+    test_host::reset();
+    write_keymap("platform = \"linux\"\n");
+    let mut driver = driver("https://dvorak.keymap.example/");
+
+    // On Dvorak the key labelled K on a US keyboard types "t"
+    press_typed(&mut driver, CTRL, KeyCode::KeyK, "t");
+    assert!(driver.palette().is_none());
+
+    press_typed(&mut driver, CTRL, KeyCode::KeyV, "k");
+    assert!(driver.palette().is_some());
+}
+
+#[test]
+fn non_latin_layouts_fall_back_to_the_physical_key() {
+    test_host::reset();
+    write_keymap("platform = \"linux\"\n");
+    let mut driver = driver("https://cyrillic.keymap.example/");
+
+    press_typed(&mut driver, CTRL, KeyCode::KeyK, "л");
+
+    assert!(driver.palette().is_some());
+}
+
+#[test]
+fn shifted_symbols_match_without_shift_in_the_binding() {
+    test_host::reset();
+    write_keymap("platform = \"linux\"\n");
+    let mut driver = driver("https://zoom.keymap.example/");
+
+    press_typed(&mut driver, CTRL | ModifiersState::SHIFT, KeyCode::Equal, "+");
+
+    assert_eq!(driver.zoom(), 1.1);
+}
+
+#[test]
+fn edits_apply_once_the_file_is_checked_again() {
+    test_host::reset();
+    write_keymap("platform = \"linux\"\n[bindings]\n\"Mod+J\" = \"find\"\n");
+    let mut driver = driver("https://reload.keymap.example/");
+
+    driver.press_with(CTRL, KeyCode::KeyJ);
+    assert_eq!(driver.find_match_count(), Some(0));
+    driver.press(KeyCode::Escape);
+
+    std::fs::write(keymap_path(), "platform = \"linux\"\n[bindings]\n\"Mod+J\" = \"command-palette\"\n").unwrap();
+    std::thread::sleep(keymap::CHECK_INTERVAL);
+    driver.press_with(CTRL, KeyCode::KeyJ);
+
+    assert!(driver.palette().is_some());
+    assert_eq!(driver.find_match_count(), None);
+}
+
+#[test]
+fn bad_entries_are_skipped() {
+    test_host::reset();
+    write_keymap(
+        r#"
+platform = "linux"
+
+[bindings]
+"Mod+Nope" = "find"
+"Mod+J" = "fly"
+"Mod+E" = "find"
+"#,
+    );
+    let mut driver = driver("https://bad.keymap.example/");
+
+    driver.press_with(CTRL, KeyCode::KeyJ);
+    assert_eq!(driver.find_match_count(), None);
+    driver.press_with(CTRL, KeyCode::KeyE);
+    assert_eq!(driver.find_match_count(), Some(0));
+
+    // A file that doesn't parse leaves the defaults
+    write_keymap("platform = [");
+    driver.press(KeyCode::Escape);
+    driver.press_mod(KeyCode::KeyK);
+    assert!(driver.palette().is_some());
+}
@@ -0,0 +1,139 @@
+/// The page/host message channel: `frontier-message:` links and forms,
+/// `data-frontier-message` elements and host message templates.
+use frontier_blitz_bridge::driver::{HostEvent, TestDriver};
+use frontier_blitz_bridge::messages::{self, Mode};
+use frontier_blitz_bridge::test_host;
+use serde_json::json;
+use winit::keyboard::KeyCode;
+
+const PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+        <body style="margin: 0;">
+            <a id="save" href="frontier-message:save?id=3" style="display: block; height: 30px;">Save</a>
+            <button id="like" data-frontier-message="like" data-frontier-payload='{"post": 7}'>Like</button>
+            <form id="login" action="frontier-message:login">
+                <input id="user" name="user" type="text">
+                <button id="go" type="submit">Go</button>
+            </form>
+            <div id="toast">Nothing yet</div>
+            <ul id="log"><li>first</li></ul>
+        </body>
+    </html>
+"#;
+
+fn driver() -> TestDriver {
+    test_host::reset();
+    TestDriver::new(PAGE, "https://msg.example/inbox").unwrap()
+}
+
+fn sent(driver: &TestDriver) -> Vec<HostEvent> {
+    driver
+        .host_events()
+        .into_iter()
+        .filter(|event| event.name == "frontier-message")
+        .collect()
+}
+
+#[test]
+fn message_links_report_to_the_host_instead_of_navigating() {
+    let mut driver = driver();
+
+    driver.click_selector("#save");
+
+    let sent = sent(&driver);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(
+        sent[0].payload,
+        json!({ "window_id": 0, "origin": "https://msg.example", "name": "save", "payload": { "id": "3" } })
+    );
+    assert!(test_host::navigations().is_empty());
+    assert_eq!(driver.url(), "https://msg.example/inbox");
+}
+
+#[test]
+fn data_attribute_sends_its_json_payload() {
+    let mut driver = driver();
+
+    driver.click_selector("#like");
+
+    let sent = sent(&driver);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].payload["name"], "like");
+    assert_eq!(sent[0].payload["payload"], json!({ "post": 7 }));
+}
+
+#[test]
+fn form_fields_become_the_payload() {
+    let mut driver = driver();
+    driver.click_selector("#user");
+    driver.type_text("ada");
+    driver.press(KeyCode::Enter);
+
+    let sent = sent(&driver);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].payload["name"], "login");
+    assert_eq!(sent[0].payload["payload"], json!({ "user": "ada" }));
+    assert!(test_host::navigations().is_empty());
+}
+
+#[test]
+fn host_messages_render_registered_templates() {
+    let mut driver = driver();
+    messages::register_template("test-toast", "#toast", "<b>{{user.name}}</b> says {{text}}", Mode::Replace);
+
+    let updated = driver.post_message("test-toast", &json!({ "user": { "name": "Ada" }, "text": "<hi>" }));
+
+    assert_eq!(updated, Ok(1));
+    assert_eq!(driver.text("#toast").as_deref(), Some("Ada says <hi>"));
+    assert_eq!(driver.text("#toast b").as_deref(), Some("Ada"));
+}
+
+#[test]
+fn append_and_prepend_keep_existing_content() {
+    let mut driver = driver();
+    messages::register_template("test-log-append", "#log", "<li>{{entry}}</li>", Mode::Append);
+    messages::register_template("test-log-prepend", "#log", "<li>{{entry}}</li>", Mode::Prepend);
+
+    driver.post_message("test-log-append", &json!({ "entry": "last" })).unwrap();
+    driver.post_message("test-log-prepend", &json!({ "entry": "zeroth" })).unwrap();
+
+    assert_eq!(driver.text("#log").as_deref(), Some("zerothfirstlast"));
+}
+
+#[test]
+fn messages_without_templates_change_nothing() {
+    let mut driver = driver();
+    assert_eq!(driver.post_message("test-unregistered", &json!(null)), Ok(0));
+    assert!(!messages::unregister_templates("test-unregistered"));
+}
+
+#[test]
+fn render_escapes_and_skips_missing_keys() {
+    let payload = json!({ "a": "x & y", "n": 2 });
+    assert_eq!(messages::render("{{a}}|{{ n }}|{{missing}}", &payload), "x &amp; y|2|");
+}
+
+#[test]
+fn host_can_reply_from_inside_the_message_callback() {
+    let mut driver = driver();
+    messages::register_template("test-liked", "#toast", "Liked post {{post}}", Mode::Replace);
+
+    let returned = std::rc::Rc::new(std::cell::Cell::new(None));
+    let seen = returned.clone();
+    test_host::on_event("frontier-message", move |_| {
+        let (name, payload) = ("test-liked", r#"{"post": 7}"#);
+        seen.set(Some(frontier_blitz_bridge::frontier_blitz_post_message(
+            0,
+            name.as_ptr(),
+            name.len(),
+            payload.as_ptr(),
+            payload.len(),
+        )));
+    });
+    driver.click_selector("#like");
+
+    // Delivered once the callback has returned to the loop
+    assert_eq!(returned.get(), Some(0));
+    assert_eq!(driver.text("#toast").as_deref(), Some("Liked post 7"));
+}
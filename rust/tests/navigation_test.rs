@@ -0,0 +1,122 @@
+/// Navigation and command palette flows against the in-process test host.
+use frontier_blitz_bridge::driver::TestDriver;
+use frontier_blitz_bridge::test_host::{self, HostCall};
+use winit::keyboard::KeyCode;
+
+const START: &str = r#"
+    <!DOCTYPE html>
+    <html>
+        <head><title>Start</title></head>
+        <body style="margin: 0;">
+            <a id="next" href="/next" style="display: block; width: 200px; height: 40px;">Next</a>
+        </body>
+    </html>
+"#;
+
+const NEXT: &str = "<!DOCTYPE html><html><head><title>Next</title></head><body><p id=\"here\">next page</p></body></html>";
+
+fn driver(start_url: &str) -> TestDriver {
+    test_host::reset();
+    test_host::use_temp_profile();
+    TestDriver::new(START, start_url).unwrap()
+}
+
+#[test]
+fn clicking_a_link_navigates_through_the_host() {
+    test_host::serve("https://nav.example/next", NEXT);
+    let mut driver = driver("https://nav.example/");
+
+    assert!(driver.click_selector("#next"));
+
+    assert_eq!(test_host::navigations(), vec!["https://nav.example/next".to_string()]);
+    assert_eq!(driver.url(), "https://nav.example/next");
+    assert_eq!(driver.title().as_deref(), Some("Next"));
+    assert_eq!(driver.history(), vec!["https://nav.example/", "https://nav.example/next"]);
+}
+
+#[test]
+fn back_and_forward_shortcuts_traverse_history() {
+    test_host::serve("https://history.example/", START);
+    test_host::serve("https://history.example/next", NEXT);
+    let mut driver = driver("https://history.example/");
+    driver.click_selector("#next");
+
+    driver.press_mod(KeyCode::BracketLeft);
+    assert_eq!(driver.url(), "https://history.example/");
+    assert!(driver.navigation().can_go_forward());
+
+    driver.press_mod(KeyCode::BracketRight);
+    assert_eq!(driver.url(), "https://history.example/next");
+    assert_eq!(driver.navigation().history_index(), 1);
+}
+
+#[test]
+fn command_palette_opens_typed_url() {
+    test_host::serve("https://palette.example/", NEXT);
+    let mut driver = driver("https://start.example/");
+
+    driver.press_mod(KeyCode::KeyK);
+    assert!(driver.palette().is_some());
+
+    driver.type_text("https://palette.example/");
+    driver.press(KeyCode::Enter);
+
+    assert_eq!(test_host::navigations(), vec!["https://palette.example/".to_string()]);
+    assert_eq!(driver.text("#here").as_deref(), Some("next page"));
+    assert!(driver.palette().is_none());
+}
+
+#[test]
+fn command_palette_toggles_on_repeated_shortcut() {
+    let mut driver = driver("https://toggle.example/");
+
+    driver.press_mod(KeyCode::KeyK);
+    assert!(driver.palette().is_some());
+
+    driver.press_mod(KeyCode::KeyK);
+    assert!(driver.palette().is_none());
+
+    // The bridge draws the palette itself, so the page is never swapped out
+    assert!(test_host::calls().is_empty());
+    assert_eq!(driver.url(), "https://toggle.example/");
+}
+
+#[test]
+fn host_buffers_are_freed() {
+    test_host::serve("https://memory.example/next", NEXT);
+    let mut driver = driver("https://memory.example/");
+    driver.click_selector("#next");
+    driver.press_mod(KeyCode::KeyK);
+    driver.press_mod(KeyCode::KeyK);
+    driver.press_mod(KeyCode::KeyR);
+
+    assert_eq!(test_host::outstanding_allocations(), 0);
+    let frees = test_host::calls()
+        .into_iter()
+        .filter(|call| matches!(call, HostCall::FreeHtml { .. }))
+        .count();
+    assert_eq!(frees, 2);
+    assert_eq!(test_host::bad_frees(), 0);
+}
+
+#[test]
+fn freeing_an_unknown_buffer_is_recorded() {
+    extern "C" {
+        fn frontier_free_html(ptr: *const u8, len: usize);
+    }
+    test_host::reset();
+    let bytes = [0u8; 3];
+
+    unsafe { frontier_free_html(bytes.as_ptr(), bytes.len()) };
+
+    assert_eq!(test_host::calls(), [HostCall::BadFree { len: 3 }]);
+    assert_eq!(test_host::bad_frees(), 1);
+}
+
+#[test]
+fn unknown_url_shows_error_page() {
+    let mut driver = driver("https://missing.example/");
+    driver.click_selector("#next");
+
+    assert_eq!(driver.title().as_deref(), Some("Error"));
+}
@@ -0,0 +1,164 @@
+/// The overlay stack: Escape closes the overlay that opened last, the palette
+/// backdrop dismisses it, and focus goes back where it was.
+use frontier_blitz_bridge::driver::{Input, TestDriver};
+use frontier_blitz_bridge::test_host;
+use winit::event::MouseButton;
+use winit::keyboard::KeyCode;
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Overlays</title></head>
+<body style="margin: 0">
+  <input id="name" name="name">
+  <p id="text">needle in a haystack</p>
+  <a id="cover" href="https://overlays.example/elsewhere" style="display: block; height: 400px">Cover</a>
+</body></html>"#;
+
+fn driver() -> TestDriver {
+    driver_at("https://overlays.example/")
+}
+
+fn driver_at(url: &str) -> TestDriver {
+    test_host::reset();
+    test_host::use_temp_profile();
+    TestDriver::new(PAGE, url).unwrap()
+}
+
+fn name_value(driver: &TestDriver) -> String {
+    let values = driver.form_values(None).unwrap().unwrap();
+    values["name"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn escape_closes_the_most_recent_overlay_first() {
+    let mut driver = driver();
+    driver.press_mod(KeyCode::KeyF);
+    driver.press_mod(KeyCode::KeyK);
+    assert!(driver.palette().is_some());
+
+    driver.press(KeyCode::Escape);
+    assert!(driver.palette().is_none());
+    assert_eq!(driver.find_match_count(), Some(0));
+
+    driver.press(KeyCode::Escape);
+    assert_eq!(driver.find_match_count(), None);
+}
+
+#[test]
+fn keys_go_to_the_overlay_on_top() {
+    let mut driver = driver();
+    driver.press_mod(KeyCode::KeyK);
+    driver.press_mod(KeyCode::KeyF);
+
+    driver.type_text("needle");
+
+    assert_eq!(driver.find_match_count(), Some(1));
+    assert_eq!(driver.palette().unwrap().query, "");
+}
+
+#[test]
+fn keys_skip_a_context_menu_to_reach_the_find_bar() {
+    let mut driver = driver();
+    driver.press_mod(KeyCode::KeyF);
+    driver.send(Input::MouseDown { x: 300.0, y: 100.0, button: MouseButton::Right });
+    driver.send(Input::MouseUp { x: 300.0, y: 100.0, button: MouseButton::Right });
+
+    driver.type_text("needle");
+
+    assert_eq!(driver.find_match_count(), Some(1));
+    assert_eq!(name_value(&driver), "");
+    // Escape still closes the menu first
+    driver.press(KeyCode::Escape);
+    assert!(driver.query_selector(r#"[data-frontier-overlay="context-menu"]"#).is_none());
+    assert_eq!(driver.find_match_count(), Some(1));
+}
+
+#[test]
+fn closing_a_lower_overlay_leaves_focus_alone() {
+    let page = r#"<!DOCTYPE html>
+<html><body style="margin: 0">
+  <input id="first" name="first">
+  <input id="second" name="second">
+  <p>needle</p>
+</body></html>"#;
+    test_host::reset();
+    let mut driver = TestDriver::new(page, "https://lower.overlays.example/").unwrap();
+    driver.click_selector("#first");
+    driver.press_mod(KeyCode::KeyF);
+    driver.click_selector("#second");
+    let second = driver.document().get_focussed_node_id();
+    assert_eq!(second, driver.query_selector("#second"));
+    driver.press_mod(KeyCode::KeyK);
+
+    // Patching closes the find bar, which is under the palette
+    driver.patch(page);
+    assert_eq!(driver.find_match_count(), None);
+    assert!(driver.palette().is_some());
+    assert_eq!(driver.document().get_focussed_node_id(), second);
+
+    driver.press(KeyCode::Escape);
+    assert_eq!(driver.document().get_focussed_node_id(), second);
+}
+
+#[test]
+fn focus_returns_to_the_page_after_the_palette() {
+    let mut driver = driver();
+    driver.click_selector("#name");
+    driver.type_text("a");
+    let focused = driver.document().get_focussed_node_id();
+    assert_eq!(focused, driver.query_selector("#name"));
+
+    driver.press_mod(KeyCode::KeyK);
+    driver.type_text("zzz");
+    driver.press(KeyCode::Escape);
+    driver.type_text("b");
+
+    assert_eq!(driver.document().get_focussed_node_id(), focused);
+    assert_eq!(name_value(&driver), "ab");
+}
+
+#[test]
+fn focus_returns_after_clicking_the_page_under_the_find_bar() {
+    let mut driver = driver();
+    driver.click_selector("#name");
+    let focused = driver.document().get_focussed_node_id();
+
+    driver.press_mod(KeyCode::KeyF);
+    driver.type_text("needle");
+    driver.click_selector("#text");
+    driver.press(KeyCode::Escape);
+    driver.type_text("x");
+
+    assert_eq!(driver.find_match_count(), None);
+    assert_eq!(driver.document().get_focussed_node_id(), focused);
+    assert_eq!(name_value(&driver), "x");
+}
+
+#[test]
+fn clicking_the_backdrop_dismisses_the_palette_without_clicking_the_page() {
+    let mut driver = driver();
+    driver.press_mod(KeyCode::KeyK);
+
+    // Left of the panel, over the link
+    driver.click(20.0, 200.0);
+
+    assert!(driver.palette().is_none());
+    assert!(test_host::navigations().is_empty());
+    assert_eq!(driver.url(), "https://overlays.example/");
+}
+
+#[test]
+fn clicks_inside_the_panel_keep_it_open_and_items_run() {
+    // Zoom is remembered per site, so keep it away from the other tests
+    let mut driver = driver_at("https://zoomed.overlays.example/");
+    driver.press_mod(KeyCode::KeyK);
+    driver.type_text("zoin");
+
+    // The query box, then the first (selected) item below it
+    driver.click(400.0, 95.0);
+    assert!(driver.palette().is_some());
+    driver.click(400.0, 135.0);
+
+    assert!(driver.palette().is_none());
+    assert_eq!(driver.zoom(), 1.1);
+    assert!(test_host::navigations().is_empty());
+}
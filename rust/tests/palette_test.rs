@@ -0,0 +1,129 @@
+/// The command palette: fuzzy-matched commands, bookmarks and history, with
+/// keyboard selection.
+use frontier_blitz_bridge::bookmarks::{self, ROOT};
+use frontier_blitz_bridge::driver::TestDriver;
+use frontier_blitz_bridge::palette::{self, Command};
+use frontier_blitz_bridge::test_host;
+use winit::keyboard::KeyCode;
+
+const PAGE: &str = r#"<!DOCTYPE html><html><head><title>Palette</title></head><body><p id="text">Hello</p></body></html>"#;
+
+fn driver(url: &str) -> TestDriver {
+    test_host::reset();
+    test_host::use_temp_profile();
+    TestDriver::new(PAGE, url).unwrap()
+}
+
+fn open_palette(driver: &mut TestDriver, query: &str) {
+    driver.press_mod(KeyCode::KeyK);
+    driver.type_text(query);
+}
+
+fn titles(driver: &TestDriver) -> Vec<String> {
+    driver.palette().unwrap().items().iter().map(|item| item.title.clone()).collect()
+}
+
+#[test]
+fn empty_query_lists_built_in_commands() {
+    let mut driver = driver("https://empty.palette.example/");
+    open_palette(&mut driver, "");
+
+    let titles = titles(&driver);
+    assert_eq!(titles[..3], ["Back", "Forward", "Reload"]);
+    assert_eq!(driver.palette().unwrap().selected().unwrap().title, "Back");
+}
+
+#[test]
+fn typing_fuzzy_matches_and_enter_runs_the_best_match() {
+    let mut driver = driver("https://zoom-in.palette.example/");
+    open_palette(&mut driver, "zoin");
+    assert_eq!(titles(&driver)[0], "Zoom In");
+
+    driver.press(KeyCode::Enter);
+
+    assert!(driver.palette().is_none());
+    assert_eq!(driver.zoom(), 1.1);
+}
+
+#[test]
+fn arrow_keys_move_the_selection() {
+    let mut driver = driver("https://zoom-out.palette.example/");
+    open_palette(&mut driver, "zoom");
+    assert_eq!(titles(&driver)[..2], ["Zoom In", "Zoom Out"]);
+
+    driver.press(KeyCode::ArrowDown);
+    driver.press(KeyCode::ArrowDown);
+    driver.press(KeyCode::ArrowUp);
+    assert_eq!(driver.palette().unwrap().selected().unwrap().title, "Zoom Out");
+
+    driver.press(KeyCode::Enter);
+    assert!(driver.zoom() < 1.0);
+}
+
+#[test]
+fn host_commands_match_keywords_and_report_back() {
+    let mut driver = driver("https://host.palette.example/");
+    palette::register_command("test-deploy", "Deploy Preview", &["ship".to_string()], Some("Ctrl+Shift+D"));
+
+    open_palette(&mut driver, "ship");
+    let selected = driver.palette().unwrap().selected().unwrap().clone();
+    assert_eq!(selected.title, "Deploy Preview");
+    assert_eq!(selected.detail, "Ctrl+Shift+D");
+    driver.press(KeyCode::Enter);
+
+    let ran: Vec<_> = driver
+        .host_events()
+        .into_iter()
+        .filter(|event| event.name == "palette-command")
+        .collect();
+    assert_eq!(ran.len(), 1);
+    assert_eq!(ran[0].payload["id"], "test-deploy");
+    assert_eq!(ran[0].payload["page_url"], "https://host.palette.example/");
+    assert!(palette::unregister_command("test-deploy"));
+}
+
+#[test]
+fn bookmarks_and_history_are_offered_once_and_open_on_enter() {
+    let url = "https://pages.palette.example/handbook";
+    test_host::serve(url, "<!DOCTYPE html><html><head><title>Handbook</title></head><body>Read me</body></html>");
+    let mut driver = driver("https://pages.palette.example/");
+    driver.load(url);
+    driver.load("https://pages.palette.example/");
+    bookmarks::add(ROOT, "Team handbook", url, &[]).unwrap();
+
+    open_palette(&mut driver, "handbook");
+
+    let pages: Vec<_> = driver
+        .palette()
+        .unwrap()
+        .items()
+        .iter()
+        .filter(|item| item.command == Command::Open(url.to_string()))
+        .cloned()
+        .collect();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].title, "Team handbook");
+
+    driver.press(KeyCode::Enter);
+    assert_eq!(driver.url(), url);
+}
+
+#[test]
+fn escape_closes_without_touching_the_page() {
+    let mut driver = driver("https://escape.palette.example/");
+    open_palette(&mut driver, "rel");
+
+    driver.press(KeyCode::Escape);
+
+    assert!(driver.palette().is_none());
+    assert_eq!(driver.text("#text").as_deref(), Some("Hello"));
+    assert!(test_host::navigations().is_empty());
+}
+
+#[test]
+fn fuzzy_score_prefers_prefixes_and_word_starts() {
+    assert_eq!(palette::fuzzy_score("", "anything"), Some(0));
+    assert_eq!(palette::fuzzy_score("xyz", "Reload"), None);
+    assert!(palette::fuzzy_score("rel", "Reload") > palette::fuzzy_score("rel", "Preload"));
+    assert!(palette::fuzzy_score("zi", "Zoom In") > palette::fuzzy_score("zi", "Zoom dial"));
+}
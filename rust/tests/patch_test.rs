@@ -0,0 +1,109 @@
+/// Incremental document patching, as used by `frontier_blitz_patch_document`.
+use frontier_blitz_bridge::driver::TestDriver;
+
+fn page(body: &str) -> String {
+    format!("<!DOCTYPE html><html><head><title>Patch</title></head><body>{body}</body></html>")
+}
+
+fn input_value(driver: &TestDriver, selector: &str) -> Option<String> {
+    let node_id = driver.query_selector(selector)?;
+    let element = driver.document().get_node(node_id)?.element_data()?;
+    Some(element.text_input_data()?.editor.text().to_string())
+}
+
+#[test]
+fn identical_html_changes_nothing() {
+    let html = page(r#"<h1 id="title">Hello</h1><p>Body</p>"#);
+    let mut driver = TestDriver::new(&html, "https://patch.example/").unwrap();
+
+    let stats = driver.patch(&html);
+
+    assert_eq!(stats.total(), 0);
+}
+
+#[test]
+fn text_and_attribute_changes_keep_element_identity() {
+    let mut driver = TestDriver::new(&page(r#"<h1 id="title" class="a">Hello</h1>"#), "https://patch.example/").unwrap();
+    let before = driver.query_selector("#title");
+
+    let stats = driver.patch(&page(r#"<h1 id="title" class="b">Goodbye</h1>"#));
+
+    assert_eq!(driver.query_selector("#title"), before);
+    assert_eq!(driver.text("#title").as_deref(), Some("Goodbye"));
+    assert!(driver.query_selector(".b").is_some());
+    assert_eq!((stats.text, stats.attributes, stats.inserted, stats.removed), (1, 1, 0, 0));
+}
+
+#[test]
+fn keyed_children_are_inserted_and_removed_in_place() {
+    let mut driver = TestDriver::new(
+        &page(r#"<ul><li id="a">A</li><li id="b">B</li><li id="c">C</li></ul>"#),
+        "https://patch.example/",
+    )
+    .unwrap();
+    let c = driver.query_selector("#c");
+
+    driver.patch(&page(r#"<ul><li id="a">A</li><li id="c">C</li><li id="d">D</li></ul>"#));
+
+    assert!(driver.query_selector("#b").is_none());
+    assert_eq!(driver.query_selector("#c"), c);
+    assert_eq!(driver.text("ul").as_deref(), Some("ACD"));
+}
+
+#[test]
+fn removing_an_unkeyed_child_keeps_the_siblings_after_it() {
+    let mut driver = TestDriver::new(
+        &page(r#"<div><h2>Notice</h2><p class="one">One</p><p class="two">Two</p></div>"#),
+        "https://patch.example/",
+    )
+    .unwrap();
+    let one = driver.query_selector(".one");
+    let two = driver.query_selector(".two");
+
+    let stats = driver.patch(&page(r#"<div><p class="one">One</p><p class="two">Two</p></div>"#));
+
+    assert!(driver.query_selector("h2").is_none());
+    assert_eq!(driver.query_selector(".one"), one);
+    assert_eq!(driver.query_selector(".two"), two);
+    assert_eq!((stats.inserted, stats.removed), (0, 1));
+}
+
+#[test]
+fn inserting_an_unkeyed_child_keeps_the_siblings_after_it() {
+    let mut driver = TestDriver::new(&page(r#"<div><p class="one">One</p></div>"#), "https://patch.example/").unwrap();
+    let one = driver.query_selector(".one");
+
+    let stats = driver.patch(&page(r#"<div><h2>Notice</h2><p class="one">One</p></div>"#));
+
+    assert_eq!(driver.query_selector(".one"), one);
+    assert_eq!(driver.text("div").as_deref(), Some("NoticeOne"));
+    assert_eq!((stats.inserted, stats.removed), (1, 0));
+}
+
+#[test]
+fn focus_and_typed_value_survive_a_patch() {
+    let form = |status: &str| page(&format!(r#"<p id="status">{status}</p><input id="name" type="text">"#));
+    let mut driver = TestDriver::new(&form("Empty"), "https://patch.example/").unwrap();
+    driver.click_selector("#name");
+    driver.type_text("Ada");
+    let focused = driver.document().get_focussed_node_id();
+
+    driver.patch(&form("Typing"));
+
+    assert_eq!(driver.text("#status").as_deref(), Some("Typing"));
+    assert_eq!(driver.document().get_focussed_node_id(), focused);
+    assert_eq!(input_value(&driver, "#name").as_deref(), Some("Ada"));
+}
+
+#[test]
+fn patch_updates_the_stored_html_but_not_the_url() {
+    let mut driver = TestDriver::new(&page("<p>One</p>"), "https://patch.example/").unwrap();
+    let generation = driver.generation();
+    let updated = page("<p>Two</p>");
+
+    driver.patch(&updated);
+
+    assert_eq!(driver.navigation().current_html(), updated);
+    assert_eq!(driver.url(), "https://patch.example/");
+    assert_eq!(driver.generation(), generation);
+}
@@ -0,0 +1,57 @@
+/// Runs every scenario in rust/scenarios, the same files `frontier-scenario` runs.
+use std::path::Path;
+
+use frontier_blitz_bridge::scenario;
+
+#[test]
+fn checked_in_scenarios_pass() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+    let mut paths: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no scenarios in {}", dir.display());
+
+    let mut failed = Vec::new();
+    for path in &paths {
+        let report = scenario::run_file(path).unwrap_or_else(|err| panic!("{err}"));
+        println!("{report}");
+        if !report.passed() {
+            failed.push(report.name);
+        }
+    }
+    assert!(failed.is_empty(), "failed scenarios: {failed:?}");
+}
+
+#[test]
+fn failing_step_skips_the_rest() {
+    let scenario: scenario::Scenario = toml::from_str(
+        r#"
+        name = "failing"
+
+        [pages]
+        "https://fail.test/" = { html = "<title>Fail</title>" }
+
+        [[step]]
+        action = "load"
+        url = "https://fail.test/"
+
+        [[step]]
+        action = "click"
+        selector = "#missing"
+
+        [[step]]
+        action = "assert_title"
+        equals = "Fail"
+        "#,
+    )
+    .unwrap();
+
+    let report = scenario::run(&scenario, Path::new(".")).unwrap();
+    assert!(!report.passed());
+    assert!(matches!(report.steps[0].outcome, scenario::StepOutcome::Passed));
+    assert!(matches!(&report.steps[1].outcome, scenario::StepOutcome::Failed(reason) if reason.contains("#missing")));
+    assert!(matches!(report.steps[2].outcome, scenario::StepOutcome::Skipped));
+}
@@ -0,0 +1,65 @@
+/// Script blocks are reported to the host with each loaded document.
+use frontier_blitz_bridge::driver::{HostEvent, TestDriver};
+use frontier_blitz_bridge::scripts;
+use frontier_blitz_bridge::test_host;
+
+const PAGE: &str = r#"
+    <!DOCTYPE html>
+    <html>
+        <head>
+            <script type="text/typescript" src="lib/util.ts" defer></script>
+            <script>console.log("javascript is ignored")</script>
+        </head>
+        <body>
+            <script type="text/TypeScript" id="main">const answer: number = 42;</script>
+        </body>
+    </html>
+"#;
+
+fn loaded_events(driver: &TestDriver) -> Vec<HostEvent> {
+    driver
+        .host_events()
+        .into_iter()
+        .filter(|event| event.name == "document-loaded")
+        .collect()
+}
+
+#[test]
+fn typescript_blocks_are_collected_in_document_order() {
+    let driver = TestDriver::new(PAGE, "https://scripts.example/app/").unwrap();
+    let found = scripts::collect(driver.document(), "https://scripts.example/app/", &["text/typescript".to_string()]);
+
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].src.as_deref(), Some("https://scripts.example/app/lib/util.ts"));
+    assert_eq!(found[0].source, "");
+    assert!(found[0].attributes.contains_key("defer"));
+    assert_eq!(found[1].index, 1);
+    assert_eq!(found[1].kind, "text/typescript");
+    assert_eq!(found[1].source, "const answer: number = 42;");
+    assert_eq!(found[1].attributes.get("id").map(String::as_str), Some("main"));
+}
+
+#[test]
+fn load_event_carries_scripts_and_generation() {
+    let driver = TestDriver::new(PAGE, "https://scripts.example/").unwrap();
+    let events = loaded_events(&driver);
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].payload["generation"], driver.generation());
+    assert_eq!(events[0].payload["scripts"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn each_navigation_gets_a_new_generation() {
+    test_host::serve("https://scripts.example/next", "<html><body>no scripts</body></html>");
+    let mut driver = TestDriver::new(PAGE, "https://scripts.example/").unwrap();
+    let first = driver.generation();
+
+    driver.load("https://scripts.example/next");
+    assert!(driver.generation() > first);
+
+    let events = loaded_events(&driver);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].payload["url"], "https://scripts.example/next");
+    assert!(events[1].payload["scripts"].as_array().unwrap().is_empty());
+}
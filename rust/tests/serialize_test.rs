@@ -0,0 +1,90 @@
+/// Live DOM serialization, as used by `frontier_blitz_serialize_dom`.
+use frontier_blitz_bridge::dom_edit::Edit;
+use frontier_blitz_bridge::driver::TestDriver;
+use winit::keyboard::KeyCode;
+
+const PAGE: &str = r#"<!DOCTYPE html><html><head><title>Save</title><style>p > b { color: red; }</style></head><body><p id="greeting" class="hello">Fish &amp; chips &lt;3</p><input id="name" type="text"><input id="agree" type="checkbox"><br></body></html>"#;
+
+fn driver() -> TestDriver {
+    TestDriver::new(PAGE, "https://save.example/").unwrap()
+}
+
+#[test]
+fn element_outer_html_escapes_text() {
+    let driver = driver();
+    assert_eq!(
+        driver.html(Some("#greeting")).as_deref(),
+        Some(r#"<p id="greeting" class="hello">Fish &amp; chips &lt;3</p>"#)
+    );
+}
+
+#[test]
+fn document_round_trips_through_the_parser() {
+    let driver = driver();
+    let html = driver.html(None).unwrap();
+
+    assert!(html.starts_with("<!DOCTYPE html><html><head><title>Save</title>"));
+    // Raw text elements aren't escaped
+    assert!(html.contains("<style>p > b { color: red; }</style>"));
+    assert!(html.contains("<br></body>"));
+
+    let reloaded = TestDriver::new(&html, "https://save.example/").unwrap();
+    assert_eq!(reloaded.html(None).unwrap(), html);
+}
+
+#[test]
+fn host_edits_are_included() {
+    let mut driver = driver();
+    driver.edit("#greeting", &Edit::SetText("Edited".into())).unwrap();
+    driver.edit("#greeting", &Edit::AddClass("changed".into())).unwrap();
+
+    assert_eq!(
+        driver.html(Some("#greeting")).as_deref(),
+        Some(r#"<p id="greeting" class="hello changed">Edited</p>"#)
+    );
+}
+
+#[test]
+fn current_form_values_are_written() {
+    let mut driver = driver();
+    driver.click_selector("#name");
+    driver.type_text("new");
+    driver.click_selector("#agree");
+
+    assert_eq!(
+        driver.html(Some("#name")).as_deref(),
+        Some(r#"<input id="name" type="text" value="new">"#)
+    );
+    assert_eq!(
+        driver.html(Some("#agree")).as_deref(),
+        Some(r#"<input id="agree" type="checkbox" checked>"#)
+    );
+}
+
+#[test]
+fn overlays_are_left_out() {
+    let mut driver = driver();
+    driver.press_mod(KeyCode::KeyF);
+
+    assert!(!driver.html(None).unwrap().contains("data-frontier-overlay"));
+}
+
+#[test]
+fn unmatched_selector_returns_nothing() {
+    assert_eq!(driver().html(Some("#missing")), None);
+}
+
+#[test]
+fn find_highlights_are_unwrapped() {
+    let mut driver = driver();
+    let before = driver.html(None).unwrap();
+    driver.press_mod(KeyCode::KeyF);
+    driver.type_text("chips");
+    assert_eq!(driver.find_match_count(), Some(1));
+
+    assert_eq!(
+        driver.html(Some("#greeting")).as_deref(),
+        Some(r#"<p id="greeting" class="hello">Fish &amp; chips &lt;3</p>"#)
+    );
+    assert_eq!(driver.html(None).unwrap(), before);
+}
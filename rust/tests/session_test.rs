@@ -0,0 +1,143 @@
+/// Session snapshots and restoring them, as used by `frontier_blitz_save_session`
+/// and restore on launch.
+use frontier_blitz_bridge::driver::{Input, TestDriver};
+use frontier_blitz_bridge::session::{self, Session, TabSnapshot, WindowSnapshot};
+use frontier_blitz_bridge::test_host;
+use winit::keyboard::KeyCode;
+
+const START: &str = r#"
+    <!DOCTYPE html>
+    <html>
+        <head><title>Start</title></head>
+        <body style="margin: 0;">
+            <a id="next" href="/long" style="display: block; height: 40px;">Long page</a>
+        </body>
+    </html>
+"#;
+
+const LONG: &str = r#"
+    <!DOCTYPE html>
+    <html>
+        <head><title>Long</title></head>
+        <body style="margin: 0;"><div style="height: 5000px;">Long</div></body>
+    </html>
+"#;
+
+fn browsed(origin: &str) -> TestDriver {
+    test_host::reset();
+    test_host::use_temp_profile();
+    test_host::serve(&format!("{origin}/"), START);
+    test_host::serve(&format!("{origin}/long"), LONG);
+    let mut driver = TestDriver::new(START, &format!("{origin}/")).unwrap();
+    driver.click_selector("#next");
+    driver.send(Input::Wheel { lines: -10.0 });
+    driver
+}
+
+#[test]
+fn snapshot_records_history_and_scroll() {
+    let driver = browsed("https://snapshot.example");
+
+    let session = driver.session();
+
+    assert_eq!(session.windows.len(), 1);
+    let tab = &session.windows[0].tabs[0];
+    assert_eq!(tab.history, vec!["https://snapshot.example/", "https://snapshot.example/long"]);
+    assert_eq!(tab.history_index, 1);
+    assert_eq!(tab.scroll, [0.0, 200.0]);
+    assert_eq!(tab.zoom, 1.0);
+}
+
+#[test]
+fn restored_window_refetches_pages_and_keeps_history() {
+    let before = browsed("https://restore.example");
+    let snapshot = before.session().windows.remove(0);
+
+    let restored = TestDriver::restore(&snapshot).unwrap().unwrap();
+
+    assert_eq!(restored.url(), "https://restore.example/long");
+    assert_eq!(restored.title().as_deref(), Some("Long"));
+    assert_eq!(restored.history(), before.history());
+    assert!(restored.navigation().can_go_back());
+    assert_eq!(restored.scroll(), (0.0, 200.0));
+}
+
+#[test]
+fn window_without_tabs_is_not_restored() {
+    let snapshot = WindowSnapshot {
+        tabs: vec![TabSnapshot {
+            history: Vec::new(),
+            history_index: 0,
+            scroll: [0.0, 0.0],
+            zoom: 1.0,
+        }],
+        active_tab: 0,
+    };
+    assert!(TestDriver::restore(&snapshot).unwrap().is_none());
+}
+
+#[test]
+fn session_file_round_trips() {
+    let profile = test_host::use_temp_profile().join("profile");
+    let saved = browsed("https://file.example").session();
+
+    session::save(&saved).unwrap();
+
+    assert_eq!(session::load(), Some(saved));
+    assert!(profile.join(session::FILE_NAME).exists());
+    assert!(!profile.join(format!("{}.tmp", session::FILE_NAME)).exists());
+}
+
+#[test]
+fn missing_scroll_and_zoom_use_defaults() {
+    let json = r#"{ "version": 1, "windows": [{ "active_tab": 0, "tabs": [{ "history": ["https://a.example/"], "history_index": 0 }] }] }"#;
+    let session: Session = serde_json::from_str(json).unwrap();
+    assert_eq!(session.windows[0].tabs[0].scroll, [0.0, 0.0]);
+    assert_eq!(session.windows[0].tabs[0].zoom, 1.0);
+}
+
+#[test]
+fn background_tabs_are_fetched_when_first_shown() {
+    test_host::reset();
+    test_host::use_temp_profile();
+    test_host::serve("https://lazy.example/", START);
+    test_host::serve("https://lazy.example/long", LONG);
+    let tab = |url: &str| TabSnapshot {
+        history: vec![url.to_string()],
+        history_index: 0,
+        scroll: [0.0, 0.0],
+        zoom: 1.0,
+    };
+    let snapshot = WindowSnapshot {
+        tabs: vec![tab("https://lazy.example/"), tab("https://lazy.example/long")],
+        active_tab: 0,
+    };
+
+    let mut restored = TestDriver::restore(&snapshot).unwrap().unwrap();
+
+    assert_eq!(test_host::navigations(), ["https://lazy.example/"]);
+    restored.press_mod(KeyCode::Tab);
+    assert_eq!(restored.title().as_deref(), Some("Long"));
+    assert_eq!(test_host::navigations(), ["https://lazy.example/", "https://lazy.example/long"]);
+    restored.press_mod(KeyCode::Tab);
+    assert_eq!(test_host::navigations().len(), 2);
+}
+
+#[test]
+fn only_windows_with_history_count_as_restorable() {
+    let empty = WindowSnapshot {
+        tabs: Vec::new(),
+        active_tab: 0,
+    };
+    let window = WindowSnapshot {
+        tabs: vec![TabSnapshot {
+            history: vec!["https://a.example/".to_string()],
+            history_index: 0,
+            scroll: [0.0, 0.0],
+            zoom: 1.0,
+        }],
+        active_tab: 0,
+    };
+    assert!(!empty.is_restorable());
+    assert!(window.is_restorable());
+}
@@ -0,0 +1,237 @@
+/// Visual regression tests: render fixture pages (and the command palette
+/// over one, through `TestDriver`) headlessly and compare them against the
+/// golden PNGs checked in under tests/golden.
+///
+/// After an intended rendering change, re-record the goldens with
+///
+///     FRONTIER_BLESS=1 cargo test --test visual_regression
+///
+/// On a mismatch the rendered image and a diff (changed pixels in red over a
+/// faded copy of the golden) are written to target/visual-diffs.
+
+use std::path::{Path, PathBuf};
+
+use frontier_blitz_bridge::driver::TestDriver;
+use frontier_blitz_bridge::headless::{self, RenderedImage};
+use winit::keyboard::KeyCode;
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 600;
+const SCALE: f64 = 1.0;
+
+// Per-pixel colour distance (0..1, YIQ weighted as in pixelmatch) below which
+// two pixels count as equal. Absorbs anti-aliasing differences.
+const PIXEL_THRESHOLD: f64 = 0.1;
+// Fraction of pixels allowed to differ before the test fails
+const MAX_DIFF_RATIO: f64 = 0.002;
+
+// Largest possible YIQ delta, used to normalise PIXEL_THRESHOLD
+const MAX_YIQ_DELTA: f64 = 35215.0;
+
+fn manifest_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn blessing() -> bool {
+    std::env::var_os("FRONTIER_BLESS").is_some_and(|value| value != "0")
+}
+
+fn fixture(path: &str) -> (String, String) {
+    let path = manifest_dir().join(path);
+    let html = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("failed to read fixture {}: {err}", path.display()));
+    let url = format!("file://{}", path.canonicalize().unwrap_or(path).display());
+    (html, url)
+}
+
+fn decode_png(path: &Path) -> Option<RenderedImage> {
+    let file = std::fs::File::open(path).ok()?;
+    Some(decode(file).unwrap_or_else(|| panic!("{} is not an 8-bit RGBA PNG", path.display())))
+}
+
+fn decode(source: impl std::io::Read) -> Option<RenderedImage> {
+    let mut decoder = png::Decoder::new(source);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::ALPHA);
+    let mut reader = decoder.read_info().ok()?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).ok()?;
+    pixels.truncate(info.buffer_size());
+
+    if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+        return None;
+    }
+    Some(RenderedImage {
+        width: info.width,
+        height: info.height,
+        pixels,
+    })
+}
+
+fn write_png(path: &Path, image: &RenderedImage) {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).unwrap();
+    }
+    let bytes = headless::encode_png(image).expect("failed to encode PNG");
+    std::fs::write(path, bytes).unwrap_or_else(|err| panic!("failed to write {}: {err}", path.display()));
+}
+
+/// RGB of an RGBA pixel composited over white.
+fn blend(pixel: &[u8]) -> (f64, f64, f64) {
+    let alpha = pixel[3] as f64 / 255.0;
+    let over_white = |channel: u8| 255.0 + (channel as f64 - 255.0) * alpha;
+    (over_white(pixel[0]), over_white(pixel[1]), over_white(pixel[2]))
+}
+
+fn yiq_delta(a: &[u8], b: &[u8]) -> f64 {
+    let (r1, g1, b1) = blend(a);
+    let (r2, g2, b2) = blend(b);
+    let y = |r: f64, g: f64, b: f64| r * 0.298_895_31 + g * 0.586_622_47 + b * 0.114_482_23;
+    let i = |r: f64, g: f64, b: f64| r * 0.595_977_99 - g * 0.274_176_10 - b * 0.321_801_89;
+    let q = |r: f64, g: f64, b: f64| r * 0.211_470_17 - g * 0.522_617_11 + b * 0.311_146_94;
+
+    let dy = y(r1, g1, b1) - y(r2, g2, b2);
+    let di = i(r1, g1, b1) - i(r2, g2, b2);
+    let dq = q(r1, g1, b1) - q(r2, g2, b2);
+    0.5053 * dy * dy + 0.299 * di * di + 0.1957 * dq * dq
+}
+
+/// Count differing pixels and build a diff image.
+fn compare(expected: &RenderedImage, actual: &RenderedImage) -> (usize, RenderedImage) {
+    let limit = MAX_YIQ_DELTA * PIXEL_THRESHOLD * PIXEL_THRESHOLD;
+    let mut diff = Vec::with_capacity(expected.pixels.len());
+    let mut differing = 0;
+
+    for (a, b) in expected.pixels.chunks_exact(4).zip(actual.pixels.chunks_exact(4)) {
+        if yiq_delta(a, b) > limit {
+            differing += 1;
+            diff.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let (r, g, b) = blend(a);
+            let gray = (r * 0.299 + g * 0.587 + b * 0.114) as u8;
+            let faded = 255 - (255 - gray) / 4;
+            diff.extend_from_slice(&[faded, faded, faded, 255]);
+        }
+    }
+
+    let image = RenderedImage {
+        width: expected.width,
+        height: expected.height,
+        pixels: diff,
+    };
+    (differing, image)
+}
+
+/// Render a fixture page as it loads, with no bridge UI.
+fn render_fixture(fixture_path: &str) -> RenderedImage {
+    let (html, url) = fixture(fixture_path);
+    headless::render(&html, &url, WIDTH, HEIGHT, SCALE).unwrap_or_else(|err| panic!("failed to render {fixture_path}: {err}"))
+}
+
+fn assert_matches_golden(name: &str, fixture_path: &str) {
+    assert_image_matches_golden(name, render_fixture(fixture_path));
+}
+
+fn assert_image_matches_golden(name: &str, actual: RenderedImage) {
+    let golden_path = manifest_dir().join("tests/golden").join(format!("{name}.png"));
+    if blessing() {
+        write_png(&golden_path, &actual);
+        eprintln!("blessed {}", golden_path.display());
+        return;
+    }
+
+    let Some(expected) = decode_png(&golden_path) else {
+        panic!(
+            "missing golden {}; run with FRONTIER_BLESS=1 to record it",
+            golden_path.display()
+        );
+    };
+
+    let out_dir = manifest_dir().join("target/visual-diffs");
+    let actual_path = out_dir.join(format!("{name}.actual.png"));
+
+    if (expected.width, expected.height) != (actual.width, actual.height) {
+        write_png(&actual_path, &actual);
+        panic!(
+            "{name}: golden is {}x{} but render is {}x{}; actual written to {}",
+            expected.width,
+            expected.height,
+            actual.width,
+            actual.height,
+            actual_path.display()
+        );
+    }
+
+    let (differing, diff) = compare(&expected, &actual);
+    let total = (actual.width * actual.height) as usize;
+    let ratio = differing as f64 / total as f64;
+    if ratio > MAX_DIFF_RATIO {
+        let diff_path = out_dir.join(format!("{name}.diff.png"));
+        write_png(&actual_path, &actual);
+        write_png(&diff_path, &diff);
+        panic!(
+            "{name}: {differing} of {total} pixels differ ({:.3}%, limit {:.3}%); see {} and {}. \
+             If the change is intended, re-run with FRONTIER_BLESS=1",
+            ratio * 100.0,
+            MAX_DIFF_RATIO * 100.0,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+#[test]
+fn test_page_matches_golden() {
+    assert_matches_golden("test_page", "../assets/test.html");
+}
+
+// The palette overlay the bridge draws over a page, opened with Mod+K like a
+// user would
+#[test]
+fn command_palette_matches_golden() {
+    let (html, url) = fixture("../assets/test.html");
+    let mut driver = TestDriver::with_viewport(&html, &url, WIDTH, HEIGHT, SCALE).unwrap();
+    driver.press_mod(KeyCode::KeyK);
+    assert!(driver.palette().is_some());
+
+    let png = driver.screenshot_png().expect("failed to render the palette");
+    let actual = decode(png.as_slice()).expect("the driver's screenshot is an 8-bit RGBA PNG");
+    assert_image_matches_golden("command_palette", actual);
+}
+
+#[test]
+fn modal_demo_hidden_matches_golden() {
+    assert_matches_golden("modal_demo_hidden", "tests/fixtures/modal_demo_hidden.html");
+}
+
+#[test]
+fn modal_demo_visible_matches_golden() {
+    assert_matches_golden("modal_demo_visible", "tests/fixtures/modal_demo_visible.html");
+}
+
+#[test]
+fn identical_images_have_no_diff() {
+    let image = RenderedImage {
+        width: 2,
+        height: 1,
+        pixels: vec![10, 20, 30, 255, 200, 100, 50, 255],
+    };
+    let (differing, _) = compare(&image, &image);
+    assert_eq!(differing, 0);
+}
+
+#[test]
+fn small_colour_shift_is_tolerated_but_large_change_is_not() {
+    let expected = RenderedImage {
+        width: 2,
+        height: 1,
+        pixels: vec![100, 100, 100, 255, 255, 255, 255, 255],
+    };
+    let actual = RenderedImage {
+        width: 2,
+        height: 1,
+        pixels: vec![102, 101, 100, 255, 0, 0, 0, 255],
+    };
+    let (differing, diff) = compare(&expected, &actual);
+    assert_eq!(differing, 1);
+    assert_eq!(&diff.pixels[4..8], &[255, 0, 0, 255]);
+}
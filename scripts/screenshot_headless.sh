@@ -0,0 +1,28 @@
+#!/bin/bash
+set -e
+
+# Headless counterpart to screenshot_app.sh: no display or GPU required.
+# Usage: scripts/screenshot_headless.sh [url] [output.png]
+
+URL="${1:-}"
+OUTPUT="${2:-/tmp/frontier_zig_headless.png}"
+
+echo "Building frontier-zig..."
+zig build --build-file zig/build.zig
+
+echo "Rendering ${URL:-navigator page} offscreen..."
+if [ -n "$URL" ]; then
+    zig/zig-out/bin/frontier-zig --screenshot "$OUTPUT" "$URL"
+else
+    zig/zig-out/bin/frontier-zig --screenshot "$OUTPUT"
+fi
+
+if [ -f "$OUTPUT" ]; then
+    FILESIZE=$(ls -lh "$OUTPUT" | awk '{print $5}')
+    echo ""
+    echo "✓ Screenshot: $OUTPUT"
+    echo "  File size: $FILESIZE"
+else
+    echo "✗ Screenshot failed"
+    exit 1
+fi
@@ -0,0 +1,10 @@
+const std = @import("std");
+
+/// Called by Rust whenever the bridge has something to report (downloads, etc).
+/// `payload` is a JSON object whose shape depends on `name`.
+/// May be called from Rust worker threads, so this must stay thread-safe.
+export fn frontier_host_event(name_ptr: [*]const u8, name_len: usize, payload_ptr: [*]const u8, payload_len: usize) void {
+    const name = name_ptr[0..name_len];
+    const payload = payload_ptr[0..payload_len];
+    std.log.info("Host event {s}: {s}", .{ name, payload });
+}
@@ -2,11 +2,26 @@ const std = @import("std");
 const navigation = @import("navigation.zig");
 const command_palette = @import("command_palette.zig");
 const shortcuts = @import("shortcuts.zig");
+const host_events = @import("host_events.zig");
+
+comptime {
+    // Make sure the exported host callbacks are linked in
+    _ = host_events;
+}
 
 extern fn frontier_blitz_run_static_html(html_ptr: [*]const u8, len: usize) callconv(.c) bool;
 extern fn frontier_blitz_navigate(html_ptr: [*]const u8, html_len: usize, url_ptr: [*]const u8, url_len: usize) callconv(.c) bool;
+extern fn frontier_blitz_set_download_dir(path_ptr: [*]const u8, path_len: usize) callconv(.c) bool;
+extern fn frontier_blitz_render_to_png(html_ptr: [*]const u8, html_len: usize, url_ptr: [*]const u8, url_len: usize, width: u32, height: u32, scale: f64) callconv(.c) BufferResult;
+extern fn frontier_blitz_free_buffer(ptr: ?[*]u8, len: usize) callconv(.c) void;
 extern fn frontier_blitz_update_document(html_ptr: [*]const u8, html_len: usize, url_ptr: [*]const u8, url_len: usize) callconv(.c) bool;
 
+// Rust-owned bytes, released with frontier_blitz_free_buffer()
+const BufferResult = extern struct {
+    ptr: ?[*]u8,
+    len: usize,
+};
+
 const DEMO_HTML =
     \\<!DOCTYPE html>
     \\<html lang="en">
@@ -163,7 +178,20 @@ pub fn main() !void {
     // Skip executable name
     _ = args.next();
 
-    const url = args.next();
+    const first_arg = args.next();
+
+    // Headless mode: frontier-zig --screenshot <out.png> [url]
+    if (first_arg) |arg| {
+        if (std.mem.eql(u8, arg, "--screenshot")) {
+            const output_path = args.next() orelse {
+                std.log.err("Usage: frontier-zig --screenshot <out.png> [url]", .{});
+                return error.MissingScreenshotPath;
+            };
+            return renderScreenshot(allocator, output_path, args.next());
+        }
+    }
+
+    const url = first_arg;
 
     if (url) |target_url| {
         // User provided a URL on command line
@@ -210,6 +238,28 @@ pub fn main() !void {
     }
 }
 
+/// Render a page offscreen through the Rust bridge and write it as a PNG.
+/// Without a URL, renders the same navigator page `just run` shows.
+fn renderScreenshot(allocator: std.mem.Allocator, output_path: []const u8, url: ?[]const u8) !void {
+    const html = if (url) |target_url|
+        try navigation.fetchUrl(allocator, target_url)
+    else
+        try command_palette.generateCommandPaletteHtml(allocator, null);
+    defer allocator.free(html);
+
+    const page_url = url orelse "http://localhost/";
+    const png = frontier_blitz_render_to_png(html.ptr, html.len, page_url.ptr, page_url.len, 1280, 800, 1.0);
+    defer frontier_blitz_free_buffer(png.ptr, png.len);
+
+    const png_ptr = png.ptr orelse {
+        std.log.err("Headless render failed—see Rust-side logs for details", .{});
+        return error.BlitzBridgeFailed;
+    };
+
+    try std.fs.cwd().writeFile(.{ .sub_path = output_path, .data = png_ptr[0..png.len] });
+    std.log.info("Wrote {d} byte screenshot to {s}", .{ png.len, output_path });
+}
+
 test "builtin sanity" {
     try std.testing.expectEqual(@as(u8, 1), 1);
 }